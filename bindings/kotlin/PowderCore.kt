@@ -0,0 +1,143 @@
+// PowderCore - Kotlin/JVM bindings
+//
+// Build the native side with `cargo build --release --features jni` and put
+// libpowdercore.so / powdercore.dll / libpowdercore.dylib on
+// java.library.path (or jniLibs/<abi>/ on Android).
+//
+//   World(200, 120, seed = 1).use { w ->
+//       w.placeBrush(100, 10, 4, Element.SAND)
+//       w.step()
+//       val pixels = ByteBuffer.allocateDirect(w.width * w.height * 4)
+//       w.renderRgba(pixels)
+//   }
+
+package com.powdercore
+
+import java.nio.ByteBuffer
+import java.nio.ByteOrder
+
+/** Raw native methods (see src/jni.rs). Status codes match PowderStatus. */
+object PowderCore {
+    init {
+        System.loadLibrary("powdercore")
+    }
+
+    const val STATUS_OK = 1
+
+    @JvmStatic external fun worldNew(width: Int, height: Int, seed: Long): Long
+    @JvmStatic external fun worldNewThreadsafe(width: Int, height: Int, seed: Long): Long
+    @JvmStatic external fun worldFree(handle: Long)
+    @JvmStatic external fun worldStep(handle: Long): Int
+    @JvmStatic external fun worldClear(handle: Long): Int
+    @JvmStatic external fun worldResize(handle: Long, width: Int, height: Int): Int
+    @JvmStatic external fun worldWidth(handle: Long): Int
+    @JvmStatic external fun worldHeight(handle: Long): Int
+    @JvmStatic external fun worldPlaceBrush(handle: Long, cx: Int, cy: Int, rad: Int, elem: Int): Int
+    @JvmStatic external fun worldGetCell(handle: Long, x: Int, y: Int): Long
+    @JvmStatic external fun worldSetCell(handle: Long, x: Int, y: Int, elem: Int, life: Int): Int
+    @JvmStatic external fun worldExportCells(handle: Long, buf: ByteBuffer): Int
+    @JvmStatic external fun worldCellsBuffer(handle: Long): ByteBuffer?
+    @JvmStatic external fun worldRenderRgba(handle: Long, buf: ByteBuffer): Int
+    @JvmStatic external fun lastErrorMessage(): String?
+}
+
+/** Element ids; must match the Rust `Element` enum (include/powdercore.h). */
+object Element {
+    const val EMPTY = 0
+    const val SAND = 1
+    const val GUNPOWDER = 2
+    const val ASH = 3
+    const val SNOW = 4
+    const val WATER = 5
+    const val SALT_WATER = 6
+    const val OIL = 7
+    const val ETHANOL = 8
+    const val ACID = 9
+    const val LAVA = 10
+    const val MERCURY = 11
+    const val STONE = 12
+    const val GLASS = 13
+    const val WALL = 14
+    const val WOOD = 15
+    const val PLANT = 16
+    const val METAL = 17
+    const val WIRE = 18
+    const val ICE = 19
+    const val COAL = 20
+    const val DIRT = 21
+    const val WET_DIRT = 22
+    const val SEAWEED = 23
+    const val SMOKE = 24
+    const val STEAM = 25
+    const val GAS = 26
+    const val TOXIC_GAS = 27
+    const val HYDROGEN = 28
+    const val CHLORINE = 29
+    const val FIRE = 30
+    const val LIGHTNING = 31
+    const val HUMAN = 32
+    const val ZOMBIE = 33
+    const val HUMAN_SPAWNER = 34
+    const val ZOMBIE_SPAWNER = 35
+    const val ANIMAL = 36
+    const val PLAYER = 37
+    const val CORPSE = 38
+    const val BONE = 39
+    const val RUBBLE = 40
+    const val DILUTED_ETHANOL = 41
+    const val WEAK_ACID = 42
+}
+
+class PowderException(message: String) : RuntimeException(message)
+
+data class Cell(val elem: Int, val life: Int)
+
+/** Owning wrapper around a native world handle. */
+class World(width: Int, height: Int, seed: Long = 0, threadsafe: Boolean = false) : AutoCloseable {
+    private var handle: Long =
+        if (threadsafe) PowderCore.worldNewThreadsafe(width, height, seed)
+        else PowderCore.worldNew(width, height, seed)
+
+    init {
+        if (handle == 0L) throw PowderException(PowderCore.lastErrorMessage() ?: "world creation failed")
+    }
+
+    private fun check(status: Int) {
+        if (status != PowderCore.STATUS_OK) {
+            throw PowderException(PowderCore.lastErrorMessage() ?: "status $status")
+        }
+    }
+
+    val width: Int get() = PowderCore.worldWidth(handle)
+    val height: Int get() = PowderCore.worldHeight(handle)
+
+    fun step() = check(PowderCore.worldStep(handle))
+    fun clear() = check(PowderCore.worldClear(handle))
+    fun resize(width: Int, height: Int) = check(PowderCore.worldResize(handle, width, height))
+    fun placeBrush(x: Int, y: Int, radius: Int, elem: Int) =
+        check(PowderCore.worldPlaceBrush(handle, x, y, radius, elem))
+
+    fun getCell(x: Int, y: Int): Cell {
+        val packed = PowderCore.worldGetCell(handle, x, y)
+        return Cell(packed.toInt(), (packed ushr 32).toInt())
+    }
+
+    fun setCell(x: Int, y: Int, elem: Int, life: Int = 0) =
+        check(PowderCore.worldSetCell(handle, x, y, elem, life))
+
+    /** Copy all cells (elem, life int pairs in native order) into a direct buffer. */
+    fun exportCells(buf: ByteBuffer): Int = PowderCore.worldExportCells(handle, buf)
+
+    /** Zero-copy view of the cells; invalid after the next mutating call. */
+    fun cellsView(): ByteBuffer? = PowderCore.worldCellsBuffer(handle)?.order(ByteOrder.nativeOrder())
+
+    /** Fill a direct buffer (width*height*4 bytes) with RGBA pixels. */
+    fun renderRgba(buf: ByteBuffer): Int = PowderCore.worldRenderRgba(handle, buf)
+
+    override fun close() {
+        if (handle != 0L) {
+            PowderCore.worldFree(handle)
+            handle = 0L
+        }
+    }
+}
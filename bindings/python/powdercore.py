@@ -0,0 +1,251 @@
+"""PowderCore - Python bindings.
+
+Thin ctypes wrapper over the C ABI (src/ffi.rs), so there's nothing to
+compile beyond the engine itself. For a native extension without the
+ctypes overhead, build the crate's `python` feature instead (src/python.rs);
+it offers the same World class, grid view and element constants. Usage:
+
+    cargo build --release
+    POWDERCORE_LIB=target/release/libpowdercore.so python3 -c '...'
+
+    import powdercore as pc
+    w = pc.World(200, 120, seed=1)
+    w.place_brush(100, 10, 4, pc.SAND)
+    w.step(60)
+    grid = w.to_numpy()          # (height, width) structured array, no copy
+    grid["elem"] == pc.SAND
+
+Element constants (SAND, WATER, ...) are read from the library at import
+time, so they always match the build you load.
+"""
+
+import ctypes
+import os
+import sys
+
+__all__ = ["World", "Cell", "PowderError", "ELEMENTS", "element_name"]
+
+
+def _load():
+    path = os.environ.get("POWDERCORE_LIB")
+    if path:
+        return ctypes.CDLL(path)
+    if sys.platform == "win32":
+        names = ["powdercore.dll"]
+    elif sys.platform == "darwin":
+        names = ["libpowdercore.dylib"]
+    else:
+        names = ["libpowdercore.so"]
+    here = os.path.dirname(os.path.abspath(__file__))
+    for name in names:
+        local = os.path.join(here, name)
+        if os.path.exists(local):
+            return ctypes.CDLL(local)
+    return ctypes.CDLL(names[0])
+
+
+_lib = _load()
+
+
+class Cell(ctypes.Structure):
+    """Same layout as the Rust `Cell` (two little-endian int32s)."""
+
+    _fields_ = [("elem", ctypes.c_int32), ("life", ctypes.c_int32)]
+
+    def __repr__(self):
+        return "Cell(%s, life=%d)" % (element_name(self.elem), self.life)
+
+
+class _Point(ctypes.Structure):
+    _fields_ = [("x", ctypes.c_int32), ("y", ctypes.c_int32)]
+
+
+_H = ctypes.c_void_p
+_i32 = ctypes.c_int32
+_size = ctypes.c_size_t
+
+
+def _sig(name, restype, *argtypes):
+    f = getattr(_lib, name)
+    f.restype = restype
+    f.argtypes = list(argtypes)
+    return f
+
+
+_new = _sig("powder_world_new", _H, _i32, _i32, ctypes.c_uint64)
+_new_threadsafe = _sig("powder_world_new_threadsafe", _H, _i32, _i32, ctypes.c_uint64)
+_free = _sig("powder_world_free", None, _H)
+_step = _sig("powder_world_step", _i32, _H)
+_clear = _sig("powder_world_clear", _i32, _H)
+_get_size = _sig("powder_world_get_size", _i32, _H, ctypes.POINTER(_i32), ctypes.POINTER(_i32))
+_resize = _sig("powder_world_resize", _i32, _H, _i32, _i32)
+_brush = _sig("powder_world_place_brush", _i32, _H, _i32, _i32, _i32, _i32)
+_get_cell = _sig("powder_world_get_cell", _i32, _H, _i32, _i32, ctypes.POINTER(Cell))
+_set_cell = _sig("powder_world_set_cell", _i32, _H, _i32, _i32, Cell)
+_spawn = _sig("powder_world_spawn", _i32, _H, _i32, _i32, _i32)
+_set_cells = _sig("powder_world_set_cells", _i32, _H, ctypes.POINTER(_Point), ctypes.POINTER(Cell), _size)
+_cells_ptr = _sig("powder_world_cells_ptr", ctypes.POINTER(Cell), _H, ctypes.POINTER(_size))
+_row_glyphs = _sig("powder_world_render_row_glyphs", _size, _H, _i32, ctypes.c_char_p, _size)
+_row_colors = _sig("powder_world_render_row_colors", _size, _H, _i32, ctypes.c_char_p, _size)
+_save = _sig("powder_world_save", _size, _H, ctypes.c_char_p, _size)
+_load_bytes = _sig("powder_world_load", _i32, _H, ctypes.c_char_p, _size)
+_last_error = _sig("powder_last_error_message", ctypes.c_char_p)
+_elem_count = _sig("powder_element_count", _i32)
+_elem_name = _sig("powder_element_name", _size, _i32, ctypes.c_char_p, _size)
+
+_OK = 1
+
+
+class PowderError(RuntimeError):
+    """A C API call failed; the message comes from powder_last_error_message."""
+
+
+def _check(status):
+    if status != _OK:
+        msg = _last_error()
+        raise PowderError(msg.decode() if msg else "status %d" % status)
+
+
+def element_name(elem):
+    """Name of an element id, e.g. element_name(1) == "Sand"."""
+    buf = ctypes.create_string_buffer(32)
+    n = _elem_name(elem, buf, len(buf))
+    if n == 0:
+        raise ValueError("invalid element id %d" % elem)
+    return buf.value.decode()
+
+
+def _constant(name):
+    return name.upper().replace(" ", "_")
+
+
+#: Element display name -> id, e.g. ELEMENTS["Salt Water"] == 6.
+ELEMENTS = {element_name(i): i for i in range(_elem_count())}
+
+# Module-level constants: SAND, SALT_WATER, TOXIC_GAS, ...
+for _name, _id in ELEMENTS.items():
+    globals()[_constant(_name)] = _id
+    __all__.append(_constant(_name))
+
+
+class World:
+    """A simulation grid. Mirrors the Rust `World`.
+
+    Pass threadsafe=True to share one world between Python threads (the
+    engine locks around every call; ctypes releases the GIL meanwhile).
+    """
+
+    def __init__(self, width, height, seed=0, threadsafe=False):
+        self._h = (_new_threadsafe if threadsafe else _new)(width, height, seed)
+        if not self._h:
+            _check(0)
+
+    def close(self):
+        if self._h:
+            _free(self._h)
+            self._h = None
+
+    def __del__(self):
+        self.close()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        self.close()
+
+    @property
+    def size(self):
+        w, h = _i32(), _i32()
+        _check(_get_size(self._h, ctypes.byref(w), ctypes.byref(h)))
+        return w.value, h.value
+
+    @property
+    def width(self):
+        return self.size[0]
+
+    @property
+    def height(self):
+        return self.size[1]
+
+    def step(self, n=1):
+        for _ in range(n):
+            _check(_step(self._h))
+
+    def clear(self):
+        _check(_clear(self._h))
+
+    def resize(self, width, height):
+        _check(_resize(self._h, width, height))
+
+    def place_brush(self, x, y, radius, elem):
+        _check(_brush(self._h, x, y, radius, elem))
+
+    def get_cell(self, x, y):
+        c = Cell()
+        _check(_get_cell(self._h, x, y, ctypes.byref(c)))
+        return c
+
+    def set_cell(self, x, y, elem, life=0):
+        _check(_set_cell(self._h, x, y, Cell(elem, life)))
+
+    def spawn(self, x, y, elem):
+        """Place a fresh `elem` with its default life (Fire keeps burning)."""
+        _check(_spawn(self._h, x, y, elem))
+
+    def set_cells(self, points, elem, life=0):
+        """Paint `elem` at every (x, y) in `points` in one call."""
+        points = list(points)
+        n = len(points)
+        pts = (_Point * n)(*[_Point(x, y) for x, y in points])
+        cells = (Cell * n)(*([Cell(elem, life)] * n))
+        _check(_set_cells(self._h, pts, cells, n))
+
+    def cells(self):
+        """Zero-copy ctypes array over the engine's cell buffer (row-major).
+
+        Supports the buffer protocol, so memoryview()/numpy.frombuffer() work
+        without copying. Only valid until the next call that changes the
+        world (step, brush, resize, ...).
+        """
+        n = _size()
+        ptr = _cells_ptr(self._h, ctypes.byref(n))
+        if not ptr:
+            _check(0)
+        return (Cell * n.value).from_address(ctypes.addressof(ptr.contents))
+
+    def to_numpy(self):
+        """The grid as a (height, width) numpy array with fields elem/life.
+
+        A view, not a copy: same lifetime rules as cells().
+        """
+        import numpy as np
+
+        w, h = self.size
+        dtype = np.dtype([("elem", "<i4"), ("life", "<i4")])
+        return np.frombuffer(self.cells(), dtype=dtype).reshape(h, w)
+
+    def row_glyphs(self, y):
+        """Row `y` as a string of glyphs, for terminal frontends."""
+        buf = ctypes.create_string_buffer(self.width)
+        n = _row_glyphs(self._h, y, buf, len(buf))
+        if n == 0 and self.width:
+            _check(0)
+        return buf.raw[:n].decode("ascii")
+
+    def row_colors(self, y):
+        """Row `y` as bytes of color indices (see powder_color_of)."""
+        buf = ctypes.create_string_buffer(self.width)
+        n = _row_colors(self._h, y, buf, len(buf))
+        if n == 0 and self.width:
+            _check(0)
+        return buf.raw[:n]
+
+    def save(self):
+        n = _save(self._h, None, 0)
+        buf = ctypes.create_string_buffer(n)
+        _save(self._h, buf, n)
+        return buf.raw
+
+    def load(self, data):
+        _check(_load_bytes(self._h, data, len(data)))
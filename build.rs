@@ -0,0 +1,344 @@
+// PowderCore - C header generation
+//
+// With the "c-header" feature, regenerate include/powdercore.h from the
+// Rust sources so C/C++ consumers always compile against declarations that
+// match the library they link. It's a small line-based scanner rather than
+// cbindgen (no build dependencies); it understands exactly the shapes the
+// FFI code uses:
+//
+//   #[repr(i32)] pub enum     -> typedef int32_t + NAME_VARIANT constants
+//   #[repr(C)] pub struct     -> typedef struct
+//   pub type X = <ptr>;       -> typedef
+//   pub type X = Option<extern "C" fn(..)>; -> function pointer typedef
+//   pub const X: <int> = N;   -> #define
+//   pub extern "C" fn ...     -> prototype
+//
+// Keep FFI items rustfmt-formatted (one field/param per line when wrapped)
+// and this stays happy.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Files scanned, in output order (types must come before their users).
+const SOURCES: &[&str] = &[
+    "src/lib.rs",
+    "src/render.rs",
+    "src/edges.rs",
+    "src/explosion.rs",
+    "src/ffi.rs",
+];
+
+fn main() {
+    for src in SOURCES {
+        println!("cargo:rerun-if-changed={src}");
+    }
+    if env::var_os("CARGO_FEATURE_C_HEADER").is_none() {
+        return;
+    }
+
+    let root = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let mut items = Items::default();
+    for src in SOURCES {
+        let text = fs::read_to_string(Path::new(&root).join(src)).unwrap();
+        scan(&text, &mut items);
+    }
+
+    let header = items.render();
+    let out = Path::new(&root).join("include/powdercore.h");
+    // Don't touch the file (and its mtime) when nothing changed.
+    if fs::read_to_string(&out).ok().as_deref() != Some(header.as_str()) {
+        fs::create_dir_all(out.parent().unwrap()).unwrap();
+        fs::write(&out, header).unwrap();
+    }
+}
+
+#[derive(Default)]
+struct Items {
+    consts: Vec<String>,
+    types: Vec<String>,
+    fns: Vec<String>,
+}
+
+impl Items {
+    fn render(&self) -> String {
+        let mut h = String::new();
+        h.push_str("/* PowderCore C API.\n");
+        h.push_str(" * Generated by build.rs from the Rust sources (feature \"c-header\").\n");
+        h.push_str(" * Do not edit by hand. */\n\n");
+        h.push_str("#ifndef POWDERCORE_H\n#define POWDERCORE_H\n\n");
+        h.push_str("#include <stddef.h>\n#include <stdint.h>\n\n");
+        h.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+        for c in &self.consts {
+            h.push_str(c);
+        }
+        if !self.consts.is_empty() {
+            h.push('\n');
+        }
+        for t in &self.types {
+            h.push_str(t);
+            h.push('\n');
+        }
+        for f in &self.fns {
+            h.push_str(f);
+            h.push('\n');
+        }
+        h.push_str("#ifdef __cplusplus\n}\n#endif\n\n#endif /* POWDERCORE_H */\n");
+        h
+    }
+}
+
+fn scan(text: &str, items: &mut Items) {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut docs: Vec<String> = Vec::new();
+    let mut repr: Option<&str> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let t = line.trim();
+        i += 1;
+
+        if let Some(d) = t.strip_prefix("///") {
+            docs.push(d.trim().to_string());
+            continue;
+        }
+        if t.starts_with("#[repr(i32)]") {
+            repr = Some("i32");
+            continue;
+        }
+        if t.starts_with("#[repr(C)]") {
+            repr = Some("C");
+            continue;
+        }
+        if t.starts_with("#[") || t.starts_with("//") {
+            continue;
+        }
+        // only top-level items
+        if line.starts_with(' ') || t.is_empty() {
+            docs.clear();
+            repr = None;
+            continue;
+        }
+
+        if let (Some("i32"), Some(rest)) = (repr, t.strip_prefix("pub enum ")) {
+            let name = rest.trim_end_matches('{').trim();
+            let (body, next) = block(&lines, i);
+            i = next;
+            items.types.push(enum_decl(name, &body, &docs));
+        } else if let (Some("C"), Some(rest)) = (repr, t.strip_prefix("pub struct ")) {
+            let name = rest.trim_end_matches('{').trim();
+            let (body, next) = block(&lines, i);
+            i = next;
+            items.types.push(struct_decl(name, &body, &docs));
+        } else if let Some(rest) = t.strip_prefix("pub type ") {
+            let (name, ty) = rest.trim_end_matches(';').split_once('=').unwrap();
+            let mut s = comment(&docs);
+            let _ = writeln!(s, "typedef {};", c_decl(ty.trim(), name.trim()));
+            items.types.push(s);
+        } else if let Some(rest) = t
+            .strip_prefix("pub const ")
+            .filter(|r| !r.starts_with("fn "))
+        {
+            let (name, val) = rest.trim_end_matches(';').split_once('=').unwrap();
+            let name = name.split(':').next().unwrap().trim();
+            let val = val.trim();
+            if val.chars().all(|c| c.is_ascii_digit() || c == '_') {
+                items
+                    .consts
+                    .push(format!("#define {name} {}\n", val.replace('_', "")));
+            }
+        } else if let Some(rest) = t.strip_prefix("pub extern \"C\" fn ") {
+            // gather the signature up to the opening brace
+            let mut sig = rest.to_string();
+            while !sig.trim_end().ends_with('{') {
+                sig.push(' ');
+                sig.push_str(lines[i].trim());
+                i += 1;
+            }
+            items
+                .fns
+                .push(fn_decl(sig.trim_end_matches('{').trim(), &docs));
+        }
+        docs.clear();
+        repr = None;
+    }
+}
+
+/// Lines of a `{ ... }` body starting at `start`, and the index after `}`.
+fn block(lines: &[&str], start: usize) -> (Vec<String>, usize) {
+    let mut body = Vec::new();
+    let mut i = start;
+    while i < lines.len() && lines[i] != "}" {
+        body.push(lines[i].trim().to_string());
+        i += 1;
+    }
+    (body, i + 1)
+}
+
+fn comment(docs: &[String]) -> String {
+    docs.iter()
+        .map(|d| {
+            if d.is_empty() {
+                "//\n".to_string()
+            } else {
+                format!("// {d}\n")
+            }
+        })
+        .collect()
+}
+
+fn enum_decl(name: &str, body: &[String], docs: &[String]) -> String {
+    let prefix = screaming(name);
+    let mut s = comment(docs);
+    let _ = writeln!(s, "typedef int32_t {name};");
+    s.push_str("enum {\n");
+    let mut next = 0i64;
+    for line in body {
+        let line = line.trim_end_matches(',');
+        if line.is_empty() || line.starts_with("//") || line.starts_with('#') {
+            continue;
+        }
+        let (variant, value) = match line.split_once('=') {
+            Some((v, n)) => (v.trim(), n.trim().parse::<i64>().unwrap()),
+            None => (line.trim(), next),
+        };
+        let _ = writeln!(s, "    {prefix}_{} = {value},", screaming(variant));
+        next = value + 1;
+    }
+    s.push_str("};\n");
+    s
+}
+
+fn struct_decl(name: &str, body: &[String], docs: &[String]) -> String {
+    let mut s = comment(docs);
+    let _ = writeln!(s, "typedef struct {name} {{");
+    let mut field_docs = Vec::new();
+    for line in body {
+        if let Some(d) = line.strip_prefix("///") {
+            field_docs.push(d.trim().to_string());
+            continue;
+        }
+        // drop trailing `// ...` notes
+        let line = line.split(" //").next().unwrap();
+        let Some(field) = line.strip_prefix("pub ") else {
+            continue;
+        };
+        let (fname, ty) = field.trim_end_matches(',').split_once(':').unwrap();
+        for d in field_docs.drain(..) {
+            let _ = writeln!(s, "    // {d}");
+        }
+        let _ = writeln!(s, "    {};", c_decl(ty.trim(), fname.trim()));
+    }
+    let _ = writeln!(s, "}} {name};");
+    s
+}
+
+fn fn_decl(sig: &str, docs: &[String]) -> String {
+    let open = sig.find('(').unwrap();
+    let close = sig.rfind(')').unwrap();
+    let name = sig[..open].trim();
+    let params: Vec<String> = sig[open + 1..close]
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (pname, ty) = p.split_once(':').unwrap();
+            c_decl(ty.trim(), pname.trim())
+        })
+        .collect();
+    let ret = sig[close + 1..].trim().trim_start_matches("->").trim();
+    let ret = if ret.is_empty() {
+        "void".to_string()
+    } else {
+        c_type(ret)
+    };
+    let params = if params.is_empty() {
+        "void".to_string()
+    } else {
+        params.join(", ")
+    };
+    let mut s = comment(docs);
+    let _ = writeln!(s, "{ret} {name}({params});");
+    s
+}
+
+/// `ty name` as a C declarator (arrays put their sizes after the name,
+/// function pointers wrap it).
+fn c_decl(ty: &str, name: &str) -> String {
+    let mut ty = ty.trim();
+    if let Some(inner) = ty.strip_prefix("Option<").and_then(|t| t.strip_suffix('>')) {
+        ty = inner.trim();
+    }
+    if let Some(f) = ty.strip_prefix("extern \"C\" fn") {
+        let (open, close) = (f.find('(').unwrap(), f.rfind(')').unwrap());
+        let params: Vec<String> = f[open + 1..close]
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(c_type)
+            .collect();
+        let ret = f[close + 1..].trim().trim_start_matches("->").trim();
+        let ret = if ret.is_empty() {
+            "void".to_string()
+        } else {
+            c_type(ret)
+        };
+        let params = if params.is_empty() {
+            "void".to_string()
+        } else {
+            params.join(", ")
+        };
+        return format!("{ret} (*{name})({params})");
+    }
+    let mut dims = Vec::new();
+    while let Some(inner) = ty.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        let (elem, len) = inner.rsplit_once(';').unwrap();
+        dims.push(len.trim().to_string());
+        ty = elem.trim();
+    }
+    let mut s = format!("{} {name}", c_type(ty));
+    for d in dims {
+        let _ = write!(s, "[{d}]");
+    }
+    s
+}
+
+fn c_type(ty: &str) -> String {
+    if let Some(inner) = ty.strip_prefix("*mut ") {
+        return format!("{}*", c_type(inner));
+    }
+    if let Some(inner) = ty.strip_prefix("*const ") {
+        return format!("const {}*", c_type(inner));
+    }
+    match ty {
+        "i8" => "int8_t",
+        "u8" => "uint8_t",
+        "i16" => "int16_t",
+        "u16" => "uint16_t",
+        "i32" => "int32_t",
+        "u32" => "uint32_t",
+        "i64" => "int64_t",
+        "u64" => "uint64_t",
+        "usize" => "size_t",
+        "isize" => "ptrdiff_t",
+        "f32" => "float",
+        "f64" => "double",
+        "c_char" => "char",
+        "c_void" => "void",
+        other => other,
+    }
+    .to_string()
+}
+
+/// CamelCase -> SCREAMING_SNAKE.
+fn screaming(name: &str) -> String {
+    let mut s = String::new();
+    for (i, ch) in name.chars().enumerate() {
+        if ch.is_ascii_uppercase() && i > 0 {
+            s.push('_');
+        }
+        s.push(ch.to_ascii_uppercase());
+    }
+    s
+}
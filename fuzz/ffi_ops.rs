@@ -0,0 +1,473 @@
+// PowderCore - FFI fuzz driver
+//
+// Reads arbitrary bytes as a script of C ABI calls: each call is an opcode
+// byte followed by its arguments (sizes, coordinates, element ints, buffer
+// lengths, save blobs), decoded so most values are small and plausible and
+// the rest are raw 32/64-bit garbage. The calls go through the exported
+// extern "C" functions exactly as a host would make them, so anything the
+// FFI layer lets through lands in the engine.
+//
+// Shared by the cargo-fuzz target (fuzz_targets/ffi.rs) and by
+// tests/ffi_fuzz.rs, which replays seeded random scripts on every
+// `cargo test`.
+//
+// What counts as a bug:
+//   - anything the sanitizers or Miri flag,
+//   - a panic caught by the FFI guard ("engine panicked" as the last error),
+//   - a call returning more than it was allowed to write, or a save that
+//     doesn't deserialize back to the same bytes.
+//
+// The driver keeps to the documented contract (buffers really are as long
+// as the length passed with them, the watcher context outlives the handle)
+// since breaking that is the host's UB, not ours. Worlds and blobs that ask
+// for more than FUZZ_MAX_CELLS cells but less than the loader's own limit
+// are skipped, so the fuzzer's memory limit isn't what gets reported.
+
+use std::ffi::{c_void, CStr};
+use std::ptr;
+
+use powdercore::*;
+
+/// Biggest world the driver will create or load.
+const FUZZ_MAX_CELLS: u64 = 1 << 16;
+/// Loader limit (save::MAX_CELLS); sizes above it are rejected up front.
+const MAX_CELLS: u64 = 1 << 28;
+
+/// Argument decoder over the remaining input. Reads past the end as zeros.
+struct Input<'a> {
+    bytes: &'a [u8],
+}
+
+impl Input<'_> {
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, n: usize) -> &[u8] {
+        let (head, rest) = self.bytes.split_at(n.min(self.bytes.len()));
+        self.bytes = rest;
+        head
+    }
+
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        let mut out = [0; N];
+        let head = self.take(N);
+        out[..head.len()].copy_from_slice(head);
+        out
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.array::<1>()[0]
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes(self.array())
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.array())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.array())
+    }
+
+    fn i32(&mut self) -> i32 {
+        self.u32() as i32
+    }
+
+    fn flag(&mut self) -> bool {
+        self.u8() & 1 != 0
+    }
+
+    /// World dimension: usually 0..100, sometimes any i32.
+    fn size(&mut self) -> i32 {
+        match self.u8() {
+            b @ 0..=0xef => (b % 100) as i32,
+            _ => self.i32(),
+        }
+    }
+
+    /// Coordinate: usually a little outside to well past a small world.
+    fn coord(&mut self) -> i32 {
+        match self.u8() {
+            b @ 0..=0xf7 => b as i32 - 16,
+            _ => self.i32(),
+        }
+    }
+
+    /// Element id: usually around the valid range, sometimes any i32.
+    fn elem(&mut self) -> i32 {
+        match self.u8() {
+            b @ 0..=0xef => (b % 48) as i32 - 2,
+            _ => self.i32(),
+        }
+    }
+
+    fn life(&mut self) -> i32 {
+        match self.u8() {
+            b @ 0..=0xef => b as i32 - 8,
+            _ => self.i32(),
+        }
+    }
+
+    /// Buffer length in elements.
+    fn len(&mut self) -> usize {
+        self.u16() as usize
+    }
+}
+
+/// Whether a `width` x `height` world is small enough to create here, or
+/// big enough that the engine must refuse it without allocating.
+fn size_allowed(width: i64, height: i64) -> bool {
+    let cells = width.max(0) as u64 * height.max(0) as u64;
+    cells <= FUZZ_MAX_CELLS || cells > MAX_CELLS
+}
+
+/// Same check for the dimensions a save blob declares (see save.rs).
+fn blob_allowed(blob: &[u8]) -> bool {
+    match (blob.get(6..10), blob.get(10..14)) {
+        (Some(w), Some(h)) if blob.starts_with(b"PWDR") => {
+            let dim = |b: &[u8]| u32::from_le_bytes(b.try_into().unwrap()) as i64;
+            size_allowed(dim(w), dim(h))
+        }
+        _ => true,
+    }
+}
+
+extern "C" fn count_changes(user_data: *mut c_void, _x: i32, _y: i32, from: i32, to: i32) {
+    assert_ne!(from, to, "watcher called for an unchanged cell");
+    unsafe { *(user_data as *mut u64) += 1 };
+}
+
+/// One world handle (possibly null) plus what the script has learned.
+struct State {
+    handle: PowderWorldHandle,
+    watchers: Vec<u64>,
+    /// Watcher callback context; boxed so its address outlives any handle.
+    changes: Box<u64>,
+}
+
+impl State {
+    fn replace(&mut self, handle: PowderWorldHandle) {
+        powder_world_free(self.handle);
+        self.handle = handle;
+        self.watchers.clear();
+    }
+
+    fn save(&self) -> Vec<u8> {
+        let len = powder_world_save(self.handle, ptr::null_mut(), 0);
+        let mut buf = vec![0u8; len];
+        let written = powder_world_save(self.handle, buf.as_mut_ptr(), buf.len());
+        assert_eq!(written, len, "save size changed between calls");
+        buf
+    }
+
+    /// Arbitrary bytes, or the current world's save with some bytes
+    /// flipped or cut short.
+    fn blob(&self, input: &mut Input) -> Vec<u8> {
+        if self.handle.is_null() || input.u8().is_multiple_of(3) {
+            let n = input.u8() as usize;
+            return input.take(n).to_vec();
+        }
+        let mut blob = self.save();
+        for _ in 0..input.u8() % 4 {
+            if !blob.is_empty() {
+                let at = input.u16() as usize % blob.len();
+                blob[at] ^= input.u8();
+            }
+        }
+        if input.flag() {
+            blob.truncate(input.u16() as usize);
+        }
+        blob
+    }
+
+    fn check_no_panic(&self) {
+        let msg = powder_last_error_message();
+        if !msg.is_null() {
+            let msg = unsafe { CStr::from_ptr(msg) }.to_string_lossy();
+            assert!(!msg.starts_with("engine panicked"), "{msg}");
+        }
+    }
+
+    /// Decode and make one call.
+    fn op(&mut self, input: &mut Input) {
+        let h = self.handle;
+        match input.u8() % 32 {
+            0 | 1 => {
+                let (w, ht, seed) = (input.size(), input.size(), input.u64());
+                if size_allowed(w as i64, ht as i64) {
+                    let threadsafe = input.flag();
+                    self.replace(if threadsafe {
+                        powder_world_new_threadsafe(w, ht, seed)
+                    } else {
+                        powder_world_new(w, ht, seed)
+                    });
+                }
+            }
+            2 => self.replace(ptr::null_mut()),
+            3..=5 => {
+                for _ in 0..input.u8() % 8 {
+                    powder_world_step(h);
+                }
+            }
+            6 if input.flag() => {
+                let (x, y) = (input.coord(), input.coord());
+                let spec = ExplosionSpec {
+                    radius: input.u8() as i32 % 24 - 2,
+                    fire_pct: input.u8(),
+                    smoke_pct: input.u8(),
+                    shrapnel: input.u8() as u16 % 64,
+                    dig_solids: input.u8() as i32 - 1,
+                };
+                let spec: *const ExplosionSpec = if input.flag() { &spec } else { ptr::null() };
+                powder_world_detonate(h, x, y, spec);
+            }
+            6 => {
+                powder_world_clear(h);
+            }
+            7 => {
+                let (mut w, mut ht) = (-1, -1);
+                if powder_world_get_size(h, &mut w, &mut ht) == PowderStatus::Ok {
+                    let mut len = 0;
+                    let cells = powder_world_cells_ptr(h, &mut len);
+                    assert_eq!(len, w as usize * ht as usize);
+                    if len > 0 {
+                        let cells = unsafe { std::slice::from_raw_parts(cells, len) };
+                        std::hint::black_box(cells.iter().map(|c| c.life as i64).sum::<i64>());
+                    }
+                }
+                std::hint::black_box(powder_world_get_tick(h));
+            }
+            8 => {
+                let (w, ht) = (input.size(), input.size());
+                if size_allowed(w as i64, ht as i64) {
+                    powder_world_resize(h, w, ht);
+                }
+            }
+            9 | 10 => {
+                let (x, y) = (input.coord(), input.coord());
+                let rad = match input.u8() {
+                    b @ 0..=0xef => b as i32 % 12 - 1,
+                    _ => input.i32(),
+                };
+                powder_world_place_brush(h, x, y, rad, input.elem());
+            }
+            11 => {
+                let (x, y) = (input.coord(), input.coord());
+                powder_world_spawn(h, x, y, input.elem());
+            }
+            12 => {
+                let (x, y) = (input.coord(), input.coord());
+                let cell = FfiCell {
+                    elem: input.elem(),
+                    life: input.life(),
+                };
+                powder_world_set_cell(h, x, y, cell);
+                let mut out = Cell::default();
+                powder_world_get_cell(h, x, y, &mut out);
+            }
+            13 => {
+                let n = input.u8() as usize % 16;
+                let points: Vec<FfiPoint> = (0..n)
+                    .map(|_| FfiPoint {
+                        x: input.coord(),
+                        y: input.coord(),
+                    })
+                    .collect();
+                let cells: Vec<FfiCell> = (0..n)
+                    .map(|_| FfiCell {
+                        elem: input.elem(),
+                        life: input.life(),
+                    })
+                    .collect();
+                powder_world_set_cells(h, points.as_ptr(), cells.as_ptr(), n);
+                let mut out = vec![Cell::default(); n];
+                powder_world_get_cells(h, points.as_ptr(), out.as_mut_ptr(), n);
+            }
+            14 => {
+                powder_world_set_edge_mode(h, input.u8() as i32 % 4 - 1);
+            }
+            15 => {
+                let kind = input.u8() as i32 % 7 - 1;
+                let intensity = match input.u8() {
+                    b @ 0..=0xef => b as u32,
+                    _ => input.u32(),
+                };
+                powder_world_set_weather(h, kind, intensity);
+            }
+            16 => {
+                powder_world_generate(h, input.u64());
+            }
+            17 => {
+                let (x, y, w, ht) = (input.coord(), input.coord(), input.size(), input.size());
+                let user_data = &mut *self.changes as *mut u64 as *mut c_void;
+                let callback = input.flag().then_some(count_changes as _);
+                let id = powder_world_watch_region(h, x, y, w, ht, callback, user_data);
+                if id != 0 {
+                    self.watchers.push(id);
+                }
+            }
+            18 => {
+                let id = match (input.u8(), self.watchers.len()) {
+                    (b @ 0..=0xef, n) if n > 0 => self.watchers.swap_remove(b as usize % n),
+                    _ => input.u64(),
+                };
+                powder_world_unwatch(h, id);
+            }
+            19 => {
+                let max = input.len();
+                let mut out = vec![Cell::default(); max];
+                assert!(powder_world_export_cells(h, out.as_mut_ptr(), max) <= max);
+            }
+            20 => {
+                let max = input.len();
+                let (mut floats, mut ints, mut depths) =
+                    (vec![0f32; max], vec![0i16; max], vec![0u16; max]);
+                let written = match input.u8() % 5 {
+                    0 => powder_world_export_temperature(h, floats.as_mut_ptr(), max),
+                    1 => powder_world_export_pressure(h, floats.as_mut_ptr(), max),
+                    2 => powder_world_export_charge(h, ints.as_mut_ptr(), max),
+                    3 => powder_world_export_activity(h, ints.as_mut_ptr(), max),
+                    _ => powder_world_export_depth(h, depths.as_mut_ptr(), max),
+                };
+                assert!(written <= max);
+            }
+            21 => {
+                let max = input.len() * 4;
+                let mut out = vec![0u8; max];
+                let mut palette = Palette {
+                    colors: [(); PALETTE_LEN].map(|_| input.array()),
+                    charged: input.array(),
+                    tint: input.i32(),
+                };
+                powder_palette_builtin(input.u8() as i32 % 5 - 1, &mut palette);
+                let palette = if input.flag() {
+                    &palette as *const Palette
+                } else {
+                    ptr::null()
+                };
+                powder_world_render_rgba(h, out.as_mut_ptr(), max, palette);
+            }
+            22 if input.flag() => {
+                let max = input.len();
+                let mut out = vec![0u8; max];
+                if input.flag() {
+                    powder_world_render_emission(h, out.as_mut_ptr(), max);
+                } else {
+                    let kind = input.u8() as i32 % 6 - 1;
+                    powder_world_render_overlay(h, kind, out.as_mut_ptr(), max);
+                }
+            }
+            22 => {
+                let (y, max) = (input.coord(), input.u8() as usize);
+                let mut out = vec![0u8; max];
+                let written = if input.flag() {
+                    powder_world_render_row_glyphs(h, y, out.as_mut_ptr(), max)
+                } else {
+                    powder_world_render_row_colors(h, y, out.as_mut_ptr(), max)
+                };
+                assert!(written <= max);
+            }
+            23 => {
+                let max = input.len();
+                let mut out = vec![0u8; max];
+                powder_world_save(h, out.as_mut_ptr(), max);
+            }
+            24 | 25 => {
+                let blob = self.blob(input);
+                if blob_allowed(&blob) {
+                    powder_world_load(h, blob.as_ptr(), blob.len());
+                }
+            }
+            26 => {
+                let blob = self.blob(input);
+                if blob_allowed(&blob) {
+                    let loaded = powder_world_deserialize(blob.as_ptr(), blob.len());
+                    if !loaded.is_null() {
+                        self.replace(loaded);
+                    }
+                }
+            }
+            27 => {
+                // a save must come back byte for byte
+                if !h.is_null() {
+                    let saved = self.save();
+                    let copy = powder_world_deserialize(saved.as_ptr(), saved.len());
+                    assert!(!copy.is_null(), "own save failed to load");
+                    let mut state = State {
+                        handle: copy,
+                        watchers: Vec::new(),
+                        changes: Box::new(0),
+                    };
+                    assert_eq!(state.save(), saved);
+                    state.replace(ptr::null_mut());
+                }
+            }
+            28 => {
+                let max = input.u8() as usize;
+                let mut out = vec![
+                    FfiEvent {
+                        kind: PowderEventKind::Explosion,
+                        x: 0,
+                        y: 0,
+                        a: 0,
+                        b: 0,
+                        id: 0,
+                        by: 0,
+                    };
+                    max
+                ];
+                assert!(powder_world_poll_events(h, out.as_mut_ptr(), max) <= max);
+            }
+            29 => {
+                let (elem, life) = (input.elem(), input.life());
+                let mut name = [0u8; 32];
+                let max = input.u8() as usize % (name.len() + 1);
+                let needed = powder_element_name(elem, name.as_mut_ptr() as *mut _, max);
+                if needed > 0 && needed <= max {
+                    assert_eq!(name[needed - 1], 0, "name not NUL terminated");
+                }
+                std::hint::black_box((
+                    powder_element_count(),
+                    powder_element_default_life(elem),
+                    powder_color_of(elem, life),
+                    powder_glyph_of(elem, life),
+                    powder_color256_of(elem, life),
+                    powder_emission_of(elem, life),
+                    powder_glyph_of_animated(elem, life, input.i32(), input.i32(), input.u64()),
+                    powder_palette_color(ptr::null(), elem, life),
+                    powder_shade_of(elem, life, input.u64()),
+                ));
+            }
+            _ => {
+                // the null-handle paths of everything above
+                let saved = self.handle;
+                self.handle = ptr::null_mut();
+                self.op(input);
+                if self.handle.is_null() {
+                    self.handle = saved;
+                } else {
+                    powder_world_free(saved);
+                    self.watchers.clear();
+                }
+            }
+        }
+        self.check_no_panic();
+    }
+}
+
+/// Run the script in `data` against a fresh handle.
+pub fn run(data: &[u8]) {
+    let mut input = Input { bytes: data };
+    let mut state = State {
+        handle: powder_world_new(32, 24, 1),
+        watchers: Vec::new(),
+        changes: Box::new(0),
+    };
+    while !input.is_empty() {
+        state.op(&mut input);
+    }
+    state.replace(ptr::null_mut());
+}
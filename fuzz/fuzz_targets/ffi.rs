@@ -0,0 +1,13 @@
+// cargo fuzz run ffi
+//
+// Byte scripts of C ABI calls; see ../ffi_ops.rs for the format and what
+// counts as a failure.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[path = "../ffi_ops.rs"]
+mod ffi_ops;
+
+fuzz_target!(|data: &[u8]| ffi_ops::run(data));
@@ -0,0 +1,355 @@
+/* PowderCore C API.
+ * Generated by build.rs from the Rust sources (feature "c-header").
+ * Do not edit by hand. */
+
+#ifndef POWDERCORE_H
+#define POWDERCORE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define PALETTE_LEN 43
+
+typedef int32_t Element;
+enum {
+    ELEMENT_EMPTY = 0,
+    ELEMENT_SAND = 1,
+    ELEMENT_GUNPOWDER = 2,
+    ELEMENT_ASH = 3,
+    ELEMENT_SNOW = 4,
+    ELEMENT_WATER = 5,
+    ELEMENT_SALT_WATER = 6,
+    ELEMENT_OIL = 7,
+    ELEMENT_ETHANOL = 8,
+    ELEMENT_ACID = 9,
+    ELEMENT_LAVA = 10,
+    ELEMENT_MERCURY = 11,
+    ELEMENT_STONE = 12,
+    ELEMENT_GLASS = 13,
+    ELEMENT_WALL = 14,
+    ELEMENT_WOOD = 15,
+    ELEMENT_PLANT = 16,
+    ELEMENT_METAL = 17,
+    ELEMENT_WIRE = 18,
+    ELEMENT_ICE = 19,
+    ELEMENT_COAL = 20,
+    ELEMENT_DIRT = 21,
+    ELEMENT_WET_DIRT = 22,
+    ELEMENT_SEAWEED = 23,
+    ELEMENT_SMOKE = 24,
+    ELEMENT_STEAM = 25,
+    ELEMENT_GAS = 26,
+    ELEMENT_TOXIC_GAS = 27,
+    ELEMENT_HYDROGEN = 28,
+    ELEMENT_CHLORINE = 29,
+    ELEMENT_FIRE = 30,
+    ELEMENT_LIGHTNING = 31,
+    ELEMENT_HUMAN = 32,
+    ELEMENT_ZOMBIE = 33,
+    ELEMENT_HUMAN_SPAWNER = 34,
+    ELEMENT_ZOMBIE_SPAWNER = 35,
+    ELEMENT_ANIMAL = 36,
+    ELEMENT_PLAYER = 37,
+    ELEMENT_CORPSE = 38,
+    ELEMENT_BONE = 39,
+    ELEMENT_RUBBLE = 40,
+    ELEMENT_DILUTED_ETHANOL = 41,
+    ELEMENT_WEAK_ACID = 42,
+};
+
+typedef struct Cell {
+    Element elem;
+    int32_t life;
+} Cell;
+
+// Per-element colors, plus whether to apply per-cell tint.
+typedef struct Palette {
+    // RGBA for each element, indexed by element id.
+    uint8_t colors[PALETTE_LEN][4];
+    // Water and salt water carrying charge.
+    uint8_t charged[4];
+    // Nonzero to apply per-cell tint.
+    int32_t tint;
+} Palette;
+
+typedef int32_t EdgeMode;
+enum {
+    EDGE_MODE_SOLID = 0,
+    EDGE_MODE_OPEN = 1,
+    EDGE_MODE_WRAP = 2,
+};
+
+// What an explosion does: its reach, what it leaves behind, debris and
+// whether it breaks solid blocks.
+typedef struct ExplosionSpec {
+    // Blast radius in cells.
+    int32_t radius;
+    // Percent of blasted cells that become fire.
+    uint8_t fire_pct;
+    // Percent that become smoke; the rest become gas.
+    uint8_t smoke_pct;
+    // Debris fragments thrown out to twice the radius.
+    uint16_t shrapnel;
+    // Nonzero to break stone, glass, metal, wire and ice into rubble.
+    int32_t dig_solids;
+} ExplosionSpec;
+
+// Result of a C API call. Success is 1 so older callers that tested for a
+// truthy return keep working; every failure is <= 0.
+typedef int32_t PowderStatus;
+enum {
+    POWDER_STATUS_OK = 1,
+    POWDER_STATUS_NULL_POINTER = 0,
+    POWDER_STATUS_INVALID_ELEMENT = -1,
+    POWDER_STATUS_PANIC = -2,
+    POWDER_STATUS_OUT_OF_BOUNDS = -3,
+    POWDER_STATUS_BAD_SIZE = -4,
+    POWDER_STATUS_OUT_OF_MEMORY = -5,
+    POWDER_STATUS_INVALID_DATA = -6,
+    POWDER_STATUS_INVALID_ARGUMENT = -7,
+};
+
+// Opaque handle type when viewed from C/other languages.
+typedef void* PowderWorldHandle;
+
+// Cell as written by C callers. Same layout as `Cell`, but the element is
+// a plain int so an out-of-range id can be rejected instead of being UB.
+typedef struct FfiCell {
+    int32_t elem;
+    int32_t life;
+} FfiCell;
+
+// Grid position for the batch cell calls.
+typedef struct FfiPoint {
+    int32_t x;
+    int32_t y;
+} FfiPoint;
+
+// Called for each changed cell of a watched region: (user_data, x, y,
+// old element id, new element id).
+typedef void (*PowderCellCallback)(void*, int32_t, int32_t, int32_t, int32_t);
+
+typedef int32_t PowderEventKind;
+enum {
+    POWDER_EVENT_KIND_EXPLOSION = 1,
+    POWDER_EVENT_KIND_HUMAN_DIED = 2,
+    POWDER_EVENT_KIND_ELEMENT_CONVERTED = 3,
+    POWDER_EVENT_KIND_ACTOR_SPAWNED = 4,
+    POWDER_EVENT_KIND_ACTOR_INFECTED = 5,
+    POWDER_EVENT_KIND_ACTOR_DIED = 6,
+    POWDER_EVENT_KIND_ZOMBIE_KILLED = 7,
+    POWDER_EVENT_KIND_IGNITED = 8,
+    POWDER_EVENT_KIND_DISSOLVED = 9,
+    POWDER_EVENT_KIND_ELEMENT_CREATED = 10,
+};
+
+// Flat event for C callers. `a`/`b` depend on `kind`: radius for
+// Explosion, from/to element ids for ElementConverted, the actor's element
+// id for ActorSpawned/ActorDied, the element id for Ignited, Dissolved and
+// ElementCreated, unused otherwise. `id` is the actor id
+// for actor events (the zombie for ZombieKilled, with the killer's id in
+// `by`), 0 otherwise.
+typedef struct FfiEvent {
+    PowderEventKind kind;
+    int32_t x;
+    int32_t y;
+    int32_t a;
+    int32_t b;
+    uint64_t id;
+    uint64_t by;
+} FfiEvent;
+
+// Message for the most recent failed call on this thread, or null if none
+// failed yet. The string stays valid until the next failing call on the
+// same thread.
+const char* powder_last_error_message(void);
+
+// Returns null on failure (see powder_last_error_message).
+PowderWorldHandle powder_world_new(int32_t width, int32_t height, uint64_t seed);
+
+// Like powder_world_new, but every call on the handle locks internally so
+// it can be shared between threads. Returns null on failure.
+PowderWorldHandle powder_world_new_threadsafe(int32_t width, int32_t height, uint64_t seed);
+
+// Free a handle from either constructor. No other thread may be using it.
+void powder_world_free(PowderWorldHandle handle);
+
+PowderStatus powder_world_step(PowderWorldHandle handle);
+
+PowderStatus powder_world_clear(PowderWorldHandle handle);
+
+PowderStatus powder_world_get_size(PowderWorldHandle handle, int32_t* out_width, int32_t* out_height);
+
+// Number of steps completed since the world was created (0 on failure).
+uint64_t powder_world_get_tick(PowderWorldHandle handle);
+
+PowderStatus powder_world_resize(PowderWorldHandle handle, int32_t width, int32_t height);
+
+PowderStatus powder_world_place_brush(PowderWorldHandle handle, int32_t cx, int32_t cy, int32_t rad, int32_t elem);
+
+PowderStatus powder_world_get_cell(PowderWorldHandle handle, int32_t x, int32_t y, Cell* out_cell);
+
+PowderStatus powder_world_set_cell(PowderWorldHandle handle, int32_t x, int32_t y, FfiCell cell);
+
+// Set what happens at the world edges; `mode` is an EdgeMode value.
+PowderStatus powder_world_set_edge_mode(PowderWorldHandle handle, int32_t mode);
+
+// Set the weather: `kind` 0 Clear, 1 Rain, 2 Snow, 3 AcidRain, 4 Meteors,
+// with `intensity` as documented on Weather.
+PowderStatus powder_world_set_weather(PowderWorldHandle handle, int32_t kind, uint32_t intensity);
+
+// Clear the world and fill it with terrain generated from `seed` using
+// the default WorldGen settings.
+PowderStatus powder_world_generate(PowderWorldHandle handle, uint64_t seed);
+
+// Call `callback` during powder_world_step for every cell in the rectangle
+// whose element changed. It runs on the stepping thread with the world
+// locked, so it must not call back into this world. Returns a watcher id
+// for powder_world_unwatch, or 0 on failure.
+uint64_t powder_world_watch_region(PowderWorldHandle handle, int32_t x, int32_t y, int32_t width, int32_t height, PowderCellCallback callback, void* user_data);
+
+// Remove a watcher added by powder_world_watch_region.
+PowderStatus powder_world_unwatch(PowderWorldHandle handle, uint64_t id);
+
+// Place a fresh `elem` at (x, y) with its default life (unlike
+// powder_world_set_cell, which stores the life it is given).
+PowderStatus powder_world_spawn(PowderWorldHandle handle, int32_t x, int32_t y, int32_t elem);
+
+// Set off an explosion at (x, y) as described by `spec`, or with null, the
+// default blast gunpowder makes (radius 5, 50% fire, 30% smoke).
+PowderStatus powder_world_detonate(PowderWorldHandle handle, int32_t x, int32_t y, const ExplosionSpec* spec);
+
+// Write `count` cells at the matching `points`. Every element id is checked
+// before anything is written, so an invalid id leaves the world untouched.
+// Points outside the world are skipped.
+PowderStatus powder_world_set_cells(PowderWorldHandle handle, const FfiPoint* points, const FfiCell* cells, size_t count);
+
+// Read the cells at `count` `points` into `out_cells`. Points outside the
+// world read as Empty.
+PowderStatus powder_world_get_cells(PowderWorldHandle handle, const FfiPoint* points, Cell* out_cells, size_t count);
+
+// Export the internal cell buffer in row-major order (y * width + x).
+// `out_cells` must point to a buffer of at least `max_len` Cells.
+// Returns the number of cells written.
+size_t powder_world_export_cells(PowderWorldHandle handle, Cell* out_cells, size_t max_len);
+
+// Nominal temperature per cell, in degrees C.
+size_t powder_world_export_temperature(PowderWorldHandle handle, float* out_buf, size_t max_len);
+
+// Hydrostatic pressure per cell, in cells of water.
+size_t powder_world_export_pressure(PowderWorldHandle handle, float* out_buf, size_t max_len);
+
+// Charge per cell (wire, metal and water; 0 elsewhere).
+size_t powder_world_export_charge(PowderWorldHandle handle, int16_t* out_buf, size_t max_len);
+
+// Steps since each cell last changed. The first call starts tracking and
+// reports all zeros.
+size_t powder_world_export_activity(PowderWorldHandle handle, int16_t* out_buf, size_t max_len);
+
+// Same-liquid cells above each cell (see World::liquid_depth_map). The
+// first call turns depth tracking on.
+size_t powder_world_export_depth(PowderWorldHandle handle, uint16_t* out_buf, size_t max_len);
+
+// Read-only pointer to the internal cell buffer (row-major, same layout as
+// `powder_world_export_cells`), with the cell count in `out_len`. No copy
+// is made: the pointer is only valid until the next call that mutates or
+// frees the world (step, clear, resize, brush, set_cell, load, ...).
+// Returns null on failure.
+const Cell* powder_world_cells_ptr(PowderWorldHandle handle, size_t* out_len);
+
+// Render the world as packed RGBA (width*height*4 bytes, row-major) into
+// `out_buf`. `palette` may be null for the default colors and tint.
+// Returns the full image size. Nothing is written when that exceeds
+// `max_len`, so call with a null buffer first to query the size.
+size_t powder_world_render_rgba(PowderWorldHandle handle, uint8_t* out_buf, size_t max_len, const Palette* palette);
+
+// Write emission_of() for every cell (width*height bytes, row-major) into
+// `out_buf`, for bloom and light halos. Same contract as
+// powder_world_render_rgba: returns the full size, writes only if it fits.
+size_t powder_world_render_emission(PowderWorldHandle handle, uint8_t* out_buf, size_t max_len);
+
+// Draw a translucent debug overlay (see overlay_rgba) into `out_buf`:
+// 0 charge, 1 life, 2 cells changed last step, 3 chunk activity (2 and 3
+// need provenance tracking). Same contract as powder_world_render_rgba;
+// returns 0 for an invalid `kind`.
+size_t powder_world_render_overlay(PowderWorldHandle handle, int32_t kind, uint8_t* out_buf, size_t max_len);
+
+// Fill `out_palette` with a built-in palette for powder_world_render_rgba:
+// 0 classic (ncurses colors), 1 xterm 256-color, 2 rich (the default).
+PowderStatus powder_palette_builtin(int32_t kind, Palette* out_palette);
+
+// Untinted color of an element under `palette` (null for the default),
+// packed as 0xRRGGBBAA. 0 for an invalid id.
+uint32_t powder_palette_color(const Palette* palette, int32_t elem, int32_t life);
+
+// Write row `y`'s glyphs (one ASCII byte per cell, as powder_glyph_of,
+// including life-based animation) into `out_bytes`. Returns the number of
+// bytes written: min(width, max).
+size_t powder_world_render_row_glyphs(PowderWorldHandle handle, int32_t y, uint8_t* out_bytes, size_t max);
+
+// Like powder_world_render_row_glyphs, but writes powder_color_of indices.
+size_t powder_world_render_row_colors(PowderWorldHandle handle, int32_t y, uint8_t* out_bytes, size_t max);
+
+// Serialize the world into `out_buf` using the binary save format.
+// Returns the full encoded size. Nothing is written when that exceeds
+// `max_len`, so call with a null buffer first to query the size.
+size_t powder_world_save(PowderWorldHandle handle, uint8_t* out_buf, size_t max_len);
+
+// Replace the world's size and cells with a save from `powder_world_save`.
+// Returns InvalidData if the bytes don't decode (world left untouched).
+PowderStatus powder_world_load(PowderWorldHandle handle, const uint8_t* buf, size_t len);
+
+// Serialize the world with the binary save format. Same contract as
+// powder_world_save: returns the full size and writes only if it fits.
+size_t powder_world_serialize(PowderWorldHandle handle, uint8_t* out_buf, size_t max_len);
+
+// Build a new world from serialized bytes. Returns null (see
+// powder_last_error_message) if the data is invalid. Free the result with
+// powder_world_free.
+PowderWorldHandle powder_world_deserialize(const uint8_t* buf, size_t len);
+
+// Move up to `max` pending events (oldest first) into `out_events`.
+// Returns how many were written; call again until it returns less than
+// `max` to drain the queue.
+size_t powder_world_poll_events(PowderWorldHandle handle, FfiEvent* out_events, size_t max);
+
+// Number of element ids (valid ids are 0..count).
+int32_t powder_element_count(void);
+
+// Life a freshly placed cell of `elem` starts with (see default_life);
+// 0 for an invalid id.
+int32_t powder_element_default_life(int32_t elem);
+
+// Copy the element's name plus a NUL terminator into `out_buf`. Returns the
+// size needed including the NUL (0 for an invalid id); nothing is written
+// when that exceeds `max_len`.
+size_t powder_element_name(int32_t elem, char* out_buf, size_t max_len);
+
+uint8_t powder_color_of(int32_t elem, int32_t life);
+
+// Brightness 0-255 for animating fire, lava and lightning at `tick` (see
+// powder_world_get_tick); 255 for other elements.
+uint8_t powder_shade_of(int32_t elem, int32_t life, uint64_t tick);
+
+// Light given off by a cell of `elem` with `life`, 0-255 (see
+// emission_of).
+uint8_t powder_emission_of(int32_t elem, int32_t life);
+
+// xterm 256-color index (see color256_of).
+uint8_t powder_color256_of(int32_t elem, int32_t life);
+
+uint8_t powder_glyph_of(int32_t elem, int32_t life);
+
+// Glyph with fire, water and gas animation for the cell at (x, y) at
+// `tick` (see glyph_of_animated).
+uint8_t powder_glyph_of_animated(int32_t elem, int32_t life, int32_t x, int32_t y, uint64_t tick);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* POWDERCORE_H */
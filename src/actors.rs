@@ -0,0 +1,335 @@
+// PowderCore - actor identity and health
+//
+// Humans, zombies, animals and players are ordinary cells, but frontends want to follow
+// individuals and the simulation wants them to take more than one hit. The
+// actor table keeps an id and health per actor, keyed by the cell index the
+// actor currently occupies:
+//
+//   - put() creates an entry when a cell becomes an actor and drops it when
+//     the actor dies or is overwritten (a Human bitten into a Zombie keeps
+//     its id),
+//   - actors only move by swapping themselves around in their own step
+//     functions, which go through move_actor(),
+//   - edits from outside the simulation mark the table stale, and the next
+//     step() re-indexes it: actors still in place keep their ids, new ones
+//     get fresh ids.
+//
+// Ids are stable across ticks but not across save/load or snapshot
+// restore.
+//
+// The table also keeps running totals (health, infected zombies) so
+// actor_stats() is O(1) for HUDs polling it every frame.
+
+use std::collections::BTreeMap;
+
+use crate::{Element, PlayerInput, Violation, World, WorldEvent};
+
+/// Health lost per tick while touching fire, lava, acid, toxic gas,
+/// chlorine or lightning.
+pub(crate) const HAZARD_DAMAGE: i32 = 34;
+/// Health lost per tick next to electrified water.
+pub(crate) const SHOCK_DAMAGE: i32 = 50;
+/// Health a zombie loses when a human lands a hit.
+pub(crate) const HIT_DAMAGE: i32 = 25;
+
+/// Identity and state of one human, zombie, animal or player.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Actor {
+    /// Unique within the world, never reused.
+    pub id: u64,
+    pub health: i32,
+    /// Building material a human has picked up (see shelter.rs).
+    pub carrying: Option<Element>,
+    /// Consecutive ticks a human has spent with its head underwater (see
+    /// swim.rs).
+    pub underwater: i32,
+    /// Buttons held, for Player actors (see player.rs).
+    pub input: PlayerInput,
+    /// Zombie that used to be a human.
+    pub infected: bool,
+    /// Where a zombie last saw (or heard from its horde about) a human.
+    pub target: Option<(i32, i32)>,
+    /// Cells of jump left to rise.
+    pub(crate) rise: i32,
+}
+
+/// Population summary from `World::actor_stats`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ActorStats {
+    pub humans: usize,
+    pub zombies: usize,
+    pub animals: usize,
+    pub players: usize,
+    /// Zombies that were bitten humans (included in `zombies`).
+    pub infected: usize,
+    /// Mean health over all actors; 0 when there are none.
+    pub average_health: f32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ActorTable {
+    by_cell: BTreeMap<usize, Actor>,
+    next_id: u64,
+    stale: bool,
+    health_sum: i64,
+    infected: usize,
+}
+
+impl ActorTable {
+    pub(crate) fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// The actor in cell `i`, creating one with `health` if there is none.
+    fn get_or_spawn(&mut self, i: usize, health: i32) -> &mut Actor {
+        let next_id = &mut self.next_id;
+        let health_sum = &mut self.health_sum;
+        self.by_cell.entry(i).or_insert_with(|| {
+            *next_id += 1;
+            *health_sum += health as i64;
+            Actor {
+                id: *next_id,
+                health,
+                carrying: None,
+                underwater: 0,
+                input: PlayerInput::default(),
+                infected: false,
+                target: None,
+                rise: 0,
+            }
+        })
+    }
+
+    fn remove(&mut self, i: usize) -> Option<Actor> {
+        let a = self.by_cell.remove(&i)?;
+        self.health_sum -= a.health as i64;
+        self.infected -= a.infected as usize;
+        Some(a)
+    }
+}
+
+pub(crate) fn is_actor(e: Element) -> bool {
+    matches!(
+        e,
+        Element::Human | Element::Zombie | Element::Animal | Element::Player
+    )
+}
+
+impl World {
+    /// The actor standing at (x, y), if any.
+    pub fn actor_at(&self, x: i32, y: i32) -> Option<Actor> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        self.actors.by_cell.get(&self.idx(x, y)).copied()
+    }
+
+    /// Every tracked actor with its position, in row-major order.
+    pub fn actors(&self) -> impl Iterator<Item = (i32, i32, Actor)> + '_ {
+        let w = self.width.max(1) as usize;
+        self.actors
+            .by_cell
+            .iter()
+            .map(move |(&i, &a)| ((i % w) as i32, (i / w) as i32, a))
+    }
+
+    /// Head counts and average health of the actors in the world, for HUDs
+    /// and win/lose checks. Kept up to date as the simulation runs; after
+    /// direct cell edits the health and infected figures only cover actors
+    /// the table knew about until the next step().
+    pub fn actor_stats(&self) -> ActorStats {
+        let mut stats = ActorStats {
+            humans: self.count(Element::Human),
+            zombies: self.count(Element::Zombie),
+            animals: self.count(Element::Animal),
+            players: self.count(Element::Player),
+            ..ActorStats::default()
+        };
+        let (sum, n) = if self.actors.stale {
+            let live: Vec<&Actor> = self
+                .actors
+                .by_cell
+                .iter()
+                .filter(|&(&i, _)| i < self.cells.len() && is_actor(self.cells[i].elem))
+                .map(|(_, a)| a)
+                .collect();
+            stats.infected = live.iter().filter(|a| a.infected).count();
+            (live.iter().map(|a| a.health as i64).sum(), live.len())
+        } else {
+            stats.infected = self.actors.infected;
+            (self.actors.health_sum, self.actors.by_cell.len())
+        };
+        if n > 0 {
+            stats.average_health = sum as f32 / n as f32;
+        }
+        stats
+    }
+
+    /// Where the actor with `id` is now, if it's still alive.
+    pub fn find_actor(&self, id: u64) -> Option<(i32, i32)> {
+        self.actors()
+            .find(|&(_, _, a)| a.id == id)
+            .map(|(x, y, _)| (x, y))
+    }
+
+    fn max_health(&self, e: Element) -> i32 {
+        match e {
+            Element::Zombie => self.config.zombie_health,
+            Element::Animal => self.config.animal_health,
+            _ => self.config.human_health,
+        }
+    }
+
+    pub(crate) fn pos_of(&self, i: usize) -> (i32, i32) {
+        let w = self.width.max(1) as usize;
+        ((i % w) as i32, (i / w) as i32)
+    }
+
+    /// Keep the table in sync with put() changing cell `i` from `from` to
+    /// `to`, reporting actors that appear, turn or die.
+    pub(crate) fn track_actor(&mut self, i: usize, from: Element, to: Element) {
+        if from == to {
+            return;
+        }
+        let (x, y) = self.pos_of(i);
+        match (is_actor(from), is_actor(to)) {
+            (true, false) => {
+                if let Some(a) = self.actors.remove(i) {
+                    self.emit(WorldEvent::ActorDied {
+                        x,
+                        y,
+                        id: a.id,
+                        elem: from,
+                    });
+                }
+            }
+            (was_actor, true) => {
+                // a bitten human keeps its id but starts over as a zombie
+                let known = self.actors.by_cell.contains_key(&i);
+                let health = self.max_health(to);
+                let turned = known && was_actor && from == Element::Human && to == Element::Zombie;
+                let a = self.actors.get_or_spawn(i, health);
+                let (old_health, was_infected) = (a.health, a.infected);
+                a.health = health;
+                a.carrying = None;
+                a.target = None;
+                a.infected = turned || (to == Element::Zombie && a.infected);
+                let (id, infected) = (a.id, a.infected);
+                self.actors.health_sum += (health - old_health) as i64;
+                self.actors.infected =
+                    self.actors.infected + infected as usize - was_infected as usize;
+                if !known || !was_actor {
+                    self.emit(WorldEvent::ActorSpawned { x, y, id, elem: to });
+                } else if turned {
+                    self.emit(WorldEvent::ActorInfected { x, y, id });
+                }
+            }
+            (false, false) => {}
+        }
+    }
+
+    /// Id of the actor in cell `i`, if it's tracked.
+    pub(crate) fn actor_id(&self, i: usize) -> Option<u64> {
+        self.actors.by_cell.get(&i).map(|a| a.id)
+    }
+
+    /// Swap cells `from` and `to` where `from` holds an actor moving into
+    /// an actor-free cell.
+    pub(crate) fn move_actor(&mut self, from: usize, to: usize) {
+        self.swap_cells(from, to);
+        if let Some(a) = self.actors.by_cell.remove(&from) {
+            self.actors.by_cell.insert(to, a);
+        }
+    }
+
+    pub(crate) fn actor_mut(&mut self, i: usize) -> Option<&mut Actor> {
+        self.actors.by_cell.get_mut(&i)
+    }
+
+    /// Take `damage` from the actor in cell `i`. Returns true if that
+    /// killed it; the caller turns the cell into whatever remains.
+    pub(crate) fn hurt(&mut self, i: usize, damage: i32) -> bool {
+        let health = self.max_health(self.cells[i].elem);
+        let a = self.actors.get_or_spawn(i, health);
+        a.health -= damage;
+        let dead = a.health <= 0;
+        self.actors.health_sum -= damage as i64;
+        dead
+    }
+
+    /// Re-key the table after the grid (formerly `old_w` wide) grew by
+    /// `dx` columns on the left and `dy` rows on top.
+    pub(crate) fn shift_actors(&mut self, old_w: i32, dx: i32, dy: i32) {
+        let (old_w, new_w) = (old_w.max(1) as usize, self.width as usize);
+        let by_cell = std::mem::take(&mut self.actors.by_cell);
+        self.actors.by_cell = by_cell
+            .into_iter()
+            .map(|(i, mut a)| {
+                let x = (i % old_w) as i32 + dx;
+                let y = (i / old_w) as i32 + dy;
+                a.target = a.target.map(|(tx, ty)| (tx + dx, ty + dy));
+                (y as usize * new_w + x as usize, a)
+            })
+            .collect();
+    }
+
+    /// Re-index the table after edits that bypassed put().
+    pub(crate) fn refresh_actors(&mut self) {
+        if !self.actors.stale {
+            return;
+        }
+        let cells = &self.cells;
+        let ActorTable {
+            by_cell,
+            health_sum,
+            infected,
+            ..
+        } = &mut self.actors;
+        by_cell.retain(|&i, a| {
+            let keep = i < cells.len() && is_actor(cells[i].elem);
+            if !keep {
+                *health_sum -= a.health as i64;
+                *infected -= a.infected as usize;
+            }
+            keep
+        });
+        for i in 0..self.cells.len() {
+            let e = self.cells[i].elem;
+            if !is_actor(e) || self.actors.by_cell.contains_key(&i) {
+                continue;
+            }
+            let health = self.max_health(e);
+            let id = self.actors.get_or_spawn(i, health).id;
+            let (x, y) = self.pos_of(i);
+            self.emit(WorldEvent::ActorSpawned { x, y, id, elem: e });
+        }
+        self.actors.stale = false;
+    }
+
+    /// The table agrees with the grid (see validate.rs). Skipped while it
+    /// is stale.
+    pub(crate) fn check_actors(&self) -> Result<(), Violation> {
+        if self.actors.stale {
+            return Ok(());
+        }
+        for (&i, a) in &self.actors.by_cell {
+            if i >= self.cells.len() || !is_actor(self.cells[i].elem) {
+                let (x, y) = self.pos_of(i);
+                return Err(Violation::StrayActor { x, y, id: a.id });
+            }
+        }
+        for (i, c) in self.cells.iter().enumerate() {
+            if is_actor(c.elem) && !self.actors.by_cell.contains_key(&i) {
+                let (x, y) = self.pos_of(i);
+                return Err(Violation::UntrackedActor { x, y, elem: c.elem });
+            }
+        }
+        let actors = self.actors.by_cell.values();
+        let health: i64 = actors.clone().map(|a| a.health as i64).sum();
+        let infected = actors.filter(|a| a.infected).count();
+        if health != self.actors.health_sum || infected != self.actors.infected {
+            return Err(Violation::ActorTotals);
+        }
+        Ok(())
+    }
+}
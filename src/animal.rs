@@ -0,0 +1,102 @@
+// PowderCore - animal companions
+//
+// An Animal is a small pet-like actor (a cat or a dog, as far as the
+// frontend is concerned). It has no fight/infect loop of its own; it just
+// follows the nearest human it can see, keeps a cell of distance, and
+// runs from zombies and hazards, which take priority over following.
+// Hazards hurt it through the actor table like any other actor.
+
+use crate::actors::HAZARD_DAMAGE;
+use crate::{is_gas, is_hazard, Element, World};
+
+impl World {
+    /// Nearest cell within `r` (square radius) of (x, y) matching `pred`
+    /// and in line of sight.
+    fn nearest<F>(&self, x: i32, y: i32, r: i32, pred: F) -> Option<(i32, i32)>
+    where
+        F: Fn(Element) -> bool,
+    {
+        let mut best = None;
+        let mut best_d = i32::MAX;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let (nx, ny) = (x + dx, y + dy);
+                if (dx, dy) == (0, 0) || !self.in_bounds(nx, ny) {
+                    continue;
+                }
+                let d = dx * dx + dy * dy;
+                if d < best_d
+                    && pred(self.cells[self.idx(nx, ny)].elem)
+                    && self.can_see(x, y, nx, ny)
+                {
+                    best = Some((nx, ny));
+                    best_d = d;
+                }
+            }
+        }
+        best
+    }
+
+    pub(crate) fn step_animal(&mut self, x: i32, y: i32, updated: &mut [bool]) {
+        let idx0 = self.idx(x, y);
+        updated[idx0] = true;
+
+        let touching = (-1..=1).any(|dy| {
+            (-1..=1).any(|dx| {
+                let (nx, ny) = (x + dx, y + dy);
+                self.in_bounds(nx, ny) && is_hazard(self.cells[self.idx(nx, ny)].elem)
+            })
+        });
+        if touching && self.hurt(idx0, HAZARD_DAMAGE) {
+            self.put(idx0, Element::Ash, 0);
+            return;
+        }
+
+        self.cells[idx0].life += 1;
+
+        if self.in_bounds(x, y + 1) {
+            let idx_b = self.idx(x, y + 1);
+            let b = self.cells[idx_b].elem;
+            if b == Element::Empty || is_gas(b) {
+                self.move_actor(idx0, idx_b);
+                updated[idx_b] = true;
+                return;
+            }
+        }
+
+        let r = self.config.animal_vision;
+        let threat = self.nearest(x, y, r, |e| e == Element::Zombie || is_hazard(e));
+        let dir = if let Some((tx, _)) = threat {
+            if tx <= x {
+                1
+            } else {
+                -1
+            }
+        } else if let Some((hx, _)) = self.nearest(x, y, r, |e| e == Element::Human) {
+            if (hx - x).abs() <= 1 {
+                return;
+            }
+            (hx - x).signum()
+        } else if self.rng.chance(30) {
+            if self.rng.chance(50) {
+                1
+            } else {
+                -1
+            }
+        } else {
+            return;
+        };
+
+        if self.try_walk(x, y, x + dir, y) {
+            return;
+        }
+        // hop up a one-cell step
+        if self.in_bounds(x + dir, y - 1)
+            && self.cells[self.idx(x + dir, y - 1)].elem == Element::Empty
+            && self.cells[self.idx(x, y - 1)].elem == Element::Empty
+        {
+            let idx_up = self.idx(x, y - 1);
+            self.move_actor(idx0, idx_up);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// PowderCore - ASCII scenes
+//
+// Text import/export using the same glyphs as glyph_of(), handy for small
+// test scenes and pasting world states into bug reports:
+//
+//     let w = World::from_ascii("  ...  \n#######")?;
+//     println!("{w:#}"); // framed glyph grid
+//
+// Export is lossy for life values: animated glyphs (y/Y, t/T) and timers
+// come back as freshly placed cells.
+
+use std::fmt;
+
+use crate::{glyph_of, default_life, Cell, Element, World, ELEMENT_TABLE};
+
+/// A character in an ASCII scene that isn't any element's glyph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AsciiError {
+    pub x: i32,
+    pub y: i32,
+    pub ch: char,
+}
+
+impl fmt::Display for AsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown glyph {:?} at ({}, {})", self.ch, self.x, self.y)
+    }
+}
+
+impl std::error::Error for AsciiError {}
+
+/// Inverse of `glyph_of`. Both animation frames of actors map back to them.
+pub fn element_from_glyph(ch: char) -> Option<Element> {
+    match ch {
+        'y' => return Some(Element::Human),
+        't' => return Some(Element::Zombie),
+        _ => {}
+    }
+    ELEMENT_TABLE.iter().copied().find(|&e| glyph_of(e, 0) == ch)
+}
+
+impl World {
+    /// One line of glyphs per row, each terminated by '\n'.
+    pub fn to_ascii(&self) -> String {
+        let mut out = String::with_capacity(self.cells.len() + self.height.max(0) as usize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = self.cells[self.idx(x, y)];
+                out.push(glyph_of(c.elem, c.life));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// `to_ascii`, optionally framed with a +--+ border so leading and
+    /// trailing empty cells stay visible in logs.
+    pub fn debug_string(&self, border: bool) -> String {
+        if !border {
+            return self.to_ascii();
+        }
+        let edge = format!("+{}+\n", "-".repeat(self.width.max(0) as usize));
+        let mut out = edge.clone();
+        for line in self.to_ascii().lines() {
+            out.push('|');
+            out.push_str(line);
+            out.push_str("|\n");
+        }
+        out.push_str(&edge);
+        out
+    }
+
+    /// Build a world from glyph lines. Width is the longest line; shorter
+    /// lines are padded with Empty. The RNG starts from the default seed.
+    pub fn from_ascii(text: &str) -> Result<World, AsciiError> {
+        let lines: Vec<&str> = text.lines().map(|l| l.trim_end_matches('\r')).collect();
+        let height = lines.len() as i32;
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as i32;
+
+        let mut world = World::new(width, height, 0);
+        for (y, line) in lines.iter().enumerate() {
+            for (x, ch) in line.chars().enumerate() {
+                let elem = element_from_glyph(ch).ok_or(AsciiError {
+                    x: x as i32,
+                    y: y as i32,
+                    ch,
+                })?;
+                let idx = world.idx(x as i32, y as i32);
+                world.cells[idx] = Cell {
+                    elem,
+                    life: default_life(elem),
+                };
+            }
+        }
+        world.cells_changed();
+        Ok(world)
+    }
+}
+
+/// The glyph grid (`to_ascii` without the final newline); the alternate
+/// form `{:#}` adds a border.
+impl fmt::Display for World {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = self.debug_string(f.alternate());
+        f.write_str(s.strip_suffix('\n').unwrap_or(&s))
+    }
+}
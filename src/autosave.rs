@@ -0,0 +1,93 @@
+// PowderCore - automatic snapshot ring
+//
+// When enabled, step() stores an RLE-compressed save (which includes the
+// RNG state) every N ticks and keeps the last K of them, so frontends get "oops, go back" for
+// free without managing their own save files.
+
+use std::collections::VecDeque;
+
+use crate::World;
+
+pub(crate) struct AutoSnapshots {
+    every: u32,
+    keep: usize,
+    since: u32,
+    ring: VecDeque<Vec<u8>>,
+}
+
+impl World {
+    /// Save a compressed snapshot every `every_n_ticks` steps, keeping the
+    /// newest `keep_last_k`. Replaces any previous ring. Zero for either
+    /// argument disables autosnapshots.
+    pub fn enable_autosnapshot(&mut self, every_n_ticks: u32, keep_last_k: usize) {
+        if every_n_ticks == 0 || keep_last_k == 0 {
+            self.autosnap = None;
+            return;
+        }
+        self.autosnap = Some(AutoSnapshots {
+            every: every_n_ticks,
+            keep: keep_last_k,
+            since: 0,
+            ring: VecDeque::with_capacity(keep_last_k),
+        });
+    }
+
+    /// Stop taking autosnapshots and drop the ring.
+    pub fn disable_autosnapshot(&mut self) {
+        self.autosnap = None;
+    }
+
+    /// Number of autosnapshots currently available to `rewind`.
+    pub fn autosnapshot_count(&self) -> usize {
+        self.autosnap.as_ref().map_or(0, |a| a.ring.len())
+    }
+
+    /// Go back to the `k`-th most recent autosnapshot (1 = newest).
+    /// Newer snapshots are discarded; the restored one stays in the ring.
+    /// Returns false (and changes nothing) if there aren't `k` snapshots.
+    pub fn rewind(&mut self, k: usize) -> bool {
+        let Some(auto) = self.autosnap.as_mut() else {
+            return false;
+        };
+        if k == 0 || k > auto.ring.len() {
+            return false;
+        }
+        auto.ring.truncate(auto.ring.len() - (k - 1));
+        auto.since = 0;
+        let bytes = auto.ring.back().cloned().expect("ring has k entries");
+
+        let Ok(loaded) = World::load_from_bytes(&bytes) else {
+            return false;
+        };
+        self.width = loaded.width;
+        self.height = loaded.height;
+        self.cells = loaded.cells;
+        self.tick = loaded.tick;
+        self.rng = loaded.rng;
+        self.cells_changed();
+        self.clear_undo();
+        true
+    }
+
+    /// Called at the end of every step().
+    pub(crate) fn autosnapshot_tick(&mut self) {
+        let due = match self.autosnap.as_mut() {
+            Some(auto) => {
+                auto.since += 1;
+                auto.since >= auto.every
+            }
+            None => false,
+        };
+        if !due {
+            return;
+        }
+        let bytes = self.save_to_bytes();
+        if let Some(auto) = self.autosnap.as_mut() {
+            auto.since = 0;
+            if auto.ring.len() == auto.keep {
+                auto.ring.pop_front();
+            }
+            auto.ring.push_back(bytes);
+        }
+    }
+}
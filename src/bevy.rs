@@ -0,0 +1,132 @@
+// PowderCore - Bevy plugin (feature "bevy")
+//
+// PowderPlugin drops the engine into a Bevy app:
+//
+//   App::new()
+//       .add_plugins((DefaultPlugins, PowderPlugin::new(320, 180, 1)))
+//       .add_systems(Startup, |mut commands: Commands, tex: Res<PowderTexture>| {
+//           commands.spawn(Sprite::from_image(tex.0.clone()));
+//       })
+//
+// The World lives in the PowderWorld resource together with its
+// FixedTimestep, so the game edits it like any other resource. Each Update,
+// PowderSystems steps it at the fixed rate and, if anything changed (a
+// step, or the game writing through ResMut<PowderWorld>), renders the grid
+// into the PowderTexture image. Order systems that paint into the world
+// `.before(PowderSystems)` to see their edits on the same frame.
+//
+// Only the Bevy sub-crates the plugin touches are pulled in, and only with
+// this feature; the image asset must be registered (DefaultPlugins does).
+
+use bevy_app::{App, Plugin, Update};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::prelude::*;
+use bevy_image::{Image, ImageSampler};
+use bevy_time::Time;
+
+use crate::{FixedTimestep, Palette, World};
+
+/// Adds the PowderWorld and PowderTexture resources and the systems that
+/// step and draw them.
+#[derive(Copy, Clone, Debug)]
+pub struct PowderPlugin {
+    pub width: i32,
+    pub height: i32,
+    pub seed: u64,
+    /// Tick rate and catch-up cap for stepping.
+    pub clock: FixedTimestep,
+    /// Colors the texture is drawn with.
+    pub palette: Palette,
+}
+
+impl PowderPlugin {
+    /// Empty world of this size, stepped at 60 ticks per second.
+    pub fn new(width: i32, height: i32, seed: u64) -> Self {
+        PowderPlugin {
+            width,
+            height,
+            seed,
+            clock: FixedTimestep::default(),
+            palette: Palette::DEFAULT,
+        }
+    }
+}
+
+/// The simulated world and the clock that steps it.
+#[derive(Resource)]
+pub struct PowderWorld {
+    pub world: World,
+    pub clock: FixedTimestep,
+    pub palette: Palette,
+}
+
+/// Handle of the image the grid is drawn into, one texel per cell.
+#[derive(Resource, Clone, Debug)]
+pub struct PowderTexture(pub Handle<Image>);
+
+/// Steps the world, then redraws the texture if it changed.
+#[derive(SystemSet, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PowderSystems;
+
+impl Plugin for PowderPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PowderWorld {
+            world: World::new(self.width, self.height, self.seed),
+            clock: self.clock,
+            palette: self.palette,
+        })
+        .add_systems(
+            Update,
+            (step_world, draw_texture).chain().in_set(PowderSystems),
+        );
+    }
+
+    // The texture is made here rather than in a Startup system so its
+    // handle is there for the game's own Startup systems.
+    fn finish(&self, app: &mut App) {
+        let world = app.world_mut();
+        let mut image = Image {
+            sampler: ImageSampler::nearest(),
+            ..Image::default()
+        };
+        let powder = world.resource::<PowderWorld>();
+        draw(&mut image, &powder.world, &powder.palette);
+        let handle = world
+            .get_resource_mut::<Assets<Image>>()
+            .expect("PowderPlugin needs image assets (add DefaultPlugins or ImagePlugin first)")
+            .add(image);
+        world.insert_resource(PowderTexture(handle));
+    }
+}
+
+fn step_world(time: Res<Time>, mut powder: ResMut<PowderWorld>) {
+    // only a real step counts as a change, not the clock ticking over
+    let p = powder.bypass_change_detection();
+    if p.clock.advance(&mut p.world, time.delta_secs_f64()) > 0 {
+        powder.set_changed();
+    }
+}
+
+fn draw_texture(
+    powder: Res<PowderWorld>,
+    texture: Res<PowderTexture>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if powder.is_changed() {
+        if let Some(image) = images.get_mut(&texture.0) {
+            draw(image, &powder.world, &powder.palette);
+        }
+    }
+}
+
+/// Size `image` to `world` (following resizes) and render the grid into it.
+fn draw(image: &mut Image, world: &World, palette: &Palette) {
+    let size = &mut image.texture_descriptor.size;
+    // zero-sized textures are invalid, so an empty world is one blank texel
+    size.width = world.width().max(1) as u32;
+    size.height = world.height().max(1) as u32;
+    let len = size.width as usize * size.height as usize * 4;
+    image.data.clear();
+    image.data.resize(len, 0);
+    world.render_rgba_into(&mut image.data, palette);
+}
@@ -0,0 +1,195 @@
+// PowderCore - headless scenario runner
+//
+// Loads a saved world or an ASCII scene, steps it without a frontend and
+// reports what happened, for batch experiments and scenario checks in CI:
+//
+//     powdercore-run scene.txt --ticks 600 --png end.png --expect-hash 9f3c...
+//
+// The scene's format is sniffed: files starting with the save magic load
+// as saves, anything else is read as an ASCII scene (see ascii.rs). Stats
+// go to stdout as `key value` lines unless --stats names a file. Exit
+// status is 0 on success, 1 if loading, writing or --expect-hash failed
+// and 2 for bad arguments.
+//
+// Built with the "cli" feature: cargo run --features cli --bin powdercore-run
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use std::{env, fs};
+
+use powdercore::{name_of, Element, Palette, World};
+
+const USAGE: &str = "\
+usage: powdercore-run <scene> [options]
+
+  <scene>              save file or ASCII scene
+  --ticks N            steps to run (default 100)
+  --stats FILE         write stats to FILE instead of stdout
+  --save FILE          write the final world as a save
+  --png FILE           write the final world as a PNG
+  --palette NAME       PNG palette: classic, xterm256 or rich (default)
+  --expect-hash HEX    fail unless the final state_hash matches";
+
+struct Args {
+    scene: PathBuf,
+    ticks: u64,
+    stats: Option<PathBuf>,
+    save: Option<PathBuf>,
+    png: Option<PathBuf>,
+    palette: Palette,
+    expect_hash: Option<u64>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut scene = None;
+    let mut out = Args {
+        scene: PathBuf::new(),
+        ticks: 100,
+        stats: None,
+        save: None,
+        png: None,
+        palette: Palette::DEFAULT,
+        expect_hash: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--ticks" => {
+                let v = value()?;
+                out.ticks = v.parse().map_err(|_| format!("bad tick count {v:?}"))?;
+            }
+            "--stats" => out.stats = Some(value()?.into()),
+            "--save" => out.save = Some(value()?.into()),
+            "--png" => out.png = Some(value()?.into()),
+            "--palette" => {
+                out.palette = match value()?.as_str() {
+                    "classic" => Palette::classic(),
+                    "xterm256" => Palette::xterm256(),
+                    "rich" => Palette::rich(),
+                    other => return Err(format!("unknown palette {other:?}")),
+                }
+            }
+            "--expect-hash" => {
+                let v = value()?;
+                let hex = v.trim_start_matches("0x");
+                let hash = u64::from_str_radix(hex, 16).map_err(|_| format!("bad hash {v:?}"))?;
+                out.expect_hash = Some(hash);
+            }
+            "-h" | "--help" => return Err(String::new()),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {arg}")),
+            _ if scene.is_none() => scene = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument {arg:?}")),
+        }
+    }
+    out.scene = scene.ok_or("no scene given")?;
+    Ok(out)
+}
+
+fn load_scene(path: &PathBuf) -> Result<World, String> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    if bytes.starts_with(b"PWDR") {
+        return World::load_from_bytes(&bytes).map_err(|e| format!("{}: {e}", path.display()));
+    }
+    let text = String::from_utf8(bytes)
+        .map_err(|_| format!("{}: neither a save nor a text scene", path.display()))?;
+    World::from_ascii(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Step totals over the whole run.
+#[derive(Default)]
+struct Totals {
+    visited: u64,
+    moved: u64,
+    reactions: u64,
+    explosions: u64,
+    busiest: Duration,
+}
+
+fn report(world: &World, ticks: u64, totals: &Totals, wall: Duration) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "size {}x{}", world.width(), world.height());
+    let _ = writeln!(out, "ticks {ticks}");
+    let _ = writeln!(out, "final_tick {}", world.tick());
+    let _ = writeln!(out, "state_hash {:016x}", world.state_hash());
+    let _ = writeln!(out, "cells_visited {}", totals.visited);
+    let _ = writeln!(out, "cells_moved {}", totals.moved);
+    let _ = writeln!(out, "reactions {}", totals.reactions);
+    let _ = writeln!(out, "explosions {}", totals.explosions);
+    let _ = writeln!(out, "wall_ms {:.3}", wall.as_secs_f64() * 1e3);
+    let _ = writeln!(
+        out,
+        "slowest_step_ms {:.3}",
+        totals.busiest.as_secs_f64() * 1e3
+    );
+    for e in Element::ALL {
+        let n = world.count(e);
+        if e != Element::Empty && n > 0 {
+            let _ = writeln!(
+                out,
+                "count.{} {n}",
+                name_of(e).to_lowercase().replace(' ', "_")
+            );
+        }
+    }
+    out
+}
+
+fn run(args: &Args) -> Result<bool, String> {
+    let mut world = load_scene(&args.scene)?;
+    let mut totals = Totals::default();
+    let started = Instant::now();
+    for _ in 0..args.ticks {
+        world.step();
+        let s = world.last_step_stats();
+        totals.visited += s.cells_visited;
+        totals.moved += s.cells_moved;
+        totals.reactions += s.reactions;
+        totals.explosions += s.explosions;
+        totals.busiest = totals.busiest.max(s.duration);
+    }
+    let stats = report(&world, args.ticks, &totals, started.elapsed());
+
+    match &args.stats {
+        Some(path) => fs::write(path, &stats).map_err(|e| format!("{}: {e}", path.display()))?,
+        None => print!("{stats}"),
+    }
+    if let Some(path) = &args.save {
+        fs::write(path, world.save_to_bytes()).map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+    if let Some(path) = &args.png {
+        world
+            .screenshot_png(path, &args.palette)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+    if let Some(want) = args.expect_hash {
+        let got = world.state_hash();
+        if got != want {
+            eprintln!("powdercore-run: state_hash {got:016x}, expected {want:016x}");
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(msg) => {
+            if !msg.is_empty() {
+                eprintln!("powdercore-run: {msg}");
+            }
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(msg) => {
+            eprintln!("powdercore-run: {msg}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,199 @@
+// PowderCore - brush shapes and variants
+//
+// place_brush paints a filled circle over whatever is there. The variants
+// here change the footprint (BrushShape), scatter paint (density) or
+// restrict which cells a brush touches; Brush bundles all of them.
+
+use std::ops::RangeInclusive;
+
+use crate::save::{unzigzag, write_varint, zigzag, ByteSource};
+use crate::{EdgeMode, Element, LoadError, World};
+
+/// Footprint of a brush of radius `rad` around its centre.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BrushShape {
+    /// Filled circle (what `place_brush` uses).
+    #[default]
+    Circle,
+    /// Filled (2*rad+1)-wide square.
+    Square,
+    /// One-cell-wide circle outline.
+    Ring,
+    /// Circle where each cell is painted with `density` percent chance.
+    Spray { density: u8 },
+    /// Vertical line 2*rad+1 cells tall.
+    VerticalLine,
+    /// Horizontal line 2*rad+1 cells wide.
+    HorizontalLine,
+}
+
+impl BrushShape {
+    /// Whether offset (dx, dy) is inside the shape (Spray: before the
+    /// density roll).
+    fn covers(self, dx: i64, dy: i64, rad: i64) -> bool {
+        let d2 = dx * dx + dy * dy;
+        match self {
+            BrushShape::Circle | BrushShape::Spray { .. } => d2 <= rad * rad,
+            BrushShape::Square => true,
+            BrushShape::Ring => d2 <= rad * rad && (rad == 0 || d2 > (rad - 1) * (rad - 1)),
+            BrushShape::VerticalLine => dx == 0,
+            BrushShape::HorizontalLine => dy == 0,
+        }
+    }
+}
+
+/// Brush settings for `World::apply_brush`: shape, size, how densely it
+/// paints and, optionally, which element it is allowed to replace.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Brush {
+    pub shape: BrushShape,
+    pub radius: i32,
+    /// Percent chance (0-100) that each covered cell is painted. Below 100
+    /// the brush scatters particles instead of stamping a solid shape.
+    pub density: u8,
+    /// Only replace cells holding this element (None = replace anything).
+    pub only: Option<Element>,
+}
+
+impl Brush {
+    /// Solid circle of radius `radius`, the same footprint as `place_brush`.
+    pub fn new(radius: i32) -> Self {
+        Brush {
+            shape: BrushShape::Circle,
+            radius,
+            density: 100,
+            only: None,
+        }
+    }
+}
+
+/// Append `brush` in the byte layout netplay frames and edit commands
+/// share: u8 shape (+ u8 density for Spray), zigzag varint radius, u8
+/// density, u8 `only` element id (255 = any).
+pub(crate) fn write_brush(out: &mut Vec<u8>, brush: &Brush) {
+    match brush.shape {
+        BrushShape::Circle => out.push(0),
+        BrushShape::Square => out.push(1),
+        BrushShape::Ring => out.push(2),
+        BrushShape::Spray { density } => out.extend_from_slice(&[3, density]),
+        BrushShape::VerticalLine => out.push(4),
+        BrushShape::HorizontalLine => out.push(5),
+    }
+    write_varint(out, zigzag(brush.radius));
+    out.push(brush.density);
+    out.push(brush.only.map_or(255, |e| e as i32 as u8));
+}
+
+/// Inverse of `write_brush`. Unknown shape or element ids are Corrupt.
+pub(crate) fn read_brush<S: ByteSource>(r: &mut S) -> Result<Brush, LoadError> {
+    let shape = match r.u8()? {
+        0 => BrushShape::Circle,
+        1 => BrushShape::Square,
+        2 => BrushShape::Ring,
+        3 => BrushShape::Spray { density: r.u8()? },
+        4 => BrushShape::VerticalLine,
+        5 => BrushShape::HorizontalLine,
+        _ => return Err(LoadError::Corrupt),
+    };
+    let radius = unzigzag(r.varint()?);
+    let density = r.u8()?;
+    let only = match r.u8()? {
+        255 => None,
+        id => Some(Element::from_id(id as i32).ok_or(LoadError::Corrupt)?),
+    };
+    Ok(Brush {
+        shape,
+        radius,
+        density,
+        only,
+    })
+}
+
+/// Offsets from `c` along an axis of `len` cells that a brush of radius
+/// `rad` can usefully visit: the ones landing on the grid, or within one lap
+/// of it when the edges wrap. A radius far bigger than the world (say, from
+/// a host app passing garbage) then costs no more than the grid itself.
+pub(crate) fn reach(c: i32, len: i32, rad: i32, wrap: bool) -> RangeInclusive<i64> {
+    let (c, len, rad) = (c as i64, len as i64, rad as i64);
+    if wrap {
+        (-rad).max(-len)..=rad.min(len)
+    } else {
+        (-rad).max(-c)..=rad.min(len - 1 - c)
+    }
+}
+
+impl World {
+    /// Paint `elem` at (cx, cy) with `brush`. Lightning ignores the brush
+    /// settings and drops a single bolt from (cx, cy), as with
+    /// `place_brush`. Density rolls use the world RNG, so recorded brush
+    /// input replays identically.
+    pub fn apply_brush(&mut self, cx: i32, cy: i32, elem: Element, brush: &Brush) {
+        if elem == Element::Lightning {
+            self.place_lightning(cx, cy);
+            return;
+        }
+        let rad = brush.radius;
+        let wrap = self.edge_mode == EdgeMode::Wrap;
+        for dy in reach(cy, self.height, rad, wrap) {
+            for dx in reach(cx, self.width, rad, wrap) {
+                let (Ok(x), Ok(y)) = (i32::try_from(cx as i64 + dx), i32::try_from(cy as i64 + dy))
+                else {
+                    continue;
+                };
+                if !brush.shape.covers(dx, dy, rad as i64) || !self.in_bounds(x, y) {
+                    continue;
+                }
+                if brush
+                    .only
+                    .is_some_and(|e| self.cells[self.idx(x, y)].elem != e)
+                {
+                    continue;
+                }
+                if let BrushShape::Spray { density } = brush.shape {
+                    if !self.rng.chance(density as u32) {
+                        continue;
+                    }
+                }
+                if !self.rng.chance(brush.density as u32) {
+                    continue;
+                }
+                self.paint(x, y, elem);
+            }
+        }
+    }
+
+    /// Place a brush of the given shape. Lightning ignores the shape and
+    /// drops a single bolt from (cx, cy), as with `place_brush`.
+    pub fn place_brush_shape(
+        &mut self,
+        cx: i32,
+        cy: i32,
+        rad: i32,
+        elem: Element,
+        shape: BrushShape,
+    ) {
+        let brush = Brush {
+            shape,
+            ..Brush::new(rad)
+        };
+        self.apply_brush(cx, cy, elem, &brush);
+    }
+
+    /// Like `place_brush`, but only cells currently holding `only` are
+    /// replaced. With `elem = Element::Empty` this is a selective eraser
+    /// (remove the water, keep the sand castle).
+    pub fn place_brush_filtered(
+        &mut self,
+        cx: i32,
+        cy: i32,
+        rad: i32,
+        elem: Element,
+        only: Element,
+    ) {
+        let brush = Brush {
+            only: Some(only),
+            ..Brush::new(rad)
+        };
+        self.apply_brush(cx, cy, elem, &brush);
+    }
+}
@@ -0,0 +1,306 @@
+// PowderCore - minimal bzip2 decoder
+//
+// Just enough bzip2 to read Powder Toy saves without pulling in a crate:
+// Huffman + MTF/RLE2 decode, inverse BWT, then the initial RLE1 pass.
+// Concatenated streams are supported; block CRCs are not verified and the
+// long-deprecated randomized blocks are rejected.
+
+const BLOCK_MAGIC: u64 = 0x3141_5926_5359;
+const EOS_MAGIC: u64 = 0x1772_4538_5090;
+const MAX_GROUPS: usize = 6;
+const MAX_ALPHA: usize = 258;
+const MAX_CODE_LEN: u32 = 20;
+const GROUP_SIZE: usize = 50;
+
+/// Decompress a complete bzip2 buffer. `limit` caps the output size.
+pub(crate) fn decompress(data: &[u8], limit: usize) -> Option<Vec<u8>> {
+    let mut bits = BitReader { data, pos: 0 };
+    let mut out = Vec::new();
+
+    loop {
+        if bits.read(8)? != b'B' as u32
+            || bits.read(8)? != b'Z' as u32
+            || bits.read(8)? != b'h' as u32
+        {
+            return None;
+        }
+        let level = bits.read(8)?;
+        if !(b'1' as u32..=b'9' as u32).contains(&level) {
+            return None;
+        }
+        let max_block = (level - b'0' as u32) as usize * 100_000;
+
+        loop {
+            let magic = ((bits.read(24)? as u64) << 24) | bits.read(24)? as u64;
+            let _crc = bits.read(32)?;
+            if magic == EOS_MAGIC {
+                break;
+            }
+            if magic != BLOCK_MAGIC {
+                return None;
+            }
+            let block = read_block(&mut bits, max_block)?;
+            undo_rle1(&block, &mut out, limit)?;
+        }
+
+        // Streams end on a byte boundary; another may follow.
+        bits.align();
+        if bits.pos / 8 >= data.len() {
+            return Some(out);
+        }
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize, // in bits
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, n: u32) -> Option<u32> {
+        let mut v = 0u32;
+        for _ in 0..n {
+            v = (v << 1) | self.bit()?;
+        }
+        Some(v)
+    }
+
+    fn bit(&mut self) -> Option<u32> {
+        let byte = *self.data.get(self.pos / 8)?;
+        let b = (byte >> (7 - (self.pos % 8))) & 1;
+        self.pos += 1;
+        Some(b as u32)
+    }
+
+    fn align(&mut self) {
+        self.pos = self.pos.div_ceil(8) * 8;
+    }
+}
+
+/// Canonical Huffman table for one coding group.
+struct Huffman {
+    limit: [i32; MAX_CODE_LEN as usize + 1],
+    base: [i32; MAX_CODE_LEN as usize + 1],
+    perm: Vec<u16>,
+    min_len: u32,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Option<Huffman> {
+        let min_len = *lengths.iter().min()? as u32;
+        let max_len = *lengths.iter().max()? as u32;
+        let mut perm = Vec::with_capacity(lengths.len());
+        for len in min_len..=max_len {
+            for (sym, &l) in lengths.iter().enumerate() {
+                if l as u32 == len {
+                    perm.push(sym as u16);
+                }
+            }
+        }
+
+        let mut limit = [-1i32; MAX_CODE_LEN as usize + 1];
+        let mut base = [0i32; MAX_CODE_LEN as usize + 1];
+        let mut code = 0i32;
+        let mut index = 0i32;
+        for len in min_len..=max_len {
+            let count = lengths.iter().filter(|&&l| l as u32 == len).count() as i32;
+            base[len as usize] = index - code;
+            code += count;
+            index += count;
+            limit[len as usize] = code - 1;
+            code <<= 1;
+        }
+        Some(Huffman {
+            limit,
+            base,
+            perm,
+            min_len,
+        })
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Option<u16> {
+        let mut len = self.min_len;
+        let mut code = bits.read(len)? as i32;
+        loop {
+            if code <= self.limit[len as usize] {
+                return self
+                    .perm
+                    .get((self.base[len as usize] + code) as usize)
+                    .copied();
+            }
+            len += 1;
+            if len > MAX_CODE_LEN {
+                return None;
+            }
+            code = (code << 1) | bits.bit()? as i32;
+        }
+    }
+}
+
+/// Decode one block up to (and including) the inverse BWT.
+fn read_block(bits: &mut BitReader, max_block: usize) -> Option<Vec<u8>> {
+    if bits.read(1)? != 0 {
+        return None; // randomized
+    }
+    let orig_ptr = bits.read(24)? as usize;
+
+    // Symbol map: 16 ranges of 16 byte values.
+    let used_ranges = bits.read(16)?;
+    let mut seq_to_unseq = Vec::with_capacity(256);
+    for i in 0..16 {
+        if used_ranges & (0x8000 >> i) != 0 {
+            let used = bits.read(16)?;
+            for j in 0..16 {
+                if used & (0x8000 >> j) != 0 {
+                    seq_to_unseq.push((i * 16 + j) as u8);
+                }
+            }
+        }
+    }
+    if seq_to_unseq.is_empty() {
+        return None;
+    }
+    let alpha_size = seq_to_unseq.len() + 2;
+
+    let n_groups = bits.read(3)? as usize;
+    let n_selectors = bits.read(15)? as usize;
+    if !(2..=MAX_GROUPS).contains(&n_groups) || n_selectors == 0 {
+        return None;
+    }
+
+    // Selectors are MTF coded as unary indices.
+    let mut mtf_groups: Vec<u8> = (0..n_groups as u8).collect();
+    let mut selectors = Vec::with_capacity(n_selectors);
+    for _ in 0..n_selectors {
+        let mut j = 0;
+        while bits.bit()? == 1 {
+            j += 1;
+            if j >= n_groups {
+                return None;
+            }
+        }
+        let g = mtf_groups.remove(j);
+        mtf_groups.insert(0, g);
+        selectors.push(g);
+    }
+
+    // Delta-coded code lengths per group.
+    let mut tables = Vec::with_capacity(n_groups);
+    for _ in 0..n_groups {
+        let mut lengths = [0u8; MAX_ALPHA];
+        let mut len = bits.read(5)? as i32;
+        for l in lengths.iter_mut().take(alpha_size) {
+            loop {
+                if !(1..=MAX_CODE_LEN as i32).contains(&len) {
+                    return None;
+                }
+                if bits.bit()? == 0 {
+                    break;
+                }
+                if bits.bit()? == 0 {
+                    len += 1;
+                } else {
+                    len -= 1;
+                }
+            }
+            *l = len as u8;
+        }
+        tables.push(Huffman::new(&lengths[..alpha_size])?);
+    }
+
+    // Huffman -> RLE2/MTF -> BWT input.
+    let eob = (alpha_size - 1) as u16;
+    let mut mtf: Vec<u8> = (0..=255u8).collect();
+    let mut tt: Vec<u8> = Vec::with_capacity(max_block);
+    let mut run = 0usize;
+    let mut run_weight = 1usize;
+    let mut decoded = 0usize;
+    loop {
+        let group = *selectors.get(decoded / GROUP_SIZE)? as usize;
+        let sym = tables[group].decode(bits)?;
+        decoded += 1;
+
+        if sym <= 1 {
+            run += run_weight << sym;
+            run_weight <<= 1;
+            if run > max_block {
+                return None;
+            }
+            continue;
+        }
+        if run > 0 {
+            if tt.len() + run > max_block {
+                return None;
+            }
+            let b = seq_to_unseq[mtf[0] as usize];
+            tt.resize(tt.len() + run, b);
+            run = 0;
+            run_weight = 1;
+        }
+        if sym == eob {
+            break;
+        }
+        let idx = (sym - 1) as usize;
+        if idx >= seq_to_unseq.len() || tt.len() >= max_block {
+            return None;
+        }
+        let v = mtf.remove(idx);
+        mtf.insert(0, v);
+        tt.push(seq_to_unseq[v as usize]);
+    }
+    if orig_ptr >= tt.len() {
+        return None;
+    }
+
+    // Inverse BWT.
+    let mut counts = [0usize; 256];
+    for &b in &tt {
+        counts[b as usize] += 1;
+    }
+    let mut starts = [0usize; 256];
+    let mut sum = 0;
+    for i in 0..256 {
+        starts[i] = sum;
+        sum += counts[i];
+    }
+    let mut next = vec![0u32; tt.len()];
+    for (i, &b) in tt.iter().enumerate() {
+        next[starts[b as usize]] = i as u32;
+        starts[b as usize] += 1;
+    }
+    let mut block = Vec::with_capacity(tt.len());
+    let mut p = next[orig_ptr] as usize;
+    for _ in 0..tt.len() {
+        block.push(tt[p]);
+        p = next[p] as usize;
+    }
+    Some(block)
+}
+
+/// Undo the initial run-length pass: 4 equal bytes are followed by a count.
+fn undo_rle1(block: &[u8], out: &mut Vec<u8>, limit: usize) -> Option<()> {
+    let mut i = 0;
+    let mut last: Option<u8> = None;
+    let mut same = 0;
+    while i < block.len() {
+        let b = block[i];
+        i += 1;
+        if same == 4 {
+            out.resize(out.len() + b as usize, last?);
+            same = 0;
+            last = None;
+        } else {
+            if Some(b) == last {
+                same += 1;
+            } else {
+                last = Some(b);
+                same = 1;
+            }
+            out.push(b);
+        }
+        if out.len() > limit {
+            return None;
+        }
+    }
+    Some(())
+}
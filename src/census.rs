@@ -0,0 +1,105 @@
+// PowderCore - element census
+//
+// Per-element cell counts, kept up to date by step() as cells change
+// element, so HUDs and win conditions ("no fire left") can ask every frame
+// without scanning the grid. Bulk edits from outside the simulation (loads,
+// get_cell_mut, deltas, ...) just mark the counts stale; the next step()
+// recounts once, and queries in the meantime fall back to a scan.
+
+use std::collections::HashMap;
+
+use crate::{Cell, Element, Violation, World, ELEMENT_TABLE};
+
+#[derive(Clone, Debug)]
+pub(crate) struct Census {
+    counts: [usize; Element::COUNT],
+    stale: bool,
+}
+
+impl Census {
+    /// Counts for a grid of `cells` Empty cells.
+    pub(crate) fn new(cells: usize) -> Self {
+        let mut counts = [0; Element::COUNT];
+        counts[Element::Empty as usize] = cells;
+        Census {
+            counts,
+            stale: false,
+        }
+    }
+
+    /// Cells were changed behind the census' back.
+    pub(crate) fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// One cell changed from `from` to `to`.
+    pub(crate) fn retally(&mut self, from: Element, to: Element) {
+        if from != to && !self.stale {
+            self.counts[from as usize] -= 1;
+            self.counts[to as usize] += 1;
+        }
+    }
+}
+
+impl World {
+    /// Number of cells currently holding `elem`.
+    pub fn count(&self, elem: Element) -> usize {
+        if self.census.stale {
+            return self.cells.iter().filter(|c| c.elem == elem).count();
+        }
+        self.census.counts[elem as usize]
+    }
+
+    /// Cell count for every element present in the world (elements with no
+    /// cells are left out).
+    pub fn census(&self) -> HashMap<Element, usize> {
+        let counts = if self.census.stale {
+            tally(&self.cells)
+        } else {
+            self.census.counts
+        };
+        ELEMENT_TABLE
+            .iter()
+            .map(|&e| (e, counts[e as usize]))
+            .filter(|&(_, n)| n > 0)
+            .collect()
+    }
+
+    /// The counts agree with the grid (see validate.rs). Skipped while
+    /// they are stale.
+    pub(crate) fn check_census(&self) -> Result<(), Violation> {
+        if self.census.stale {
+            return Ok(());
+        }
+        let actual = tally(&self.cells);
+        for e in ELEMENT_TABLE {
+            let (counted, actual) = (self.census.counts[e as usize], actual[e as usize]);
+            if counted != actual {
+                return Err(Violation::Census {
+                    elem: e,
+                    counted,
+                    actual,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Recount from the cells if anything invalidated the census.
+    pub(crate) fn refresh_census(&mut self) {
+        if self.census.stale {
+            self.census = Census {
+                counts: tally(&self.cells),
+                stale: false,
+            };
+        }
+    }
+}
+
+fn tally(cells: &[Cell]) -> [usize; Element::COUNT] {
+    let mut counts = [0; Element::COUNT];
+    for c in cells {
+        counts[c.elem as usize] += 1;
+    }
+    counts
+}
@@ -0,0 +1,208 @@
+// PowderCore - growing worlds
+//
+// A World is a fixed rectangle chosen at startup. ChunkedWorld lifts that
+// limit: it starts as a single 64x64 chunk and allocates whole chunks on
+// demand, when a brush or spawn lands outside the current area or when
+// something moving (powders, liquids, gases, fire, actors) reaches the
+// edge during step().
+//
+// Coordinates are global and may be negative; chunk (cx, cy) covers cells
+// cx*64..cx*64+63 by cy*64..cy*64+63. Internally the allocated chunks
+// always form a rectangle backed by one ordinary World, so the simulation
+// itself is unchanged and every World API is still available through
+// world()/world_mut() in local coordinates (see to_local). Growing never
+// shrinks back; set_max_chunks caps the memory a runaway gas cloud or an
+// endless fall can claim.
+
+use crate::gravity::DOWN;
+use crate::{category_of, Category, Cell, ChangeCause, Element, Provenance, Rect, World};
+
+/// Side length of a chunk, in cells.
+pub const CHUNK_SIZE: i32 = 64;
+
+/// World that grows in CHUNK_SIZE chunks as particles spread.
+pub struct ChunkedWorld {
+    world: World,
+    /// Chunk coordinates of the backing world's top-left chunk.
+    origin: (i32, i32),
+    max_chunks: usize,
+}
+
+fn chunk_of(v: i32) -> i32 {
+    v.div_euclid(CHUNK_SIZE)
+}
+
+/// Cells that can travel and so pull new chunks in.
+fn moving(e: Element) -> bool {
+    !matches!(category_of(e), Category::Empty | Category::Solid)
+}
+
+impl ChunkedWorld {
+    /// One empty chunk at (0, 0), growing without limit.
+    pub fn new(seed: u64) -> Self {
+        ChunkedWorld {
+            world: World::new(CHUNK_SIZE, CHUNK_SIZE, seed),
+            origin: (0, 0),
+            max_chunks: usize::MAX,
+        }
+    }
+
+    /// The backing world, in local coordinates.
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Global coordinates of the allocated area.
+    pub fn bounds(&self) -> Rect {
+        Rect::new(
+            self.origin.0 * CHUNK_SIZE,
+            self.origin.1 * CHUNK_SIZE,
+            self.world.width,
+            self.world.height,
+        )
+    }
+
+    /// Global (x, y) in the backing world's coordinates.
+    pub fn to_local(&self, x: i32, y: i32) -> (i32, i32) {
+        (
+            x - self.origin.0 * CHUNK_SIZE,
+            y - self.origin.1 * CHUNK_SIZE,
+        )
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        (self.world.width / CHUNK_SIZE * (self.world.height / CHUNK_SIZE)) as usize
+    }
+
+    /// Stop allocating once this many chunks exist. Cells keep running
+    /// into the edge of the allocated area like an ordinary world.
+    pub fn set_max_chunks(&mut self, max: usize) {
+        self.max_chunks = max;
+    }
+
+    /// Cell at global (x, y); Empty where nothing is allocated.
+    pub fn get_cell(&self, x: i32, y: i32) -> Cell {
+        let (lx, ly) = self.to_local(x, y);
+        self.world.get_cell(lx, ly)
+    }
+
+    /// Allocate every chunk touching `rect` (global). Returns false, and
+    /// allocates nothing, if that would exceed the chunk limit.
+    pub fn ensure(&mut self, rect: Rect) -> bool {
+        if rect.width <= 0 || rect.height <= 0 {
+            return true;
+        }
+        let b = self.bounds();
+        let (cx0, cy0) = (self.origin.0, self.origin.1);
+        let (cx1, cy1) = (chunk_of(b.x + b.width - 1), chunk_of(b.y + b.height - 1));
+        let left = (cx0 - chunk_of(rect.x)).max(0);
+        let top = (cy0 - chunk_of(rect.y)).max(0);
+        let right = (chunk_of(rect.x + rect.width - 1) - cx1).max(0);
+        let bottom = (chunk_of(rect.y + rect.height - 1) - cy1).max(0);
+        self.grow(left, top, right, bottom)
+    }
+
+    /// Place `elem` at global (x, y), allocating its chunk if needed.
+    pub fn spawn(&mut self, x: i32, y: i32, elem: Element) -> bool {
+        if !self.ensure(Rect::new(x, y, 1, 1)) {
+            return false;
+        }
+        let (lx, ly) = self.to_local(x, y);
+        self.world.spawn(lx, ly, elem)
+    }
+
+    /// `World::place_brush` at global (cx, cy), allocating the chunks the
+    /// brush covers. Parts beyond the chunk limit are clipped.
+    pub fn place_brush(&mut self, cx: i32, cy: i32, rad: i32, elem: Element) {
+        let r = rad.max(0);
+        self.ensure(Rect::new(cx - r, cy - r, 2 * r + 1, 2 * r + 1));
+        let (lx, ly) = self.to_local(cx, cy);
+        self.world.place_brush(lx, ly, rad, elem);
+    }
+
+    /// Advance one tick, then add a row or column of chunks on every side
+    /// where something moving has reached the edge.
+    pub fn step(&mut self) {
+        self.world.step();
+
+        let (w, h) = (self.world.width, self.world.height);
+        let row = |y: i32| (0..w).any(|x| moving(self.world.get_cell(x, y).elem));
+        let col = |x: i32| (0..h).any(|y| moving(self.world.get_cell(x, y).elem));
+        let (left, right) = (col(0) as i32, col(w - 1) as i32);
+        let (top, bottom) = (row(0) as i32, row(h - 1) as i32);
+        if left + top + right + bottom > 0 {
+            self.grow(left, top, right, bottom);
+        }
+    }
+
+    /// Add chunks on each side. All or nothing under the chunk limit.
+    fn grow(&mut self, left: i32, top: i32, right: i32, bottom: i32) -> bool {
+        if left + top + right + bottom == 0 {
+            return true;
+        }
+        let cw = (self.world.width / CHUNK_SIZE + left + right) as usize;
+        let ch = (self.world.height / CHUNK_SIZE + top + bottom) as usize;
+        if cw.saturating_mul(ch) > self.max_chunks {
+            return false;
+        }
+        self.world.pad(
+            left * CHUNK_SIZE,
+            top * CHUNK_SIZE,
+            right * CHUNK_SIZE,
+            bottom * CHUNK_SIZE,
+        );
+        self.origin.0 -= left;
+        self.origin.1 -= top;
+        true
+    }
+}
+
+impl World {
+    /// Add empty cells around the grid, keeping every cell, actor id,
+    /// actor memory, frozen region, gravity zone, provenance record and
+    /// undoable command in place relative to its neighbours.
+    fn pad(&mut self, left: i32, top: i32, right: i32, bottom: i32) {
+        let old_w = self.width;
+        let (new_w, new_h) = (old_w + left + right, self.height + top + bottom);
+        let at = (old_w, new_w, new_h, left, top);
+        if self.frozen.len() == self.cells.len() {
+            self.frozen = regrid(&self.frozen, at, false);
+        }
+        if self.gravity.len() == self.cells.len() {
+            self.gravity = regrid(&self.gravity, at, (DOWN.0 as i8, DOWN.1 as i8));
+        }
+        if self.provenance.enabled() && self.provenance.cells().len() == self.cells.len() {
+            let fresh = Provenance {
+                tick: self.tick,
+                cause: ChangeCause::Unchanged,
+            };
+            let cells = regrid(self.provenance.cells(), at, fresh);
+            self.provenance.set_cells(cells);
+        }
+        self.cells = regrid(&self.cells, at, Cell::default());
+        self.width = new_w;
+        self.height = new_h;
+        self.shift_actors(old_w, left, top);
+        self.shift_undo(left, top);
+        self.census.invalidate();
+    }
+}
+
+/// Copy per-cell data `old_w` wide into a new_w x new_h grid filled with
+/// `fill`, `left` columns in and `top` rows down.
+fn regrid<T: Copy>(
+    old: &[T],
+    (old_w, new_w, new_h, left, top): (i32, i32, i32, i32, i32),
+    fill: T,
+) -> Vec<T> {
+    let mut grid = vec![fill; (new_w * new_h) as usize];
+    for (y, row) in old.chunks(old_w.max(1) as usize).enumerate() {
+        let to = ((y as i32 + top) * new_w + left) as usize;
+        grid[to..to + row.len()].copy_from_slice(row);
+    }
+    grid
+}
@@ -0,0 +1,312 @@
+// PowderCore - edit commands and undo
+//
+// World::apply runs an edit described as a Command and remembers what the
+// cells it could touch held beforehand (a Stamp of the bounding rect), so
+// World::undo can put them back. Every frontend that routes its tools
+// through apply gets the same undo behaviour, and since commands encode to
+// bytes (to_bytes/from_bytes) the edit history can be saved, shared or
+// replayed elsewhere.
+//
+// Undo restores the region exactly as it was before the command, whatever
+// the simulation has done there since: sand that fell into it is erased,
+// sand that fell out of it comes back. Edits made without apply aren't
+// recorded. Anything that replaces the whole grid (resize, load, restore,
+// rewind) forgets the history; a ChunkedWorld growing shifts it along with
+// the cells. The newest UNDO_LIMIT
+// commands are kept by default; see set_undo_limit.
+//
+// Wire format (see save.rs for the varint/zigzag helpers):
+//
+//   magic   4 bytes  "PWCM"
+//   version u8
+//   kind    u8, then
+//     0 brush:   zigzag varint x, y, u8 element id, brush (see brush.rs)
+//     1 fill:    rect (zigzag varint x, y, width, height), u8 element id
+//     2 stamp:   zigzag varint x, y, u8 paste mode, varint length,
+//                stamp file bytes
+//     3 replace: rect, u8 from element id, u8 to element id
+
+use std::collections::VecDeque;
+
+use crate::brush::{read_brush, write_brush};
+use crate::save::{unzigzag, write_varint, zigzag, ByteSource, Reader};
+use crate::{Brush, EdgeMode, Element, LoadError, PasteMode, Rect, Stamp, World};
+
+const COMMAND_MAGIC: &[u8; 4] = b"PWCM";
+const COMMAND_VERSION: u8 = 1;
+
+/// Commands kept for undo unless set_undo_limit says otherwise.
+pub const UNDO_LIMIT: usize = 64;
+
+/// An undoable edit for `World::apply`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// `World::apply_brush` at (x, y).
+    Brush {
+        x: i32,
+        y: i32,
+        elem: Element,
+        brush: Brush,
+    },
+    /// `World::fill_rect`.
+    Fill { rect: Rect, elem: Element },
+    /// `World::paste` with the stamp's top-left corner at (x, y).
+    Stamp {
+        stamp: Stamp,
+        x: i32,
+        y: i32,
+        mode: PasteMode,
+    },
+    /// Turn every `from` cell inside `rect` into `to`.
+    Replace {
+        rect: Rect,
+        from: Element,
+        to: Element,
+    },
+}
+
+pub(crate) struct UndoStack {
+    limit: usize,
+    /// Oldest first: the command, where it was applied and what was there.
+    done: VecDeque<(Command, Rect, Stamp)>,
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        UndoStack {
+            limit: UNDO_LIMIT,
+            done: VecDeque::new(),
+        }
+    }
+}
+
+impl World {
+    /// Run `command` and record it for `undo`.
+    pub fn apply(&mut self, command: Command) {
+        let at = self.grid_region(command.bounds(self.height));
+        let before = self.copy_region(at);
+        self.run_command(&command);
+        if self.undo.limit == 0 {
+            return;
+        }
+        if self.undo.done.len() == self.undo.limit {
+            self.undo.done.pop_front();
+        }
+        self.undo.done.push_back((command, at, before));
+    }
+
+    /// Revert the most recent applied command, returning it (to redo with
+    /// apply, say). None when there's nothing left to undo.
+    pub fn undo(&mut self) -> Option<Command> {
+        let (command, at, before) = self.undo.done.pop_back()?;
+        self.paste(&before, at.x, at.y, PasteMode::Overwrite);
+        Some(command)
+    }
+
+    /// Number of commands `undo` can still revert.
+    pub fn undo_count(&self) -> usize {
+        self.undo.done.len()
+    }
+
+    /// Applied commands that can still be undone, oldest first.
+    pub fn edit_history(&self) -> impl Iterator<Item = &Command> + '_ {
+        self.undo.done.iter().map(|(c, _, _)| c)
+    }
+
+    /// Keep at most `limit` commands (0 turns recording off). Drops the
+    /// oldest ones if there are more already.
+    pub fn set_undo_limit(&mut self, limit: usize) {
+        self.undo.limit = limit;
+        while self.undo.done.len() > limit {
+            self.undo.done.pop_front();
+        }
+    }
+
+    /// Forget every recorded command.
+    pub fn clear_undo(&mut self) {
+        self.undo.done.clear();
+    }
+
+    /// Move every recorded command and its undo region by (dx, dy), for a
+    /// grid that grew by that much on the top-left.
+    pub(crate) fn shift_undo(&mut self, dx: i32, dy: i32) {
+        for (command, at, _) in &mut self.undo.done {
+            command.shift(dx, dy);
+            at.x += dx;
+            at.y += dy;
+        }
+    }
+
+    fn run_command(&mut self, command: &Command) {
+        match *command {
+            Command::Brush { x, y, elem, brush } => self.apply_brush(x, y, elem, &brush),
+            Command::Fill { rect, elem } => self.fill_rect(rect, elem),
+            Command::Stamp {
+                ref stamp,
+                x,
+                y,
+                mode,
+            } => self.paste(stamp, x, y, mode),
+            Command::Replace { rect, from, to } => {
+                let r = rect.clip(self.width, self.height);
+                for y in r.y..r.y + r.height {
+                    for x in r.x..r.x + r.width {
+                        if self.cells[self.idx(x, y)].elem == from {
+                            self.paint(x, y, to);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The part of `rect` that holds real cells: clipped to the grid, or
+    /// with wrapped edges, at most one lap of it per axis (copy_region and
+    /// paste wrap the same way).
+    pub(crate) fn grid_region(&self, rect: Rect) -> Rect {
+        if self.edge_mode != EdgeMode::Wrap {
+            return rect.clip(self.width, self.height);
+        }
+        let lap = |start: i32, len: i32, size: i32| {
+            if len >= size {
+                (0, size)
+            } else {
+                (start.rem_euclid(size.max(1)), len.max(0))
+            }
+        };
+        let (x, width) = lap(rect.x, rect.width, self.width);
+        let (y, height) = lap(rect.y, rect.height, self.height);
+        Rect::new(x, y, width, height)
+    }
+}
+
+impl Command {
+    /// Rect holding every cell the command can change in a world
+    /// `world_height` rows tall (lightning bolts run down to the floor).
+    fn bounds(&self, world_height: i32) -> Rect {
+        match self {
+            Command::Brush { x, y, elem, .. } if *elem == Element::Lightning => {
+                Rect::new(*x, *y, 1, world_height.saturating_sub(*y))
+            }
+            Command::Brush { x, y, brush, .. } => {
+                let rad = brush.radius.max(0) as i64;
+                let corner = |c: i32| (c as i64 - rad).clamp(i32::MIN as i64, i32::MAX as i64);
+                let side = (2 * rad + 1).min(i32::MAX as i64) as i32;
+                Rect::new(corner(*x) as i32, corner(*y) as i32, side, side)
+            }
+            Command::Fill { rect, .. } | Command::Replace { rect, .. } => *rect,
+            Command::Stamp { stamp, x, y, .. } => Rect::new(*x, *y, stamp.width(), stamp.height()),
+        }
+    }
+
+    fn shift(&mut self, dx: i32, dy: i32) {
+        match self {
+            Command::Brush { x, y, .. } | Command::Stamp { x, y, .. } => {
+                *x = x.saturating_add(dx);
+                *y = y.saturating_add(dy);
+            }
+            Command::Fill { rect, .. } | Command::Replace { rect, .. } => {
+                rect.x = rect.x.saturating_add(dx);
+                rect.y = rect.y.saturating_add(dy);
+            }
+        }
+    }
+
+    /// Encode for saving or sending.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(COMMAND_MAGIC);
+        out.push(COMMAND_VERSION);
+        let rect = |out: &mut Vec<u8>, r: &Rect| {
+            for v in [r.x, r.y, r.width, r.height] {
+                write_varint(out, zigzag(v));
+            }
+        };
+        match self {
+            Command::Brush { x, y, elem, brush } => {
+                out.push(0);
+                write_varint(&mut out, zigzag(*x));
+                write_varint(&mut out, zigzag(*y));
+                out.push(*elem as i32 as u8);
+                write_brush(&mut out, brush);
+            }
+            Command::Fill { rect: r, elem } => {
+                out.push(1);
+                rect(&mut out, r);
+                out.push(*elem as i32 as u8);
+            }
+            Command::Stamp { stamp, x, y, mode } => {
+                out.push(2);
+                write_varint(&mut out, zigzag(*x));
+                write_varint(&mut out, zigzag(*y));
+                out.push(match mode {
+                    PasteMode::Overwrite => 0,
+                    PasteMode::Merge => 1,
+                    PasteMode::Under => 2,
+                });
+                let bytes = stamp.to_bytes();
+                write_varint(&mut out, bytes.len() as u64);
+                out.extend_from_slice(&bytes);
+            }
+            Command::Replace { rect: r, from, to } => {
+                out.push(3);
+                rect(&mut out, r);
+                out.push(*from as i32 as u8);
+                out.push(*to as i32 as u8);
+            }
+        }
+        out
+    }
+
+    /// Decode bytes produced by `to_bytes`. Unknown element, shape or mode
+    /// ids are Corrupt rather than guessed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Command, LoadError> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(4)? != COMMAND_MAGIC {
+            return Err(LoadError::BadMagic);
+        }
+        let version = r.u8()?;
+        if version != COMMAND_VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
+        }
+        fn int(r: &mut Reader) -> Result<i32, LoadError> {
+            Ok(unzigzag(r.varint()?))
+        }
+        fn elem(r: &mut Reader) -> Result<Element, LoadError> {
+            Element::from_id(r.u8()? as i32).ok_or(LoadError::Corrupt)
+        }
+        fn rect(r: &mut Reader) -> Result<Rect, LoadError> {
+            Ok(Rect::new(int(r)?, int(r)?, int(r)?, int(r)?))
+        }
+        match r.u8()? {
+            0 => Ok(Command::Brush {
+                x: int(&mut r)?,
+                y: int(&mut r)?,
+                elem: elem(&mut r)?,
+                brush: read_brush(&mut r)?,
+            }),
+            1 => Ok(Command::Fill {
+                rect: rect(&mut r)?,
+                elem: elem(&mut r)?,
+            }),
+            2 => {
+                let (x, y) = (int(&mut r)?, int(&mut r)?);
+                let mode = match r.u8()? {
+                    0 => PasteMode::Overwrite,
+                    1 => PasteMode::Merge,
+                    2 => PasteMode::Under,
+                    _ => return Err(LoadError::Corrupt),
+                };
+                let len = usize::try_from(r.varint()?).map_err(|_| LoadError::Truncated)?;
+                let stamp = Stamp::from_bytes(r.take(len)?)?;
+                Ok(Command::Stamp { stamp, x, y, mode })
+            }
+            3 => Ok(Command::Replace {
+                rect: rect(&mut r)?,
+                from: elem(&mut r)?,
+                to: elem(&mut r)?,
+            }),
+            _ => Err(LoadError::Corrupt),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+// PowderCore - simulation tuning
+//
+// Knobs that used to be hard-coded in step(). Defaults reproduce the
+// original behaviour exactly; change them per world with
+// World::set_config or WorldBuilder::config.
+
+use crate::{Element, ExplosionSpec, MixRule, World, MIX_RULES};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SimConfig {
+    /// How far (in cells, square radius) humans look for zombies.
+    pub human_vision: i32,
+    /// How far zombies look for humans.
+    pub zombie_vision: i32,
+    /// How far animals look for humans to follow and things to flee.
+    pub animal_vision: i32,
+    /// Ticks a lava cell stays liquid before cooling into stone.
+    pub lava_cool_ticks: i32,
+    /// How far (in cells) a human in danger searches for a safe spot.
+    pub flee_radius: i32,
+    /// Starting health of a human.
+    pub human_health: i32,
+    /// Starting health of a zombie (including freshly bitten humans).
+    pub zombie_health: i32,
+    /// Starting health of an animal.
+    pub animal_health: i32,
+    /// Ticks a human can stay underwater before drowning.
+    pub human_breath: i32,
+    /// Percent chance per tick that a human next to water throws it on a
+    /// fire it can see; 0 disables firefighting.
+    pub firefight_chance: u32,
+    /// How far (square radius) a human will throw water.
+    pub firefight_range: i32,
+    /// Ticks before a Corpse decays into Bone.
+    pub corpse_decay_ticks: i32,
+    /// Ticks before Bone crumbles into Ash.
+    pub bone_decay_ticks: i32,
+    /// Ticks between actors emitted by a HumanSpawner/ZombieSpawner.
+    pub spawner_interval: i32,
+    /// Spawners stop emitting while the world holds this many actors of
+    /// their kind.
+    pub spawner_cap: usize,
+    /// How far (in cells) stone, dirt and wood may reach from a support
+    /// before crumbling into rubble; 0 disables cave-ins.
+    pub support_distance: i32,
+    /// Give every cell update its own RNG stream keyed by position and
+    /// tick instead of sharing one sequence (see the Rng notes in lib.rs).
+    /// Deterministic either way, but the results differ.
+    pub rng_streams: bool,
+    /// Per element (indexed by `Element as usize`): update cells of it only
+    /// on ticks that are a multiple of this, e.g. 4 to grow plants at a
+    /// quarter of the pace or 2 to run lava at half rate. 0 and 1 both
+    /// mean every tick. All cells of an element wait on the same ticks;
+    /// in between they sit still, though other cells can still push them
+    /// around or react with them.
+    pub update_every: [u32; Element::COUNT],
+    /// Blast of gunpowder set off by fire, lava or a charged wire
+    /// (lightning adds a cell of radius).
+    pub gunpowder_blast: ExplosionSpec,
+    /// Blast of hydrogen touching fire or lava, and of hydrogen or gas
+    /// caught by spreading fire or lightning.
+    pub hydrogen_blast: ExplosionSpec,
+    /// Percent scaling the odds of every contact reaction: things catching
+    /// fire and gas igniting, acid dissolving, liquids mixing, toxic gas
+    /// killing plants, ice melting and zombie bites turning humans. 100 is
+    /// normal; 50 halves the odds (reactions that were certain become
+    /// coin flips), 200 doubles them up to certain. 0 stops those
+    /// reactions entirely.
+    pub reactivity: u32,
+    /// Which liquids blend when they touch, and into what (see mixing.rs).
+    pub mix_rules: &'static [MixRule],
+}
+
+impl SimConfig {
+    pub const DEFAULT: SimConfig = SimConfig {
+        human_vision: 6,
+        zombie_vision: 6,
+        animal_vision: 6,
+        lava_cool_ticks: 200,
+        flee_radius: 10,
+        human_health: 100,
+        zombie_health: 60,
+        animal_health: 40,
+        human_breath: 120,
+        firefight_chance: 30,
+        firefight_range: 3,
+        corpse_decay_ticks: 600,
+        bone_decay_ticks: 1200,
+        spawner_interval: 120,
+        spawner_cap: 20,
+        support_distance: 0,
+        rng_streams: false,
+        update_every: [1; Element::COUNT],
+        gunpowder_blast: ExplosionSpec::GUNPOWDER,
+        hydrogen_blast: ExplosionSpec::HYDROGEN,
+        reactivity: 100,
+        mix_rules: MIX_RULES,
+    };
+
+    /// A slower, quieter world: reactions at half the usual odds.
+    pub const CALM: SimConfig = SimConfig::DEFAULT.with_reactivity(50);
+
+    /// Everything catches, dissolves and bites twice as readily.
+    pub const CHAOTIC: SimConfig = SimConfig::DEFAULT.with_reactivity(200);
+
+    /// This config with `elem` updating every `ticks` ticks.
+    pub const fn with_update_every(mut self, elem: Element, ticks: u32) -> SimConfig {
+        self.update_every[elem as usize] = ticks;
+        self
+    }
+
+    /// This config with `reactivity` percent.
+    pub const fn with_reactivity(mut self, reactivity: u32) -> SimConfig {
+        self.reactivity = reactivity;
+        self
+    }
+
+    /// A reaction's `pct` percent chance after scaling by reactivity.
+    pub fn reaction_chance(&self, pct: u32) -> u32 {
+        (pct as u64 * self.reactivity as u64 / 100).min(100) as u32
+    }
+
+    /// Whether cells of `elem` update on `tick`.
+    pub fn updates_on(&self, elem: Element, tick: u64) -> bool {
+        let every = self.update_every[elem as usize].max(1) as u64;
+        tick.is_multiple_of(every)
+    }
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig::DEFAULT
+    }
+}
+
+impl World {
+    pub fn config(&self) -> &SimConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: SimConfig) {
+        self.config = config;
+    }
+}
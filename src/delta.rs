@@ -0,0 +1,155 @@
+// PowderCore - tick-to-tick deltas for network sync
+//
+// A server keeps the snapshot it last sent, diffs the live world against it
+// and ships only the changed runs of cells. Viewers apply the delta to their
+// own copy. If the world was resized in between, the delta is taken against
+// an empty grid of the new size so applying it still yields the right state.
+//
+// Wire format (see save.rs for the varint/zigzag helpers):
+//
+//   magic   4 bytes  "PWDD"
+//   width   u32 LE
+//   height  u32 LE
+//   runs    varint count, then per run:
+//             varint  gap (cells skipped since the end of the previous run)
+//             varint  length
+//             length x (u8 element id, zigzag varint life)
+
+use crate::save::{read_dims, unzigzag, write_varint, zigzag, ByteSource, Reader};
+use crate::{Cell, Element, LoadError, World, WorldSnapshot};
+
+const DELTA_MAGIC: &[u8; 4] = b"PWDD";
+
+/// A contiguous run of changed cells starting at a row-major index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeltaRun {
+    pub start: usize,
+    pub cells: Vec<Cell>,
+}
+
+/// Changes needed to turn one world state into another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorldDelta {
+    pub width: i32,
+    pub height: i32,
+    pub runs: Vec<DeltaRun>,
+}
+
+impl WorldDelta {
+    /// True when nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Number of changed cells.
+    pub fn changed_cells(&self) -> usize {
+        self.runs.iter().map(|r| r.cells.len()).sum()
+    }
+
+    /// Encode for sending over the wire.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(DELTA_MAGIC);
+        out.extend_from_slice(&(self.width as u32).to_le_bytes());
+        out.extend_from_slice(&(self.height as u32).to_le_bytes());
+        write_varint(&mut out, self.runs.len() as u64);
+        let mut end = 0;
+        for run in &self.runs {
+            write_varint(&mut out, (run.start - end) as u64);
+            write_varint(&mut out, run.cells.len() as u64);
+            for c in &run.cells {
+                out.push(c.elem as i32 as u8);
+                write_varint(&mut out, zigzag(c.life));
+            }
+            end = run.start + run.cells.len();
+        }
+        out
+    }
+
+    /// Decode bytes produced by `to_bytes`. Unknown element ids become Empty.
+    pub fn from_bytes(bytes: &[u8]) -> Result<WorldDelta, LoadError> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(4)? != DELTA_MAGIC {
+            return Err(LoadError::BadMagic);
+        }
+        let (width, height) = read_dims(&mut r)?;
+        let total = width as u64 * height as u64;
+
+        let count = r.varint()?;
+        let mut runs = Vec::new();
+        let mut end = 0u64;
+        for _ in 0..count {
+            let start = end.checked_add(r.varint()?).ok_or(LoadError::Corrupt)?;
+            let len = r.varint()?;
+            if len == 0 || start > total || len > total - start {
+                return Err(LoadError::Corrupt);
+            }
+            let mut cells = Vec::with_capacity(len as usize);
+            for _ in 0..len {
+                let id = r.u8()?;
+                let life = unzigzag(r.varint()?);
+                cells.push(match Element::from_id(id as i32) {
+                    Some(elem) => Cell { elem, life },
+                    None => Cell::default(),
+                });
+            }
+            runs.push(DeltaRun {
+                start: start as usize,
+                cells,
+            });
+            end = start + len;
+        }
+
+        Ok(WorldDelta {
+            width,
+            height,
+            runs,
+        })
+    }
+}
+
+impl World {
+    /// Collect every cell that differs from `snap`.
+    pub fn diff_since(&self, snap: &WorldSnapshot) -> WorldDelta {
+        let same_size = snap.width == self.width && snap.height == self.height;
+        let empty = Cell::default();
+        let mut old = snap.chunks.iter().flat_map(|c| c.iter());
+
+        let mut runs: Vec<DeltaRun> = Vec::new();
+        for (i, cur) in self.cells.iter().enumerate() {
+            let before = if same_size { old.next().unwrap_or(&empty) } else { &empty };
+            if before == cur {
+                continue;
+            }
+            match runs.last_mut() {
+                Some(run) if run.start + run.cells.len() == i => run.cells.push(*cur),
+                _ => runs.push(DeltaRun {
+                    start: i,
+                    cells: vec![*cur],
+                }),
+            }
+        }
+
+        WorldDelta {
+            width: self.width,
+            height: self.height,
+            runs,
+        }
+    }
+
+    /// Apply a delta produced by `diff_since`. A delta for a different size
+    /// resizes (and clears) the world first.
+    pub fn apply_delta(&mut self, delta: &WorldDelta) {
+        if delta.width != self.width || delta.height != self.height {
+            self.resize(delta.width, delta.height);
+        }
+        for run in &delta.runs {
+            let end = (run.start + run.cells.len()).min(self.cells.len());
+            if run.start >= end {
+                continue;
+            }
+            self.cells[run.start..end].copy_from_slice(&run.cells[..end - run.start]);
+        }
+        self.cells_changed();
+    }
+}
@@ -0,0 +1,107 @@
+// PowderCore - liquid depth
+//
+// Apparent depth of each liquid cell: how many cells of the same liquid sit
+// directly above it, unbroken (0 at the surface, and for anything that
+// isn't a liquid). Frontends use it to draw deep water darker.
+//
+// Tracking is off until set_depth_tracking(true) or the first
+// liquid_depth_map() call. While it's on, the map is rebuilt once at the end
+// of each step(), so frontends redrawing every frame read a finished buffer
+// instead of each walking the columns themselves. Cell edits through
+// get_cell_mut, loads and restores mark the map stale rather than
+// rebuilding it on the spot (the caller hasn't written yet): until the next
+// step, liquid_depth walks the column and liquid_depth_map rebuilds on
+// demand. Brush and drawing edits show up after the next step.
+//
+// Columns are measured straight up to row 0: wrapped edges and gravity
+// zones are ignored, as in pressure_map.
+
+use crate::{is_liquid, World};
+
+#[derive(Default)]
+pub(crate) struct DepthMap {
+    enabled: bool,
+    /// Tick `cells` was built at.
+    tick: u64,
+    /// Cells were replaced since `cells` was built.
+    stale: bool,
+    cells: Vec<u16>,
+}
+
+impl World {
+    /// Keep the liquid depth map up to date each step (see above).
+    pub fn set_depth_tracking(&mut self, on: bool) {
+        self.depth.enabled = on;
+        if on {
+            self.refresh_depth();
+        } else {
+            self.depth.cells = Vec::new();
+        }
+    }
+
+    pub fn depth_tracking(&self) -> bool {
+        self.depth.enabled
+    }
+
+    /// Same-liquid cells directly above (x, y); 0 for non-liquids and
+    /// out-of-bounds positions. Reads the map when tracking is on, otherwise
+    /// walks up the column.
+    pub fn liquid_depth(&self, x: i32, y: i32) -> u16 {
+        if !self.in_bounds(x, y) {
+            return 0;
+        }
+        let i = self.idx(x, y);
+        if self.depth_current() {
+            return self.depth.cells[i];
+        }
+        let (x, y) = self.pos_of(i);
+        let elem = self.cells[i].elem;
+        if !is_liquid(elem) {
+            return 0;
+        }
+        let above = (0..y).rev().take_while(|&yy| self.cells[self.idx(x, yy)].elem == elem);
+        above.count().min(u16::MAX as usize) as u16
+    }
+
+    /// Depth of every cell, row-major like cells(). Turns tracking on if
+    /// it's off.
+    pub fn liquid_depth_map(&mut self) -> &[u16] {
+        if !self.depth.enabled {
+            self.set_depth_tracking(true);
+        } else if !self.depth_current() {
+            self.refresh_depth();
+        }
+        &self.depth.cells
+    }
+
+    fn depth_current(&self) -> bool {
+        self.depth.enabled
+            && !self.depth.stale
+            && self.depth.tick == self.tick
+            && self.depth.cells.len() == self.cells.len()
+    }
+
+    /// Cells were edited outside step(): stop trusting the map until the
+    /// next rebuild.
+    pub(crate) fn depth_changed(&mut self) {
+        self.depth.stale = true;
+    }
+
+    /// Rebuild the map, top row down. Called at the end of step().
+    pub(crate) fn refresh_depth(&mut self) {
+        if !self.depth.enabled {
+            return;
+        }
+        let w = self.width as usize;
+        let out = &mut self.depth.cells;
+        out.clear();
+        out.resize(self.cells.len(), 0);
+        for (i, c) in self.cells.iter().enumerate() {
+            if i >= w && is_liquid(c.elem) && self.cells[i - w].elem == c.elem {
+                out[i] = out[i - w].saturating_add(1);
+            }
+        }
+        self.depth.tick = self.tick;
+        self.depth.stale = false;
+    }
+}
@@ -0,0 +1,211 @@
+// PowderCore - drawing primitives
+//
+// Rectangles, lines, circles and ellipses, filled or hollow, for editors
+// and level scripts. Cells get the same starting life as place_brush gives
+// them, and anything falling outside the world is clipped.
+
+use std::ops::RangeInclusive;
+
+use crate::brush::reach;
+use crate::{default_life, EdgeMode, Element, Rect, World};
+
+impl World {
+    /// Fill `rect` (clipped to the world) with `elem`.
+    pub fn fill_rect(&mut self, rect: Rect, elem: Element) {
+        let r = rect.clip(self.width, self.height);
+        for y in r.y..r.y + r.height {
+            for x in r.x..r.x + r.width {
+                self.paint(x, y, elem);
+            }
+        }
+    }
+
+    /// One-cell-wide line from (x0, y0) to (x1, y1), both ends included.
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, elem: Element) {
+        let line = Line::new(x0, y0, x1, y1);
+        for k in self.line_reach(&line, 0) {
+            let (x, y) = line.at(k);
+            self.paint(x, y, elem);
+        }
+    }
+
+    /// One-cell-wide circle of radius `r` around (cx, cy).
+    pub fn draw_circle_outline(&mut self, cx: i32, cy: i32, r: i32, elem: Element) {
+        if r < 0 {
+            return;
+        }
+        // The midpoint circle, tested cell by cell so it can be clipped: in
+        // each octant the walk keeps x while x² - x + (y + 1)² < r², so it
+        // lands on the smallest x with x(x + 1) + y² >= r².
+        let r2 = (r as i128).pow(2);
+        let wrap = self.edge_mode == EdgeMode::Wrap;
+        for dy in reach(cy, self.height, r, wrap) {
+            for dx in reach(cx, self.width, r, wrap) {
+                let (a, b) = (dx.abs().max(dy.abs()) as i128, dx.abs().min(dy.abs()) as i128);
+                let on = a * (a + 1) + b * b >= r2 && (r2 > a * (a - 1) + b * b || a == 0);
+                if on {
+                    self.paint_at(cx, cy, dx, dy, elem);
+                }
+            }
+        }
+    }
+
+    /// Hollow rectangle: `rect` with walls `thickness` cells thick, drawn
+    /// inwards. Thick enough walls fill it completely.
+    pub fn draw_rect_outline(&mut self, rect: Rect, thickness: i32, elem: Element) {
+        let t = thickness.max(1);
+        let inner = Rect::new(
+            rect.x + t,
+            rect.y + t,
+            rect.width - 2 * t,
+            rect.height - 2 * t,
+        );
+        let r = rect.clip(self.width, self.height);
+        for y in r.y..r.y + r.height {
+            for x in r.x..r.x + r.width {
+                if !inner.contains(x, y) {
+                    self.paint(x, y, elem);
+                }
+            }
+        }
+    }
+
+    /// Hollow circle of outer radius `r` with a wall `thickness` cells
+    /// thick, drawn inwards (a container or pipe cross-section).
+    pub fn draw_ring(&mut self, cx: i32, cy: i32, r: i32, thickness: i32, elem: Element) {
+        if r < 0 {
+            return;
+        }
+        let (r, inner) = (r as i64, r as i64 - thickness.max(1) as i64);
+        let wrap = self.edge_mode == EdgeMode::Wrap;
+        for dy in reach(cy, self.height, r as i32, wrap) {
+            for dx in reach(cx, self.width, r as i32, wrap) {
+                let d2 = dx * dx + dy * dy;
+                if d2 <= r * r && (inner < 0 || d2 > inner * inner) {
+                    self.paint_at(cx, cy, dx, dy, elem);
+                }
+            }
+        }
+    }
+
+    /// Filled axis-aligned ellipse with radii `rx`, `ry` around (cx, cy).
+    pub fn fill_ellipse(&mut self, cx: i32, cy: i32, rx: i32, ry: i32, elem: Element) {
+        if rx < 0 || ry < 0 {
+            return;
+        }
+        let (rx2, ry2) = ((rx as i128).pow(2).max(1), (ry as i128).pow(2).max(1));
+        let wrap = self.edge_mode == EdgeMode::Wrap;
+        for dy in reach(cy, self.height, ry, wrap) {
+            for dx in reach(cx, self.width, rx, wrap) {
+                // dx²/rx² + dy²/ry² <= 1, kept in integers
+                if (dx as i128).pow(2) * ry2 + (dy as i128).pow(2) * rx2 <= rx2 * ry2 {
+                    self.paint_at(cx, cy, dx, dy, elem);
+                }
+            }
+        }
+    }
+
+    /// Brush stroke from (x0, y0) to (x1, y1): place_brush along the whole
+    /// segment, so fast mouse drags draw a solid line instead of dots.
+    pub fn stroke_brush(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, rad: i32, elem: Element) {
+        // Brushes half a radius apart still overlap into a solid band.
+        let spacing = (rad / 2).max(1) as i64;
+        let line = Line::new(x0, y0, x1, y1);
+        let steps = self.line_reach(&line, rad);
+        let (first, last) = (steps.start().div_euclid(spacing) * spacing, *steps.end());
+        let ends = (last == line.len() && last % spacing != 0).then_some(last);
+        for k in (first..=last).step_by(spacing as usize).chain(ends) {
+            if steps.contains(&k) {
+                let (x, y) = line.at(k);
+                self.place_brush(x, y, rad, elem);
+            }
+        }
+    }
+
+    /// Steps along `line` that can reach the grid, allowing `rad` cells
+    /// either side for a brush: those whose major-axis coordinate is within
+    /// `rad` of the grid. With wrapped edges every step lands somewhere, so
+    /// it stops after as many steps as the grid has cells; like `reach`,
+    /// a huge line then costs no more than the grid itself.
+    fn line_reach(&self, line: &Line, rad: i32) -> RangeInclusive<i64> {
+        let (c, len, s) = if line.x_major {
+            (line.x0, self.width as i64, line.sx)
+        } else {
+            (line.y0, self.height as i64, line.sy)
+        };
+        if self.edge_mode == EdgeMode::Wrap {
+            return 0..=line.len().min(self.cells.len() as i64);
+        }
+        let (lo, hi) = (-(rad.max(0) as i64), len - 1 + rad.max(0) as i64);
+        let (first, last) = if s > 0 { (lo - c, hi - c) } else { (c - hi, c - lo) };
+        first.max(0)..=last.min(line.len())
+    }
+
+    /// paint() at (cx + dx, cy + dy), skipping sums that leave i32.
+    fn paint_at(&mut self, cx: i32, cy: i32, dx: i64, dy: i64, elem: Element) {
+        if let (Ok(x), Ok(y)) = (i32::try_from(cx as i64 + dx), i32::try_from(cy as i64 + dy)) {
+            self.paint(x, y, elem);
+        }
+    }
+
+    /// Set one cell to `elem` with its starting life (ignored off-grid).
+    pub(crate) fn paint(&mut self, x: i32, y: i32, elem: Element) {
+        if self.in_bounds(x, y) {
+            let i = self.idx(x, y);
+            self.put(i, elem, default_life(elem));
+        }
+    }
+}
+
+/// Bresenham line from (x0, y0) to (x1, y1), both ends included.
+pub(crate) fn line_points(x0: i32, y0: i32, x1: i32, y1: i32) -> impl Iterator<Item = (i32, i32)> {
+    let line = Line::new(x0, y0, x1, y1);
+    (0..=line.len()).map(move |k| line.at(k))
+}
+
+/// A Bresenham line that can be entered at any step, so callers can skip
+/// the stretch off the grid. Step k is the cell the usual error-carrying
+/// walk reaches after k moves along the major axis.
+pub(crate) struct Line {
+    x0: i64,
+    y0: i64,
+    sx: i64,
+    sy: i64,
+    major: i64,
+    minor: i64,
+    x_major: bool,
+}
+
+impl Line {
+    pub(crate) fn new(x0: i32, y0: i32, x1: i32, y1: i32) -> Self {
+        let (dx, dy) = ((x1 as i64 - x0 as i64).abs(), (y1 as i64 - y0 as i64).abs());
+        Line {
+            x0: x0 as i64,
+            y0: y0 as i64,
+            sx: if x0 < x1 { 1 } else { -1 },
+            sy: if y0 < y1 { 1 } else { -1 },
+            major: dx.max(dy),
+            minor: dx.min(dy),
+            x_major: dx >= dy,
+        }
+    }
+
+    /// Moves from end to end; the line covers one more cell than this.
+    pub(crate) fn len(&self) -> i64 {
+        self.major
+    }
+
+    /// The cell after `k` moves (0..=len), always between the ends.
+    pub(crate) fn at(&self, k: i64) -> (i32, i32) {
+        // the walk has taken floor((2k * minor + major) / (2 * major))
+        // minor-axis moves by step k
+        let m = if self.major == 0 {
+            0
+        } else {
+            ((2 * k as i128 * self.minor as i128 + self.major as i128) / (2 * self.major as i128))
+                as i64
+        };
+        let (dx, dy) = if self.x_major { (k, m) } else { (m, k) };
+        ((self.x0 + self.sx * dx) as i32, (self.y0 + self.sy * dy) as i32)
+    }
+}
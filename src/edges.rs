@@ -0,0 +1,79 @@
+// PowderCore - world edge behaviour
+//
+// What lies beyond the grid:
+//
+//   - Solid (the default): an invisible wall, nothing leaves the world,
+//   - Open: a bottomless void. Falling powders, liquids, actors and remains
+//     that reach the bottom row drop out, gases drift out of the top row,
+//     and liquids and gases leak out of the side columns,
+//   - Wrap: the world is a torus; every neighbour lookup and move wraps
+//     around both axes.
+//
+// Wrap works by making in_bounds()/idx() wrap coordinates, so movement,
+// neighbour scans, brushes and queries all agree on it. Open keeps the
+// Solid bounds and removes the escaping cells at the end of each step().
+// The mode is a property of the World and isn't saved with it.
+
+use crate::{is_gas, is_liquid, is_sand_like, Element, World};
+
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EdgeMode {
+    #[default]
+    Solid = 0,
+    Open = 1,
+    Wrap = 2,
+}
+
+impl EdgeMode {
+    pub(crate) fn from_id(id: i32) -> Option<EdgeMode> {
+        match id {
+            0 => Some(EdgeMode::Solid),
+            1 => Some(EdgeMode::Open),
+            2 => Some(EdgeMode::Wrap),
+            _ => None,
+        }
+    }
+}
+
+/// Things that fall and so leave through an open bottom edge.
+fn falls(e: Element) -> bool {
+    is_sand_like(e)
+        || is_liquid(e)
+        || crate::actors::is_actor(e)
+        || matches!(e, Element::Corpse | Element::Bone)
+}
+
+impl World {
+    pub fn edge_mode(&self) -> EdgeMode {
+        self.edge_mode
+    }
+
+    pub fn set_edge_mode(&mut self, mode: EdgeMode) {
+        self.edge_mode = mode;
+    }
+
+    /// Open edges: remove what is escaping the grid this tick.
+    pub(crate) fn drain_open_edges(&mut self) {
+        if self.edge_mode != EdgeMode::Open || self.width <= 0 || self.height <= 0 {
+            return;
+        }
+        let (w, h) = (self.width, self.height);
+        for x in 0..w {
+            self.escape(x, h - 1, falls);
+            self.escape(x, 0, is_gas);
+        }
+        for y in 0..h {
+            for x in [0, w - 1] {
+                self.escape(x, y, |e| is_liquid(e) || is_gas(e));
+            }
+        }
+    }
+
+    fn escape(&mut self, x: i32, y: i32, leaves: fn(Element) -> bool) {
+        let i = self.idx(x, y);
+        if leaves(self.cells[i].elem) {
+            self.put(i, Element::Empty, 0);
+        }
+    }
+}
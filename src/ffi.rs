@@ -0,0 +1,1239 @@
+// ===== C ABI LAYER (for any language via FFI) =====
+//
+// Build as cdylib/staticlib and use these from C, C++, Python, Nim, Kotlin, etc.
+// All functions are null-safe and do nothing if passed a null pointer.
+// Beyond that, pointer arguments must be valid for the sizes given; the
+// exports stay safe fns for Rust callers, with clippy's pointer-deref lint
+// allowed on each one that reads or writes through a pointer.
+//
+// Every export runs inside catch_unwind: a panic in the engine must never
+// unwind across the C boundary (that's UB in the host). Instead the call
+// returns PowderStatus::Panic (or null / 0 for functions returning handles
+// or sizes).
+//
+// Any failure also records a human-readable message for the calling thread,
+// readable with powder_last_error_message().
+//
+// Threading: a handle from powder_world_new must only be used by one thread
+// at a time (the host does its own locking). A handle from
+// powder_world_new_threadsafe carries a mutex that every call below takes,
+// so e.g. stepping on a worker thread while a render thread exports cells
+// or renders RGBA is safe. Raw pointers into the world (cells_ptr) are not
+// covered by the lock; use export_cells/get_cells/render_rgba across threads.
+
+use std::cell::RefCell;
+use std::cell::UnsafeCell;
+use std::ffi::CString;
+use std::ops::{Deref, DerefMut};
+use std::os::raw::{c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::save::MAX_CELLS;
+use crate::{
+    color256_of, color_of, default_life, emission_of, glyph_of, glyph_of_animated, name_of,
+    overlay_rgba, shade_of, Cell, CellChange, EdgeMode, Element, ExplosionSpec, Overlay, Palette,
+    Rect, Weather, World, WorldEvent, WorldGen,
+};
+
+/// Result of a C API call. Success is 1 so older callers that tested for a
+/// truthy return keep working; every failure is <= 0.
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PowderStatus {
+    Ok = 1,
+    NullPointer = 0,
+    InvalidElement = -1,
+    Panic = -2,
+    OutOfBounds = -3,
+    BadSize = -4,
+    OutOfMemory = -5,
+    InvalidData = -6,
+    InvalidArgument = -7,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Remember `msg` as this thread's last error and hand back `status`.
+fn fail(status: PowderStatus, msg: impl Into<String>) -> PowderStatus {
+    let msg = CString::new(msg.into()).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(msg));
+    status
+}
+
+/// Message for the most recent failed call on this thread, or null if none
+/// failed yet. The string stays valid until the next failing call on the
+/// same thread.
+#[no_mangle]
+pub extern "C" fn powder_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Opaque handle type when viewed from C/other languages.
+pub type PowderWorldHandle = *mut c_void;
+
+/// Cell as written by C callers. Same layout as `Cell`, but the element is
+/// a plain int so an out-of-range id can be rejected instead of being UB.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct FfiCell {
+    pub elem: i32,
+    pub life: i32,
+}
+
+/// Grid position for the batch cell calls.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FfiPoint {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Run `f`, turning a panic into `fallback`.
+fn guard<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(v) => v,
+        Err(payload) => {
+            let what = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic");
+            fail(PowderStatus::Panic, format!("engine panicked: {what}"));
+            fallback
+        }
+    }
+}
+
+/// Allocate an empty grid, reporting bad sizes and OOM instead of aborting.
+fn alloc_cells(width: i32, height: i32) -> Result<Vec<Cell>, PowderStatus> {
+    if width < 0 || height < 0 {
+        return Err(fail(
+            PowderStatus::BadSize,
+            format!("negative world size {width}x{height}"),
+        ));
+    }
+    let n = width as u64 * height as u64;
+    if n > MAX_CELLS {
+        return Err(fail(
+            PowderStatus::BadSize,
+            format!("world size {width}x{height} exceeds {MAX_CELLS} cells"),
+        ));
+    }
+    let mut cells = Vec::new();
+    if cells.try_reserve_exact(n as usize).is_err() {
+        return Err(fail(
+            PowderStatus::OutOfMemory,
+            format!("out of memory allocating {width}x{height} world"),
+        ));
+    }
+    cells.resize(n as usize, Cell::default());
+    Ok(cells)
+}
+
+fn null_arg() -> PowderStatus {
+    fail(PowderStatus::NullPointer, "null handle or pointer argument")
+}
+
+fn bad_element(elem: i32) -> PowderStatus {
+    fail(
+        PowderStatus::InvalidElement,
+        format!("invalid element id {elem}"),
+    )
+}
+
+fn out_of_bounds(x: i32, y: i32) -> PowderStatus {
+    fail(
+        PowderStatus::OutOfBounds,
+        format!("position ({x}, {y}) is outside the world"),
+    )
+}
+
+/// What a PowderWorldHandle points to.
+struct Handle {
+    /// Present for threadsafe handles; held for the duration of each call.
+    lock: Option<Mutex<()>>,
+    world: UnsafeCell<World>,
+}
+
+/// A world borrowed from a handle, holding its lock (if any) until dropped.
+struct WorldRef<'a> {
+    _lock: Option<MutexGuard<'a, ()>>,
+    world: &'a mut World,
+}
+
+impl Deref for WorldRef<'_> {
+    type Target = World;
+    fn deref(&self) -> &World {
+        self.world
+    }
+}
+
+impl DerefMut for WorldRef<'_> {
+    fn deref_mut(&mut self) -> &mut World {
+        self.world
+    }
+}
+
+/// Borrow the world behind a handle (caller checked for null), taking the
+/// handle's lock first if it has one. A poisoned lock is still taken: the
+/// panic that poisoned it was already reported as PowderStatus::Panic.
+unsafe fn lock_world<'a>(handle: PowderWorldHandle) -> WorldRef<'a> {
+    let h = &*(handle as *const Handle);
+    let lock = h
+        .lock
+        .as_ref()
+        .map(|m| m.lock().unwrap_or_else(|e| e.into_inner()));
+    WorldRef {
+        _lock: lock,
+        world: &mut *h.world.get(),
+    }
+}
+
+fn new_handle(width: i32, height: i32, seed: u64, threadsafe: bool) -> PowderWorldHandle {
+    guard(ptr::null_mut(), || {
+        let Ok(cells) = alloc_cells(width, height) else {
+            return ptr::null_mut();
+        };
+        let mut w = World::new(0, 0, seed);
+        w.width = width;
+        w.height = height;
+        w.cells = cells;
+        w.reset_tracking();
+        into_handle(w, threadsafe)
+    })
+}
+
+fn into_handle(world: World, threadsafe: bool) -> PowderWorldHandle {
+    let boxed = Box::new(Handle {
+        lock: threadsafe.then(|| Mutex::new(())),
+        world: UnsafeCell::new(world),
+    });
+    Box::into_raw(boxed) as PowderWorldHandle
+}
+
+/// Returns null on failure (see powder_last_error_message).
+#[no_mangle]
+pub extern "C" fn powder_world_new(width: i32, height: i32, seed: u64) -> PowderWorldHandle {
+    new_handle(width, height, seed, false)
+}
+
+/// Like powder_world_new, but every call on the handle locks internally so
+/// it can be shared between threads. Returns null on failure.
+#[no_mangle]
+pub extern "C" fn powder_world_new_threadsafe(
+    width: i32,
+    height: i32,
+    seed: u64,
+) -> PowderWorldHandle {
+    new_handle(width, height, seed, true)
+}
+
+/// Free a handle from either constructor. No other thread may be using it.
+#[no_mangle]
+pub extern "C" fn powder_world_free(handle: PowderWorldHandle) {
+    if handle.is_null() {
+        return;
+    }
+    guard((), || unsafe {
+        drop(Box::from_raw(handle as *mut Handle));
+    })
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_step(handle: PowderWorldHandle) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    guard(PowderStatus::Panic, || {
+        unsafe { lock_world(handle) }.step();
+        PowderStatus::Ok
+    })
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_clear(handle: PowderWorldHandle) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    guard(PowderStatus::Panic, || {
+        unsafe { lock_world(handle) }.clear();
+        PowderStatus::Ok
+    })
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_get_size(
+    handle: PowderWorldHandle,
+    out_width: *mut i32,
+    out_height: *mut i32,
+) -> PowderStatus {
+    if handle.is_null() || out_width.is_null() || out_height.is_null() {
+        return null_arg();
+    }
+    guard(PowderStatus::Panic, || {
+        let w = unsafe { lock_world(handle) };
+        unsafe {
+            *out_width = w.width();
+            *out_height = w.height();
+        }
+        PowderStatus::Ok
+    })
+}
+
+/// Number of steps completed since the world was created (0 on failure).
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_get_tick(handle: PowderWorldHandle) -> u64 {
+    if handle.is_null() {
+        null_arg();
+        return 0;
+    }
+    guard(0, || unsafe { lock_world(handle) }.tick())
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_resize(
+    handle: PowderWorldHandle,
+    width: i32,
+    height: i32,
+) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    guard(PowderStatus::Panic, || {
+        let cells = match alloc_cells(width, height) {
+            Ok(c) => c,
+            Err(status) => return status,
+        };
+        let mut w = unsafe { lock_world(handle) };
+        w.replace_grid(width, height, cells);
+        w.reset_tracking();
+        PowderStatus::Ok
+    })
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_place_brush(
+    handle: PowderWorldHandle,
+    cx: i32,
+    cy: i32,
+    rad: i32,
+    elem: i32,
+) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    let Ok(elem) = Element::try_from(elem) else {
+        return bad_element(elem);
+    };
+    guard(PowderStatus::Panic, || {
+        unsafe { lock_world(handle) }.place_brush(cx, cy, rad, elem);
+        PowderStatus::Ok
+    })
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_get_cell(
+    handle: PowderWorldHandle,
+    x: i32,
+    y: i32,
+    out_cell: *mut Cell,
+) -> PowderStatus {
+    if handle.is_null() || out_cell.is_null() {
+        return null_arg();
+    }
+    guard(PowderStatus::Panic, || {
+        let w = unsafe { lock_world(handle) };
+        if !w.in_bounds(x, y) {
+            return out_of_bounds(x, y);
+        }
+        let c = w.get_cell(x, y);
+        unsafe {
+            *out_cell = c;
+        }
+        PowderStatus::Ok
+    })
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_set_cell(
+    handle: PowderWorldHandle,
+    x: i32,
+    y: i32,
+    cell: FfiCell,
+) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    let Ok(elem) = Element::try_from(cell.elem) else {
+        return bad_element(cell.elem);
+    };
+    guard(PowderStatus::Panic, || {
+        match unsafe { lock_world(handle) }.get_cell_mut(x, y) {
+            Some(c) => {
+                *c = Cell {
+                    elem,
+                    life: cell.life,
+                };
+                PowderStatus::Ok
+            }
+            None => out_of_bounds(x, y),
+        }
+    })
+}
+
+/// Set what happens at the world edges; `mode` is an EdgeMode value.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_set_edge_mode(handle: PowderWorldHandle, mode: i32) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    let Some(mode) = EdgeMode::from_id(mode) else {
+        return fail(
+            PowderStatus::InvalidArgument,
+            format!("invalid edge mode {mode}"),
+        );
+    };
+    guard(PowderStatus::Panic, || {
+        unsafe { lock_world(handle) }.set_edge_mode(mode);
+        PowderStatus::Ok
+    })
+}
+
+/// Set the weather: `kind` 0 Clear, 1 Rain, 2 Snow, 3 AcidRain, 4 Meteors,
+/// with `intensity` as documented on Weather.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_set_weather(
+    handle: PowderWorldHandle,
+    kind: i32,
+    intensity: u32,
+) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    let Some(weather) = Weather::from_id(kind, intensity) else {
+        return fail(
+            PowderStatus::InvalidArgument,
+            format!("invalid weather kind {kind}"),
+        );
+    };
+    guard(PowderStatus::Panic, || {
+        unsafe { lock_world(handle) }.set_weather(weather);
+        PowderStatus::Ok
+    })
+}
+
+/// Clear the world and fill it with terrain generated from `seed` using
+/// the default WorldGen settings.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_generate(handle: PowderWorldHandle, seed: u64) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    guard(PowderStatus::Panic, || {
+        unsafe { lock_world(handle) }.generate(seed, &WorldGen::DEFAULT);
+        PowderStatus::Ok
+    })
+}
+
+/// Called for each changed cell of a watched region: (user_data, x, y,
+/// old element id, new element id).
+pub type PowderCellCallback = Option<extern "C" fn(*mut c_void, i32, i32, i32, i32)>;
+
+/// A C callback and its context pointer.
+struct CellWatcher {
+    callback: extern "C" fn(*mut c_void, i32, i32, i32, i32),
+    user_data: *mut c_void,
+}
+
+// The callback only ever runs inside powder_world_step on whichever thread
+// steps the world; making that safe for `user_data` is the caller's job,
+// as documented on powder_world_watch_region.
+unsafe impl Send for CellWatcher {}
+unsafe impl Sync for CellWatcher {}
+
+impl CellWatcher {
+    fn call(&self, c: CellChange) {
+        (self.callback)(self.user_data, c.x, c.y, c.from as i32, c.to as i32);
+    }
+}
+
+/// Call `callback` during powder_world_step for every cell in the rectangle
+/// whose element changed. It runs on the stepping thread with the world
+/// locked, so it must not call back into this world. Returns a watcher id
+/// for powder_world_unwatch, or 0 on failure.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_watch_region(
+    handle: PowderWorldHandle,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    callback: PowderCellCallback,
+    user_data: *mut c_void,
+) -> u64 {
+    let Some(callback) = callback.filter(|_| !handle.is_null()) else {
+        null_arg();
+        return 0;
+    };
+    let watcher = CellWatcher {
+        callback,
+        user_data,
+    };
+    guard(0, || {
+        unsafe { lock_world(handle) }
+            .watch_region(Rect::new(x, y, width, height), move |c| watcher.call(c))
+    })
+}
+
+/// Remove a watcher added by powder_world_watch_region.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_unwatch(handle: PowderWorldHandle, id: u64) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    guard(PowderStatus::Panic, || {
+        if unsafe { lock_world(handle) }.unwatch(id) {
+            PowderStatus::Ok
+        } else {
+            fail(PowderStatus::InvalidArgument, format!("no watcher {id}"))
+        }
+    })
+}
+
+/// Place a fresh `elem` at (x, y) with its default life (unlike
+/// powder_world_set_cell, which stores the life it is given).
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_spawn(
+    handle: PowderWorldHandle,
+    x: i32,
+    y: i32,
+    elem: i32,
+) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    let Ok(elem) = Element::try_from(elem) else {
+        return bad_element(elem);
+    };
+    guard(PowderStatus::Panic, || {
+        if unsafe { lock_world(handle) }.spawn(x, y, elem) {
+            PowderStatus::Ok
+        } else {
+            out_of_bounds(x, y)
+        }
+    })
+}
+
+/// Set off an explosion at (x, y) as described by `spec`, or with null, the
+/// default blast gunpowder makes (radius 5, 50% fire, 30% smoke).
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_detonate(
+    handle: PowderWorldHandle,
+    x: i32,
+    y: i32,
+    spec: *const ExplosionSpec,
+) -> PowderStatus {
+    if handle.is_null() {
+        return null_arg();
+    }
+    let spec = if spec.is_null() {
+        ExplosionSpec::DEFAULT
+    } else {
+        unsafe { *spec }
+    };
+    guard(PowderStatus::Panic, || {
+        unsafe { lock_world(handle) }.detonate(x, y, spec);
+        PowderStatus::Ok
+    })
+}
+
+/// Write `count` cells at the matching `points`. Every element id is checked
+/// before anything is written, so an invalid id leaves the world untouched.
+/// Points outside the world are skipped.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_set_cells(
+    handle: PowderWorldHandle,
+    points: *const FfiPoint,
+    cells: *const FfiCell,
+    count: usize,
+) -> PowderStatus {
+    if handle.is_null() || (count > 0 && (points.is_null() || cells.is_null())) {
+        return null_arg();
+    }
+    if count == 0 {
+        return PowderStatus::Ok;
+    }
+    let points = unsafe { std::slice::from_raw_parts(points, count) };
+    let cells = unsafe { std::slice::from_raw_parts(cells, count) };
+    let mut elems = Vec::with_capacity(count);
+    for c in cells {
+        match Element::try_from(c.elem) {
+            Ok(e) => elems.push(e),
+            Err(_) => return bad_element(c.elem),
+        }
+    }
+    guard(PowderStatus::Panic, || {
+        let mut w = unsafe { lock_world(handle) };
+        for ((p, c), elem) in points.iter().zip(cells).zip(elems) {
+            if let Some(dst) = w.get_cell_mut(p.x, p.y) {
+                *dst = Cell { elem, life: c.life };
+            }
+        }
+        PowderStatus::Ok
+    })
+}
+
+/// Read the cells at `count` `points` into `out_cells`. Points outside the
+/// world read as Empty.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_get_cells(
+    handle: PowderWorldHandle,
+    points: *const FfiPoint,
+    out_cells: *mut Cell,
+    count: usize,
+) -> PowderStatus {
+    if handle.is_null() || (count > 0 && (points.is_null() || out_cells.is_null())) {
+        return null_arg();
+    }
+    if count == 0 {
+        return PowderStatus::Ok;
+    }
+    guard(PowderStatus::Panic, || {
+        let w = unsafe { lock_world(handle) };
+        let points = unsafe { std::slice::from_raw_parts(points, count) };
+        let out = unsafe { std::slice::from_raw_parts_mut(out_cells, count) };
+        for (p, dst) in points.iter().zip(out) {
+            *dst = w.get_cell(p.x, p.y);
+        }
+        PowderStatus::Ok
+    })
+}
+
+/// Export the internal cell buffer in row-major order (y * width + x).
+/// `out_cells` must point to a buffer of at least `max_len` Cells.
+/// Returns the number of cells written.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_export_cells(
+    handle: PowderWorldHandle,
+    out_cells: *mut Cell,
+    max_len: usize,
+) -> usize {
+    if handle.is_null() || out_cells.is_null() {
+        null_arg();
+        return 0;
+    }
+    guard(0, || {
+        let w = unsafe { lock_world(handle) };
+        let n = w.cells.len().min(max_len);
+        unsafe {
+            ptr::copy_nonoverlapping(w.cells.as_ptr(), out_cells, n);
+        }
+        n
+    })
+}
+
+/// Copy an overlay into `out_buf`, at most `max_len` values.
+fn export_overlay<T: Copy>(
+    handle: PowderWorldHandle,
+    out_buf: *mut T,
+    max_len: usize,
+    overlay: fn(&mut World) -> &[T],
+) -> usize {
+    if handle.is_null() || out_buf.is_null() {
+        null_arg();
+        return 0;
+    }
+    guard(0, || {
+        let mut w = unsafe { lock_world(handle) };
+        let values = overlay(&mut w);
+        let n = values.len().min(max_len);
+        unsafe {
+            ptr::copy_nonoverlapping(values.as_ptr(), out_buf, n);
+        }
+        n
+    })
+}
+
+// Debug overlays, row-major like powder_world_export_cells (see
+// World::temperature_map and friends for units). Each returns the number
+// of values written.
+
+/// Nominal temperature per cell, in degrees C.
+#[no_mangle]
+pub extern "C" fn powder_world_export_temperature(
+    handle: PowderWorldHandle,
+    out_buf: *mut f32,
+    max_len: usize,
+) -> usize {
+    export_overlay(handle, out_buf, max_len, World::temperature_map)
+}
+
+/// Hydrostatic pressure per cell, in cells of water.
+#[no_mangle]
+pub extern "C" fn powder_world_export_pressure(
+    handle: PowderWorldHandle,
+    out_buf: *mut f32,
+    max_len: usize,
+) -> usize {
+    export_overlay(handle, out_buf, max_len, World::pressure_map)
+}
+
+/// Charge per cell (wire, metal and water; 0 elsewhere).
+#[no_mangle]
+pub extern "C" fn powder_world_export_charge(
+    handle: PowderWorldHandle,
+    out_buf: *mut i16,
+    max_len: usize,
+) -> usize {
+    export_overlay(handle, out_buf, max_len, World::charge_map)
+}
+
+/// Steps since each cell last changed. The first call starts tracking and
+/// reports all zeros.
+#[no_mangle]
+pub extern "C" fn powder_world_export_activity(
+    handle: PowderWorldHandle,
+    out_buf: *mut i16,
+    max_len: usize,
+) -> usize {
+    export_overlay(handle, out_buf, max_len, World::activity_map)
+}
+
+/// Same-liquid cells above each cell (see World::liquid_depth_map). The
+/// first call turns depth tracking on.
+#[no_mangle]
+pub extern "C" fn powder_world_export_depth(
+    handle: PowderWorldHandle,
+    out_buf: *mut u16,
+    max_len: usize,
+) -> usize {
+    export_overlay(handle, out_buf, max_len, World::liquid_depth_map)
+}
+
+/// Read-only pointer to the internal cell buffer (row-major, same layout as
+/// `powder_world_export_cells`), with the cell count in `out_len`. No copy
+/// is made: the pointer is only valid until the next call that mutates or
+/// frees the world (step, clear, resize, brush, set_cell, load, ...).
+/// Returns null on failure.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_cells_ptr(
+    handle: PowderWorldHandle,
+    out_len: *mut usize,
+) -> *const Cell {
+    if handle.is_null() || out_len.is_null() {
+        null_arg();
+        return ptr::null();
+    }
+    guard(ptr::null(), || {
+        let w = unsafe { lock_world(handle) };
+        unsafe {
+            *out_len = w.cells.len();
+        }
+        w.cells.as_ptr()
+    })
+}
+
+/// Render the world as packed RGBA (width*height*4 bytes, row-major) into
+/// `out_buf`. `palette` may be null for the default colors and tint.
+/// Returns the full image size. Nothing is written when that exceeds
+/// `max_len`, so call with a null buffer first to query the size.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_render_rgba(
+    handle: PowderWorldHandle,
+    out_buf: *mut u8,
+    max_len: usize,
+    palette: *const Palette,
+) -> usize {
+    if handle.is_null() {
+        null_arg();
+        return 0;
+    }
+    guard(0, || {
+        let w = unsafe { lock_world(handle) };
+        let palette = if palette.is_null() {
+            Palette::DEFAULT
+        } else {
+            unsafe { *palette }
+        };
+        let len = w.cells.len() * 4;
+        if !out_buf.is_null() && len <= max_len {
+            let out = unsafe { std::slice::from_raw_parts_mut(out_buf, len) };
+            w.render_rgba_into(out, &palette);
+        }
+        len
+    })
+}
+
+/// Write emission_of() for every cell (width*height bytes, row-major) into
+/// `out_buf`, for bloom and light halos. Same contract as
+/// powder_world_render_rgba: returns the full size, writes only if it fits.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_render_emission(
+    handle: PowderWorldHandle,
+    out_buf: *mut u8,
+    max_len: usize,
+) -> usize {
+    if handle.is_null() {
+        null_arg();
+        return 0;
+    }
+    guard(0, || {
+        let w = unsafe { lock_world(handle) };
+        let len = w.cells.len();
+        if !out_buf.is_null() && len <= max_len {
+            let out = unsafe { std::slice::from_raw_parts_mut(out_buf, len) };
+            w.render_emission_into(out);
+        }
+        len
+    })
+}
+
+/// Draw a translucent debug overlay (see overlay_rgba) into `out_buf`:
+/// 0 charge, 1 life, 2 cells changed last step, 3 chunk activity (2 and 3
+/// need provenance tracking). Same contract as powder_world_render_rgba;
+/// returns 0 for an invalid `kind`.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_render_overlay(
+    handle: PowderWorldHandle,
+    kind: i32,
+    out_buf: *mut u8,
+    max_len: usize,
+) -> usize {
+    if handle.is_null() {
+        null_arg();
+        return 0;
+    }
+    let Some(overlay) = Overlay::from_id(kind) else {
+        fail(
+            PowderStatus::InvalidArgument,
+            format!("invalid overlay {kind}"),
+        );
+        return 0;
+    };
+    guard(0, || {
+        let w = unsafe { lock_world(handle) };
+        let len = w.cells.len() * 4;
+        if !out_buf.is_null() && len <= max_len {
+            let out = unsafe { std::slice::from_raw_parts_mut(out_buf, len) };
+            overlay_rgba(&w, overlay, out);
+        }
+        len
+    })
+}
+
+/// Fill `out_palette` with a built-in palette for powder_world_render_rgba:
+/// 0 classic (ncurses colors), 1 xterm 256-color, 2 rich (the default).
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_palette_builtin(kind: i32, out_palette: *mut Palette) -> PowderStatus {
+    if out_palette.is_null() {
+        return null_arg();
+    }
+    let Some(palette) = Palette::from_id(kind) else {
+        return fail(
+            PowderStatus::InvalidArgument,
+            format!("invalid palette {kind}"),
+        );
+    };
+    unsafe { *out_palette = palette };
+    PowderStatus::Ok
+}
+
+/// Untinted color of an element under `palette` (null for the default),
+/// packed as 0xRRGGBBAA. 0 for an invalid id.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_palette_color(palette: *const Palette, elem: i32, life: i32) -> u32 {
+    let Ok(e) = Element::try_from(elem) else {
+        bad_element(elem);
+        return 0;
+    };
+    let palette = if palette.is_null() {
+        Palette::DEFAULT
+    } else {
+        unsafe { *palette }
+    };
+    u32::from_be_bytes(palette.rgba_of(e, life))
+}
+
+/// Write row `y`'s glyphs (one ASCII byte per cell, as powder_glyph_of,
+/// including life-based animation) into `out_bytes`. Returns the number of
+/// bytes written: min(width, max).
+#[no_mangle]
+pub extern "C" fn powder_world_render_row_glyphs(
+    handle: PowderWorldHandle,
+    y: i32,
+    out_bytes: *mut u8,
+    max: usize,
+) -> usize {
+    render_row(handle, y, out_bytes, max, |c| {
+        glyph_of(c.elem, c.life) as u8
+    })
+}
+
+/// Like powder_world_render_row_glyphs, but writes powder_color_of indices.
+#[no_mangle]
+pub extern "C" fn powder_world_render_row_colors(
+    handle: PowderWorldHandle,
+    y: i32,
+    out_bytes: *mut u8,
+    max: usize,
+) -> usize {
+    render_row(handle, y, out_bytes, max, |c| color_of(c.elem, c.life))
+}
+
+fn render_row(
+    handle: PowderWorldHandle,
+    y: i32,
+    out: *mut u8,
+    max: usize,
+    f: impl Fn(&Cell) -> u8,
+) -> usize {
+    if handle.is_null() || out.is_null() {
+        null_arg();
+        return 0;
+    }
+    guard(0, || {
+        let w = unsafe { lock_world(handle) };
+        if y < 0 || y >= w.height {
+            out_of_bounds(0, y);
+            return 0;
+        }
+        // not idx(): a zero-width wrapped world has rows but nothing to wrap
+        let start = y as usize * w.width as usize;
+        let row = &w.cells[start..start + w.width as usize];
+        let n = row.len().min(max);
+        let out = unsafe { std::slice::from_raw_parts_mut(out, n) };
+        for (dst, c) in out.iter_mut().zip(row) {
+            *dst = f(c);
+        }
+        n
+    })
+}
+
+/// Serialize the world into `out_buf` using the binary save format.
+/// Returns the full encoded size. Nothing is written when that exceeds
+/// `max_len`, so call with a null buffer first to query the size.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_save(
+    handle: PowderWorldHandle,
+    out_buf: *mut u8,
+    max_len: usize,
+) -> usize {
+    if handle.is_null() {
+        null_arg();
+        return 0;
+    }
+    guard(0, || {
+        let bytes = unsafe { lock_world(handle) }.save_to_bytes();
+        if !out_buf.is_null() && bytes.len() <= max_len {
+            unsafe {
+                ptr::copy_nonoverlapping(bytes.as_ptr(), out_buf, bytes.len());
+            }
+        }
+        bytes.len()
+    })
+}
+
+/// Replace the world's size and cells with a save from `powder_world_save`.
+/// Returns InvalidData if the bytes don't decode (world left untouched).
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_load(
+    handle: PowderWorldHandle,
+    buf: *const u8,
+    len: usize,
+) -> PowderStatus {
+    if handle.is_null() || buf.is_null() {
+        return null_arg();
+    }
+    guard(PowderStatus::Panic, || {
+        let mut w = unsafe { lock_world(handle) };
+        let bytes = unsafe { std::slice::from_raw_parts(buf, len) };
+        match World::load_from_bytes(bytes) {
+            Ok(loaded) => {
+                w.replace_grid(loaded.width, loaded.height, loaded.cells);
+                w.tick = loaded.tick;
+                w.seed = loaded.seed;
+                w.rng = loaded.rng;
+                w.reset_tracking();
+                w.cells_changed();
+                PowderStatus::Ok
+            }
+            Err(e) => fail(PowderStatus::InvalidData, format!("bad save data: {e}")),
+        }
+    })
+}
+
+/// Serialize the world with the binary save format. Same contract as
+/// powder_world_save: returns the full size and writes only if it fits.
+#[no_mangle]
+pub extern "C" fn powder_world_serialize(
+    handle: PowderWorldHandle,
+    out_buf: *mut u8,
+    max_len: usize,
+) -> usize {
+    powder_world_save(handle, out_buf, max_len)
+}
+
+/// Build a new world from serialized bytes. Returns null (see
+/// powder_last_error_message) if the data is invalid. Free the result with
+/// powder_world_free.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_deserialize(buf: *const u8, len: usize) -> PowderWorldHandle {
+    if buf.is_null() {
+        null_arg();
+        return ptr::null_mut();
+    }
+    guard(ptr::null_mut(), || {
+        let bytes = unsafe { std::slice::from_raw_parts(buf, len) };
+        match World::load_from_bytes(bytes) {
+            Ok(world) => into_handle(world, false),
+            Err(e) => {
+                fail(PowderStatus::InvalidData, format!("bad save data: {e}"));
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PowderEventKind {
+    Explosion = 1,
+    HumanDied = 2,
+    ElementConverted = 3,
+    ActorSpawned = 4,
+    ActorInfected = 5,
+    ActorDied = 6,
+    ZombieKilled = 7,
+    Ignited = 8,
+    Dissolved = 9,
+    ElementCreated = 10,
+}
+
+/// Flat event for C callers. `a`/`b` depend on `kind`: radius for
+/// Explosion, from/to element ids for ElementConverted, the actor's element
+/// id for ActorSpawned/ActorDied, the element id for Ignited, Dissolved and
+/// ElementCreated, unused otherwise. `id` is the actor id
+/// for actor events (the zombie for ZombieKilled, with the killer's id in
+/// `by`), 0 otherwise.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FfiEvent {
+    pub kind: PowderEventKind,
+    pub x: i32,
+    pub y: i32,
+    pub a: i32,
+    pub b: i32,
+    pub id: u64,
+    pub by: u64,
+}
+
+impl From<WorldEvent> for FfiEvent {
+    fn from(ev: WorldEvent) -> Self {
+        let flat = |kind, x, y, a, b| FfiEvent {
+            kind,
+            x,
+            y,
+            a,
+            b,
+            id: 0,
+            by: 0,
+        };
+        match ev {
+            WorldEvent::Explosion { x, y, radius } => {
+                flat(PowderEventKind::Explosion, x, y, radius, 0)
+            }
+            WorldEvent::HumanDied { x, y } => flat(PowderEventKind::HumanDied, x, y, 0, 0),
+            WorldEvent::ElementConverted { x, y, from, to } => flat(
+                PowderEventKind::ElementConverted,
+                x,
+                y,
+                from as i32,
+                to as i32,
+            ),
+            WorldEvent::ActorSpawned { x, y, id, elem } => FfiEvent {
+                id,
+                ..flat(PowderEventKind::ActorSpawned, x, y, elem as i32, 0)
+            },
+            WorldEvent::ActorInfected { x, y, id } => FfiEvent {
+                id,
+                ..flat(PowderEventKind::ActorInfected, x, y, 0, 0)
+            },
+            WorldEvent::ActorDied { x, y, id, elem } => FfiEvent {
+                id,
+                ..flat(PowderEventKind::ActorDied, x, y, elem as i32, 0)
+            },
+            WorldEvent::ZombieKilled { x, y, id, by } => FfiEvent {
+                id,
+                by,
+                ..flat(PowderEventKind::ZombieKilled, x, y, 0, 0)
+            },
+            WorldEvent::Ignited { x, y, elem } => {
+                flat(PowderEventKind::Ignited, x, y, elem as i32, 0)
+            }
+            WorldEvent::Dissolved { x, y, elem } => {
+                flat(PowderEventKind::Dissolved, x, y, elem as i32, 0)
+            }
+            WorldEvent::ElementCreated { x, y, elem } => {
+                flat(PowderEventKind::ElementCreated, x, y, elem as i32, 0)
+            }
+        }
+    }
+}
+
+/// Move up to `max` pending events (oldest first) into `out_events`.
+/// Returns how many were written; call again until it returns less than
+/// `max` to drain the queue.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_world_poll_events(
+    handle: PowderWorldHandle,
+    out_events: *mut FfiEvent,
+    max: usize,
+) -> usize {
+    if handle.is_null() || out_events.is_null() {
+        null_arg();
+        return 0;
+    }
+    guard(0, || {
+        let mut w = unsafe { lock_world(handle) };
+        let n = w.events.len().min(max);
+        for (i, ev) in w.events.drain(..n).enumerate() {
+            unsafe {
+                *out_events.add(i) = ev.into();
+            }
+        }
+        n
+    })
+}
+
+/// Number of element ids (valid ids are 0..count).
+#[no_mangle]
+pub extern "C" fn powder_element_count() -> i32 {
+    Element::COUNT as i32
+}
+
+/// Life a freshly placed cell of `elem` starts with (see default_life);
+/// 0 for an invalid id.
+#[no_mangle]
+pub extern "C" fn powder_element_default_life(elem: i32) -> i32 {
+    Element::try_from(elem).map_or(0, default_life)
+}
+
+/// Copy the element's name plus a NUL terminator into `out_buf`. Returns the
+/// size needed including the NUL (0 for an invalid id); nothing is written
+/// when that exceeds `max_len`.
+#[allow(clippy::not_unsafe_ptr_arg_deref)] // null-checked, see above
+#[no_mangle]
+pub extern "C" fn powder_element_name(elem: i32, out_buf: *mut c_char, max_len: usize) -> usize {
+    let Ok(e) = Element::try_from(elem) else {
+        bad_element(elem);
+        return 0;
+    };
+    let name = name_of(e).as_bytes();
+    let len = name.len() + 1;
+    if !out_buf.is_null() && len <= max_len {
+        unsafe {
+            ptr::copy_nonoverlapping(name.as_ptr(), out_buf as *mut u8, name.len());
+            *out_buf.add(name.len()) = 0;
+        }
+    }
+    len
+}
+
+// Cheap wrappers for glyph/color so other languages can use the same mapping
+// without re-implementing logic, if they want. i tried my best
+
+// Invalid element ids give 0 (no color / NUL glyph).
+
+#[no_mangle]
+pub extern "C" fn powder_color_of(elem: i32, life: i32) -> u8 {
+    match Element::try_from(elem) {
+        Ok(e) => guard(0, || color_of(e, life)),
+        Err(_) => 0,
+    }
+}
+
+/// Brightness 0-255 for animating fire, lava and lightning at `tick` (see
+/// powder_world_get_tick); 255 for other elements.
+#[no_mangle]
+pub extern "C" fn powder_shade_of(elem: i32, life: i32, tick: u64) -> u8 {
+    match Element::try_from(elem) {
+        Ok(e) => guard(0, || shade_of(e, life, tick)),
+        Err(_) => 0,
+    }
+}
+
+/// Light given off by a cell of `elem` with `life`, 0-255 (see
+/// emission_of).
+#[no_mangle]
+pub extern "C" fn powder_emission_of(elem: i32, life: i32) -> u8 {
+    match Element::try_from(elem) {
+        Ok(e) => guard(0, || emission_of(e, life)),
+        Err(_) => 0,
+    }
+}
+
+/// xterm 256-color index (see color256_of).
+#[no_mangle]
+pub extern "C" fn powder_color256_of(elem: i32, life: i32) -> u8 {
+    match Element::try_from(elem) {
+        Ok(e) => guard(0, || color256_of(e, life)),
+        Err(_) => 0,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn powder_glyph_of(elem: i32, life: i32) -> u8 {
+    match Element::try_from(elem) {
+        Ok(e) => guard(0, || glyph_of(e, life) as u8),
+        Err(_) => 0,
+    }
+}
+
+/// Glyph with fire, water and gas animation for the cell at (x, y) at
+/// `tick` (see glyph_of_animated).
+#[no_mangle]
+pub extern "C" fn powder_glyph_of_animated(
+    elem: i32,
+    life: i32,
+    x: i32,
+    y: i32,
+    tick: u64,
+) -> u8 {
+    match Element::try_from(elem) {
+        Ok(e) => guard(0, || glyph_of_animated(e, life, x, y, tick) as u8),
+        Err(_) => 0,
+    }
+}
+// please file an issue in github if there is any sort of issue, thanks
@@ -0,0 +1,50 @@
+// PowderCore - firefighting humans
+//
+// A human standing next to water can throw it onto fire it sees nearby:
+// the water cell is used up and the fire turns into smoke. Villages with
+// a well or a pond nearby get a chance against arson.
+//
+// SimConfig::firefight_chance is the percent chance per tick that a human
+// with water at hand and fire within SimConfig::firefight_range acts on it;
+// 0 turns firefighting off.
+
+use crate::{default_life, Element, World};
+
+fn throwable(e: Element) -> bool {
+    matches!(e, Element::Water | Element::SaltWater)
+}
+
+impl World {
+    /// Let the human at (x, y) douse a fire. Returns true if it did, which
+    /// uses up its turn.
+    pub(crate) fn fight_fire(&mut self, x: i32, y: i32) -> bool {
+        let chance = self.config.firefight_chance;
+        if chance == 0 {
+            return false;
+        }
+        // uncharged water only, nobody scoops up a live wire's puddle
+        let water = (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            .find(|&(nx, ny)| {
+                self.in_bounds(nx, ny) && {
+                    let c = self.cells[self.idx(nx, ny)];
+                    throwable(c.elem) && c.life == 0
+                }
+            });
+        let Some((wx, wy)) = water else {
+            return false;
+        };
+        let Some((fx, fy)) = self.spot(x, y, self.config.firefight_range, Element::Fire) else {
+            return false;
+        };
+        if !self.rng.chance(chance) {
+            return false;
+        }
+
+        let (wi, fi) = (self.idx(wx, wy), self.idx(fx, fy));
+        self.put(wi, Element::Empty, 0);
+        self.put(fi, Element::Smoke, default_life(Element::Smoke));
+        self.emit_converted(fx, fy, Element::Fire, Element::Smoke);
+        true
+    }
+}
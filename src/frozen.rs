@@ -0,0 +1,52 @@
+// PowderCore - frozen regions
+//
+// Editors can freeze a finished part of a build while working elsewhere.
+// A frozen cell is never stepped: step() starts each tick with those cells
+// already marked as updated, so they don't fall, flow, burn or react.
+// Cells outside can still move into a frozen cell that is empty (and then
+// stay put there), but nothing inside moves on its own.
+//
+// The mask is one flag per cell, left empty while nothing is frozen so
+// ordinary worlds pay nothing. It is dropped when the world is resized or
+// restored to a different size, and isn't saved.
+
+use crate::{Rect, World};
+
+impl World {
+    /// Freeze (or thaw) every cell in `rect`, clipped to the world.
+    pub fn set_frozen_region(&mut self, rect: Rect, frozen: bool) {
+        let r = rect.clip(self.width, self.height);
+        if r.width <= 0 || r.height <= 0 || (!frozen && self.frozen.is_empty()) {
+            return;
+        }
+        if self.frozen.len() != self.cells.len() {
+            self.frozen = vec![false; self.cells.len()];
+        }
+        for y in r.y..r.y + r.height {
+            let start = self.idx(r.x, y);
+            self.frozen[start..start + r.width as usize].fill(frozen);
+        }
+        if !frozen && !self.frozen.contains(&true) {
+            self.frozen.clear();
+        }
+    }
+
+    /// Whether (x, y) is in a frozen region.
+    pub fn is_frozen(&self, x: i32, y: i32) -> bool {
+        self.in_bounds(x, y) && self.frozen.get(self.idx(x, y)) == Some(&true)
+    }
+
+    /// Thaw every frozen region.
+    pub fn unfreeze_all(&mut self) {
+        self.frozen.clear();
+    }
+
+    /// Per-cell "already updated" flags to start a step with.
+    pub(crate) fn initial_updated(&mut self) -> Vec<bool> {
+        if self.frozen.len() == self.cells.len() {
+            return self.frozen.clone();
+        }
+        self.frozen.clear();
+        vec![false; self.cells.len()]
+    }
+}
@@ -0,0 +1,80 @@
+// PowderCore - gravity zones
+//
+// Rectangles where "down" points somewhere else: up, sideways, diagonally,
+// or nowhere at all. Powders, liquids and gases ask gravity() for the
+// direction of the cell they are in and move relative to it:
+//
+//   - powders fall along gravity, then slide to either side of it,
+//   - liquids fall along gravity, then spread across it,
+//   - gases rise against gravity and drift across it.
+//
+// With diagonal gravity, powders slide along its two axis components
+// instead. A zone of (0, 0) is weightless and nothing in it falls or rises.
+// Actors, fire and everything else ignore zones.
+//
+// Zones are baked into one direction per cell as they are added (later
+// zones win where they overlap); the table stays empty until the first
+// zone so ordinary worlds pay nothing. Like frozen regions, zones are
+// dropped on resize and restore to a different size, and aren't saved.
+
+use crate::{Rect, World};
+
+/// Normal gravity: one cell down.
+pub(crate) const DOWN: (i32, i32) = (0, 1);
+
+impl World {
+    /// Make everything in `rect` (clipped to the world) fall towards
+    /// (dx, dy), each clamped to -1..=1.
+    pub fn add_gravity_zone(&mut self, rect: Rect, dx: i32, dy: i32) {
+        let r = rect.clip(self.width, self.height);
+        if r.width <= 0 || r.height <= 0 {
+            return;
+        }
+        if self.gravity.len() != self.cells.len() {
+            self.gravity = vec![(DOWN.0 as i8, DOWN.1 as i8); self.cells.len()];
+        }
+        let g = (dx.clamp(-1, 1) as i8, dy.clamp(-1, 1) as i8);
+        for y in r.y..r.y + r.height {
+            let start = self.idx(r.x, y);
+            self.gravity[start..start + r.width as usize].fill(g);
+        }
+    }
+
+    /// Remove every gravity zone.
+    pub fn clear_gravity_zones(&mut self) {
+        self.gravity.clear();
+    }
+
+    /// Which way is down at (x, y).
+    pub fn gravity_at(&self, x: i32, y: i32) -> (i32, i32) {
+        if !self.in_bounds(x, y) {
+            return DOWN;
+        }
+        self.gravity(self.idx(x, y))
+    }
+
+    /// Which way is down for cell `i`.
+    pub(crate) fn gravity(&self, i: usize) -> (i32, i32) {
+        match self.gravity.get(i) {
+            Some(&(gx, gy)) if self.gravity.len() == self.cells.len() => (gx as i32, gy as i32),
+            _ => DOWN,
+        }
+    }
+}
+
+/// Offset across gravity `g`, towards side `s` (+1 or -1). For normal
+/// gravity that's (s, 0).
+pub(crate) fn across(g: (i32, i32), s: i32) -> (i32, i32) {
+    (g.1 * s, -g.0 * s)
+}
+
+/// Where a powder under gravity `g` slides when it can't fall straight:
+/// one cell along `g` and one to side `s`, or for diagonal gravity one of
+/// its two axis components.
+pub(crate) fn slide(g: (i32, i32), s: i32) -> (i32, i32) {
+    if g.0 != 0 && g.1 != 0 {
+        return if s > 0 { (g.0, 0) } else { (0, g.1) };
+    }
+    let (ax, ay) = across(g, s);
+    (g.0 + ax, g.1 + ay)
+}
@@ -0,0 +1,68 @@
+// PowderCore - zombie hordes
+//
+// Zombies used to wander at random whenever no human was in sight, so a
+// horde fell apart within a few ticks. Now each zombie remembers where it
+// last saw a human (Actor::target) and shares it: a zombie without a
+// target picks one up from any zombie it can see, so the whole pack heads
+// for the last sighting. A zombie with nothing to chase drifts toward the
+// middle of the zombies around it instead of walking off alone.
+//
+// Targets are cleared once the zombie gets there.
+
+use crate::{Element, World};
+
+impl World {
+    /// Direction the zombie at (x, y) should walk given the human (or
+    /// corpse) it can see, if any. None means no preference.
+    pub(crate) fn horde_dir(&mut self, x: i32, y: i32, seen: Option<(i32, i32)>) -> Option<i32> {
+        let idx0 = self.idx(x, y);
+        if let Some(pos) = seen {
+            if let Some(a) = self.actor_mut(idx0) {
+                a.target = Some(pos);
+            }
+            return None;
+        }
+
+        let r = self.config.zombie_vision;
+        let mut shared = None;
+        let (mut sum_x, mut n) = (0, 0);
+        for ry in -r..=r {
+            for rx in -r..=r {
+                let (nx, ny) = (x + rx, y + ry);
+                if (rx, ry) == (0, 0) || !self.in_bounds(nx, ny) {
+                    continue;
+                }
+                let i = self.idx(nx, ny);
+                if self.cells[i].elem != Element::Zombie || !self.can_see(x, y, nx, ny) {
+                    continue;
+                }
+                sum_x += nx;
+                n += 1;
+                if shared.is_none() {
+                    shared = self.actor_at(nx, ny).and_then(|a| a.target);
+                }
+            }
+        }
+
+        let a = self.actor_mut(idx0)?;
+        if a.target.is_none() {
+            a.target = shared;
+        }
+        if let Some((tx, _)) = a.target {
+            if (tx - x).abs() <= 1 {
+                a.target = None;
+            } else {
+                return Some((tx - x).signum());
+            }
+        }
+
+        // stick with the pack
+        if n > 0 {
+            let mid = sum_x / n;
+            if (mid - x).abs() > 2 {
+                return Some((mid - x).signum());
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,88 @@
+// PowderCore - cell iteration
+//
+// Row-major walks over the whole grid or a rectangle, yielding positions
+// with each cell, so tools don't need nested get_cell loops. With the
+// "parallel" feature there are also versions that split the rows across
+// threads (std::thread::scope, no thread pool dependency).
+
+use crate::{Cell, Rect, World};
+
+impl World {
+    /// Every cell with its position, row by row.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (i32, i32, &Cell)> + '_ {
+        let w = self.width.max(1) as usize;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, c)| ((i % w) as i32, (i / w) as i32, c))
+    }
+
+    /// Cells inside `rect` (clipped to the world), row by row.
+    pub fn iter_region(&self, rect: Rect) -> impl Iterator<Item = (i32, i32, &Cell)> + '_ {
+        let r = rect.clip(self.width, self.height);
+        (r.y..r.y + r.height).flat_map(move |y| {
+            let start = self.idx(r.x, y);
+            self.cells[start..start + r.width as usize]
+                .iter()
+                .enumerate()
+                .map(move |(i, c)| (r.x + i as i32, y, c))
+        })
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl World {
+    /// Call `f` for every cell, spreading rows over all available cores.
+    /// Order is unspecified.
+    pub fn par_for_each_cell<F>(&self, f: F)
+    where
+        F: Fn(i32, i32, &Cell) + Sync,
+    {
+        self.par_for_each_in_region(Rect::new(0, 0, self.width, self.height), f);
+    }
+
+    /// Parallel version of `iter_region` with a callback. Order is
+    /// unspecified.
+    pub fn par_for_each_in_region<F>(&self, rect: Rect, f: F)
+    where
+        F: Fn(i32, i32, &Cell) + Sync,
+    {
+        self.par_fold_region(rect, || (), |(), x, y, c| f(x, y, c), |(), ()| ());
+    }
+
+    /// Fold each band of rows in parallel with `fold` (starting from
+    /// `init()`), then combine the band results with `reduce`.
+    pub fn par_fold_region<T, I, F, R>(&self, rect: Rect, init: I, fold: F, reduce: R) -> T
+    where
+        T: Send,
+        I: Fn() -> T + Sync,
+        F: Fn(T, i32, i32, &Cell) -> T + Sync,
+        R: Fn(T, T) -> T,
+    {
+        let r = rect.clip(self.width, self.height);
+        let threads = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(r.height.max(1) as usize);
+        let band = (r.height as usize).div_ceil(threads).max(1) as i32;
+
+        std::thread::scope(|s| {
+            let handles: Vec<_> = (0..threads as i32)
+                .map(|t| {
+                    let (init, fold) = (&init, &fold);
+                    let y0 = r.y + t * band;
+                    let y1 = (y0 + band).min(r.y + r.height);
+                    s.spawn(move || {
+                        let part = Rect::new(r.x, y0, r.width, (y1 - y0).max(0));
+                        self.iter_region(part)
+                            .fold(init(), |acc, (x, y, c)| fold(acc, x, y, c))
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("par_fold_region worker panicked"))
+                .reduce(&reduce)
+                .unwrap_or_else(&init)
+        })
+    }
+}
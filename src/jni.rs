@@ -0,0 +1,253 @@
+// PowderCore - JNI exports (feature "jni")
+//
+// Native methods for bindings/kotlin/PowderCore.kt (class
+// com.powdercore.PowderCore), so JVM and Android frontends can load the
+// library with System.loadLibrary("powdercore") and need no C glue.
+//
+// Worlds are passed around as jlong handles (the same pointers the C API
+// uses) and every call goes through the C functions in ffi.rs, so status
+// codes, panic catching and threadsafe handles behave identically.
+//
+// The crate has no dependencies, so instead of the `jni` crate we call the
+// few JNIEnv functions we need straight from its function table. The slot
+// numbers are fixed by the JNI spec.
+
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+use crate::ffi::*;
+use crate::{Cell, Palette};
+
+/// A JNIEnv is a pointer to the JNI function table.
+type JNIEnv = *const *const c_void;
+type JClass = *mut c_void;
+type JObject = *mut c_void;
+type JString = *mut c_void;
+type JInt = i32;
+type JLong = i64;
+
+// JNINativeInterface slots
+const NEW_STRING_UTF: usize = 167;
+const NEW_DIRECT_BYTE_BUFFER: usize = 229;
+const GET_DIRECT_BUFFER_ADDRESS: usize = 230;
+const GET_DIRECT_BUFFER_CAPACITY: usize = 231;
+
+unsafe fn env_fn(env: *mut JNIEnv, slot: usize) -> *const c_void {
+    *(*env).add(slot)
+}
+
+unsafe fn new_string_utf(env: *mut JNIEnv, s: *const c_char) -> JString {
+    let f: extern "system" fn(*mut JNIEnv, *const c_char) -> JString =
+        std::mem::transmute(env_fn(env, NEW_STRING_UTF));
+    f(env, s)
+}
+
+unsafe fn new_direct_byte_buffer(env: *mut JNIEnv, addr: *mut c_void, len: i64) -> JObject {
+    let f: extern "system" fn(*mut JNIEnv, *mut c_void, i64) -> JObject =
+        std::mem::transmute(env_fn(env, NEW_DIRECT_BYTE_BUFFER));
+    f(env, addr, len)
+}
+
+/// Address and capacity of a direct ByteBuffer (null/0 if it isn't one).
+unsafe fn direct_buffer(env: *mut JNIEnv, buf: JObject) -> (*mut u8, usize) {
+    if buf.is_null() {
+        return (ptr::null_mut(), 0);
+    }
+    let addr: extern "system" fn(*mut JNIEnv, JObject) -> *mut c_void =
+        std::mem::transmute(env_fn(env, GET_DIRECT_BUFFER_ADDRESS));
+    let cap: extern "system" fn(*mut JNIEnv, JObject) -> i64 =
+        std::mem::transmute(env_fn(env, GET_DIRECT_BUFFER_CAPACITY));
+    let p = addr(env, buf) as *mut u8;
+    let n = cap(env, buf);
+    if p.is_null() || n < 0 {
+        return (ptr::null_mut(), 0);
+    }
+    (p, n as usize)
+}
+
+fn handle(h: JLong) -> PowderWorldHandle {
+    h as usize as PowderWorldHandle
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldNew(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    width: JInt,
+    height: JInt,
+    seed: JLong,
+) -> JLong {
+    powder_world_new(width, height, seed as u64) as usize as JLong
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldNewThreadsafe(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    width: JInt,
+    height: JInt,
+    seed: JLong,
+) -> JLong {
+    powder_world_new_threadsafe(width, height, seed as u64) as usize as JLong
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldFree(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+) {
+    powder_world_free(handle(h));
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldStep(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+) -> JInt {
+    powder_world_step(handle(h)) as JInt
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldClear(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+) -> JInt {
+    powder_world_clear(handle(h)) as JInt
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldResize(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+    width: JInt,
+    height: JInt,
+) -> JInt {
+    powder_world_resize(handle(h), width, height) as JInt
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldWidth(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+) -> JInt {
+    let (mut w, mut hh) = (0, 0);
+    powder_world_get_size(handle(h), &mut w, &mut hh);
+    w
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldHeight(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+) -> JInt {
+    let (mut w, mut hh) = (0, 0);
+    powder_world_get_size(handle(h), &mut w, &mut hh);
+    hh
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldPlaceBrush(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+    cx: JInt,
+    cy: JInt,
+    rad: JInt,
+    elem: JInt,
+) -> JInt {
+    powder_world_place_brush(handle(h), cx, cy, rad, elem) as JInt
+}
+
+/// Element id in the low 32 bits, life in the high 32 bits (0 if the
+/// position is out of bounds).
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldGetCell(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+    x: JInt,
+    y: JInt,
+) -> JLong {
+    let mut c = Cell::default();
+    powder_world_get_cell(handle(h), x, y, &mut c);
+    (c.life as i64) << 32 | (c.elem as i32 as u32 as i64)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldSetCell(
+    _env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+    x: JInt,
+    y: JInt,
+    elem: JInt,
+    life: JInt,
+) -> JInt {
+    powder_world_set_cell(handle(h), x, y, FfiCell { elem, life }) as JInt
+}
+
+/// Copy the cells (8 bytes each: elem, life as native-endian ints) into a
+/// direct ByteBuffer. Returns the number of cells written.
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldExportCells(
+    env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+    buf: JObject,
+) -> JInt {
+    let (p, cap) = unsafe { direct_buffer(env, buf) };
+    let max = cap / std::mem::size_of::<Cell>();
+    powder_world_export_cells(handle(h), p as *mut Cell, max) as JInt
+}
+
+/// Direct ByteBuffer over the engine's own cell buffer (no copy). Only valid
+/// until the next call that changes the world; not covered by the lock of
+/// threadsafe handles.
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldCellsBuffer(
+    env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+) -> JObject {
+    let mut n = 0usize;
+    let p = powder_world_cells_ptr(handle(h), &mut n);
+    if p.is_null() {
+        return ptr::null_mut();
+    }
+    let bytes = (n * std::mem::size_of::<Cell>()) as i64;
+    unsafe { new_direct_byte_buffer(env, p as *mut c_void, bytes) }
+}
+
+/// Render RGBA with the default palette into a direct ByteBuffer of at
+/// least width*height*4 bytes. Returns the image size in bytes.
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_worldRenderRgba(
+    env: *mut JNIEnv,
+    _cls: JClass,
+    h: JLong,
+    buf: JObject,
+) -> JInt {
+    let (p, cap) = unsafe { direct_buffer(env, buf) };
+    let palette: *const Palette = ptr::null();
+    powder_world_render_rgba(handle(h), p, cap, palette) as JInt
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_powdercore_PowderCore_lastErrorMessage(
+    env: *mut JNIEnv,
+    _cls: JClass,
+) -> JString {
+    let msg = powder_last_error_message();
+    if msg.is_null() {
+        return ptr::null_mut();
+    }
+    // Messages are UTF-8; JNI wants modified UTF-8, which only differs for
+    // NUL and characters outside the BMP (neither shows up in practice).
+    unsafe { new_string_utf(env, msg) }
+}
@@ -0,0 +1,150 @@
+// PowderCore - layered worlds
+//
+// Two worlds of the same size stacked on top of each other: the visible
+// foreground, and a pipes layer behind it for hidden plumbing. The pipes
+// layer only accepts liquids and the inert solids that channel them (wall,
+// stone, glass, metal). Each layer steps on its own with its own RNG; they
+// exchange matter only at links placed by the level author:
+//
+//   - an Intake swallows liquid from the foreground cell into the empty
+//     pipe cell behind it,
+//   - an Outlet releases pipe liquid into the foreground cell in front of
+//     it when that cell is empty.
+//
+// Links are applied after both layers have stepped, in the order added.
+// There is no pressure: liquid in the pipes flows downhill like anywhere
+// else, so outlets belong below their intakes.
+
+use crate::{is_liquid, Element, Palette, Rect, World};
+
+/// Which way matter moves through a pipe link.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PipeFlow {
+    /// Foreground into the pipes.
+    Intake,
+    /// Pipes out into the foreground.
+    Outlet,
+}
+
+/// Connection between the two layers at (x, y).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PipeLink {
+    pub x: i32,
+    pub y: i32,
+    pub flow: PipeFlow,
+}
+
+/// A foreground world with a liquid-only pipes layer behind it.
+pub struct LayeredWorld {
+    foreground: World,
+    pipes: World,
+    links: Vec<PipeLink>,
+}
+
+/// What the pipes layer may hold.
+fn pipe_material(e: Element) -> bool {
+    is_liquid(e)
+        || matches!(
+            e,
+            Element::Empty | Element::Wall | Element::Stone | Element::Glass | Element::Metal
+        )
+}
+
+impl LayeredWorld {
+    /// Two empty layers of the given size. The pipes layer's RNG is seeded
+    /// from `seed` too, but differently, so the layers don't move in step.
+    pub fn new(width: i32, height: i32, seed: u64) -> Self {
+        LayeredWorld {
+            foreground: World::new(width, height, seed),
+            pipes: World::new(width, height, seed.wrapping_add(1)),
+            links: Vec::new(),
+        }
+    }
+
+    pub fn foreground(&self) -> &World {
+        &self.foreground
+    }
+
+    pub fn foreground_mut(&mut self) -> &mut World {
+        &mut self.foreground
+    }
+
+    /// The pipes layer. Edit it through set_pipe/fill_pipe_rect, which keep
+    /// it liquid-only.
+    pub fn pipes(&self) -> &World {
+        &self.pipes
+    }
+
+    /// Place `elem` in the pipes layer. Returns false if (x, y) is out of
+    /// bounds or `elem` doesn't belong in pipes.
+    pub fn set_pipe(&mut self, x: i32, y: i32, elem: Element) -> bool {
+        pipe_material(elem) && self.pipes.spawn(x, y, elem)
+    }
+
+    /// Fill `rect` of the pipes layer with `elem` (e.g. Wall for the pipe
+    /// walls, Empty for the bore). Returns false if `elem` doesn't belong
+    /// in pipes.
+    pub fn fill_pipe_rect(&mut self, rect: Rect, elem: Element) -> bool {
+        if !pipe_material(elem) {
+            return false;
+        }
+        self.pipes.fill_rect(rect, elem);
+        true
+    }
+
+    /// Connect the layers at (x, y). Returns false if it's out of bounds.
+    pub fn add_link(&mut self, x: i32, y: i32, flow: PipeFlow) -> bool {
+        if !self.foreground.in_bounds(x, y) {
+            return false;
+        }
+        self.links.push(PipeLink { x, y, flow });
+        true
+    }
+
+    /// Remove every link at (x, y).
+    pub fn remove_links_at(&mut self, x: i32, y: i32) {
+        self.links.retain(|l| (l.x, l.y) != (x, y));
+    }
+
+    pub fn links(&self) -> &[PipeLink] {
+        &self.links
+    }
+
+    /// Step both layers, then move liquid through the links.
+    pub fn step(&mut self) {
+        self.foreground.step();
+        self.pipes.step();
+        for &PipeLink { x, y, flow } in &self.links {
+            if !self.foreground.in_bounds(x, y) || !self.pipes.in_bounds(x, y) {
+                continue;
+            }
+            let (from, to) = match flow {
+                PipeFlow::Intake => (&mut self.foreground, &mut self.pipes),
+                PipeFlow::Outlet => (&mut self.pipes, &mut self.foreground),
+            };
+            let (fi, ti) = (from.idx(x, y), to.idx(x, y));
+            let c = from.cells[fi];
+            if is_liquid(c.elem) && to.cells[ti].elem == Element::Empty {
+                to.put(ti, c.elem, c.life);
+                from.put(fi, Element::Empty, 0);
+            }
+        }
+    }
+
+    /// Render both layers into one RGBA image: the foreground, with the
+    /// pipes layer showing through wherever the foreground is empty.
+    pub fn render_rgba(&self, palette: &Palette) -> Vec<u8> {
+        let mut out = self.pipes.render_rgba(palette);
+        let front = self.foreground.render_rgba(palette);
+        for ((px, fpx), c) in out
+            .chunks_exact_mut(4)
+            .zip(front.chunks_exact(4))
+            .zip(self.foreground.cells())
+        {
+            if c.elem != Element::Empty {
+                px.copy_from_slice(fpx);
+            }
+        }
+        out
+    }
+}
@@ -11,19 +11,127 @@
 // You are expected to call World::step() from your own loop
 // and render cells however you like (ncurses, ANSI, GUI, etc).
 //
-// At the bottom of this file there is a small C ABI layer
-// (extern "C" + no_mangle) so the engine can be used from
-// any language that can call C functions.
-
-// ===== Imports for FFI / low-level ops =====
-
-use std::os::raw::c_void;
-use std::ptr;
+// The small C ABI layer (extern "C" + no_mangle) lives in ffi.rs
+// so the engine can be used from any language that can call
+// C functions.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+// ===== Subsystems (one file each) =====
+
+mod actors;
+mod animal;
+mod ascii;
+mod autosave;
+#[cfg(feature = "bevy")]
+mod bevy;
+mod brush;
+mod builder;
+mod bzip2;
+mod census;
+mod chunked;
+mod command;
+mod config;
+mod delta;
+mod depth;
+mod draw;
+mod edges;
+mod events;
+mod explosion;
+mod ffi;
+mod firefight;
+mod frozen;
+mod gravity;
+mod horde;
+mod iter;
+#[cfg(feature = "jni")]
+mod jni;
+mod layers;
+mod measure;
+mod mixing;
+mod netplay;
+mod overlay;
+mod remains;
+pub mod render;
+mod pathfind;
+mod player;
+#[cfg(feature = "image")]
+mod png;
+mod ports;
+mod provenance;
+#[cfg(feature = "python")]
+mod python;
+mod query;
+mod replay;
+mod sample;
+mod save;
+mod schedule;
+mod shelter;
+mod senses;
+mod snapshot;
+mod spawner;
+mod stamp;
+mod stats;
+mod structure;
+mod swim;
+mod terrain;
+mod text;
+mod timestep;
+mod tpt;
+mod unicode;
+mod validate;
+mod viewport;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod watch;
+mod weather;
+pub mod worldgen;
+
+pub use actors::{Actor, ActorStats};
+pub use ascii::{element_from_glyph, AsciiError};
+#[cfg(feature = "bevy")]
+pub use bevy::{PowderPlugin, PowderSystems, PowderTexture, PowderWorld};
+pub use brush::{Brush, BrushShape};
+pub use builder::WorldBuilder;
+pub use chunked::{ChunkedWorld, CHUNK_SIZE};
+pub use command::{Command, UNDO_LIMIT};
+pub use config::SimConfig;
+pub use delta::{DeltaRun, WorldDelta};
+pub use edges::EdgeMode;
+pub use events::{WorldEvent, MAX_EVENTS};
+pub use explosion::ExplosionSpec;
+pub use ffi::*;
+pub use layers::{LayeredWorld, PipeFlow, PipeLink};
+pub use measure::{mass_of, Measurements};
+pub use mixing::{MixRule, MIX_RULES};
+pub use netplay::{Desync, LockstepSession, NetCommand, NetFrame, HASH_HISTORY};
+pub use player::PlayerInput;
+pub use ports::{Edge, Port, PortKind};
+pub use provenance::{ChangeCause, Provenance};
+pub use render::{color256_of, overlay_rgba, world_to_rgba, Overlay, Palette, PALETTE_LEN};
+pub use replay::{Replay, ReplayInput};
+pub use sample::CellInfo;
+pub use save::LoadError;
+pub use schedule::Action;
+pub use snapshot::WorldSnapshot;
+pub use stamp::{MirrorAxis, PasteMode, Stamp};
+pub use stats::StepStats;
+pub use terrain::TerrainLayer;
+pub use text::Font;
+pub use timestep::FixedTimestep;
+pub use unicode::glyph_of_unicode;
+pub use validate::Violation;
+pub use viewport::Viewport;
+pub use watch::CellChange;
+pub use weather::Weather;
+pub use worldgen::WorldGen;
 
 // ===== Elements =====
 
 #[repr(i32)] // stable underlying representation for FFI
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+// NOTE: ids are part of the C ABI and the save format, only ever append.
 pub enum Element {
     Empty,
     // powders
@@ -64,10 +172,116 @@ pub enum Element {
     Lightning,
     Human,
     Zombie,
+    HumanSpawner,
+    ZombieSpawner,
+    Animal,
+    Player,
+    Corpse,
+    Bone,
+    Rubble,
+    DilutedEthanol,
+    WeakAcid,
+}
+
+impl Element {
+    /// Number of element variants.
+    pub const COUNT: usize = 43;
+
+    /// Every element in id order, for building menus.
+    pub const ALL: [Element; Element::COUNT] = ELEMENT_TABLE;
+
+    /// Look up an element by its numeric id (the `repr(i32)` discriminant).
+    pub(crate) fn from_id(id: i32) -> Option<Element> {
+        if id < 0 {
+            return None;
+        }
+        ELEMENT_TABLE.get(id as usize).copied()
+    }
+
+    /// Look up an element by name, ignoring case, spaces, '_' and '-', so
+    /// "Salt Water", "salt_water" and "SALTWATER" all work.
+    pub fn from_name(name: &str) -> Option<Element> {
+        let key = |s: &str| -> String {
+            s.chars()
+                .filter(|c| !matches!(c, ' ' | '_' | '-'))
+                .map(|c| c.to_ascii_lowercase())
+                .collect()
+        };
+        let wanted = key(name);
+        ELEMENT_TABLE.iter().copied().find(|&e| key(name_of(e)) == wanted)
+    }
+}
+
+/// Error for an integer that isn't a valid element id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InvalidElement(pub i32);
+
+impl std::fmt::Display for InvalidElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid element id {}", self.0)
+    }
 }
 
+impl std::error::Error for InvalidElement {}
+
+impl TryFrom<i32> for Element {
+    type Error = InvalidElement;
+
+    fn try_from(id: i32) -> Result<Self, Self::Error> {
+        Element::from_id(id).ok_or(InvalidElement(id))
+    }
+}
+
+// Every element in discriminant order. New elements must be appended at the
+// END of the enum (and here) so ids stay stable for FFI callers and saves.
+const ELEMENT_TABLE: [Element; Element::COUNT] = [
+    Element::Empty,
+    Element::Sand,
+    Element::Gunpowder,
+    Element::Ash,
+    Element::Snow,
+    Element::Water,
+    Element::SaltWater,
+    Element::Oil,
+    Element::Ethanol,
+    Element::Acid,
+    Element::Lava,
+    Element::Mercury,
+    Element::Stone,
+    Element::Glass,
+    Element::Wall,
+    Element::Wood,
+    Element::Plant,
+    Element::Metal,
+    Element::Wire,
+    Element::Ice,
+    Element::Coal,
+    Element::Dirt,
+    Element::WetDirt,
+    Element::Seaweed,
+    Element::Smoke,
+    Element::Steam,
+    Element::Gas,
+    Element::ToxicGas,
+    Element::Hydrogen,
+    Element::Chlorine,
+    Element::Fire,
+    Element::Lightning,
+    Element::Human,
+    Element::Zombie,
+    Element::HumanSpawner,
+    Element::ZombieSpawner,
+    Element::Animal,
+    Element::Player,
+    Element::Corpse,
+    Element::Bone,
+    Element::Rubble,
+    Element::DilutedEthanol,
+    Element::WeakAcid,
+];
+
 #[repr(C)] // FFI-safe layout
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Cell {
     pub elem: Element,
     pub life: i32, // age / gas lifetime / charge / wetness / anim tick
@@ -82,9 +296,60 @@ impl Default for Cell {
     }
 }
 
+/// Axis-aligned rectangle of cells (x, y = top-left corner).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Whether (x, y) lies inside the rectangle.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// Part of the rectangle inside a `width` x `height` grid (may be empty).
+    pub(crate) fn clip(&self, width: i32, height: i32) -> Rect {
+        let x0 = self.x.max(0);
+        let y0 = self.y.max(0);
+        let x1 = self.x.saturating_add(self.width).min(width);
+        let y1 = self.y.saturating_add(self.height).min(height);
+        Rect::new(x0, y0, (x1 - x0).max(0), (y1 - y0).max(0))
+    }
+}
+
 // ===== Very simple PRNG (no external crate) =====
 //
 // We use a tiny LCG so the engine is self-contained and deterministic.
+//
+// Determinism guarantee: the same seed, size and sequence of edits give
+// bit-identical worlds on every platform. The LCG is plain wrapping u64
+// arithmetic, simulation code uses no floats and no hashed iteration order,
+// and step() is single threaded (the "parallel" helpers only read). The
+// whole generator is its 64-bit state, so saving that state (rng_state, or
+// any save or snapshot) and restoring it later resumes the exact same
+// sequence. tests/determinism.rs pins golden values; if they change, saves
+// and replays recorded by earlier versions diverge.
+//
+// Normally step() draws from this one generator in visiting order, so what
+// a cell does depends on every draw made before it. With
+// SimConfig::rng_streams each tick instead draws a single key, and every
+// cell update runs on its own stream seeded from splitmix64(key, x, y):
+// the randomness a cell sees no longer depends on which cells were updated
+// first, which is what stepping regions on separate threads needs. It
+// gives different (equally deterministic) results, hence opt-in.
 
 #[derive(Clone)]
 struct Rng {
@@ -115,6 +380,18 @@ impl Rng {
         min + v as i32
     }
 
+    fn next_u64(&mut self) -> u64 {
+        (self.next_u32() as u64) << 32 | self.next_u32() as u64
+    }
+
+    /// Stream for updating cell (x, y) in the tick keyed `key`.
+    fn for_cell(key: u64, x: i32, y: i32) -> Self {
+        let pos = (x as u32 as u64) << 32 | y as u32 as u64;
+        Rng {
+            state: splitmix64(key ^ splitmix64(pos)),
+        }
+    }
+
     fn chance(&mut self, pct: u32) -> bool {
         if pct == 0 {
             return false;
@@ -126,6 +403,13 @@ impl Rng {
     }
 }
 
+fn splitmix64(v: u64) -> u64 {
+    let mut z = v.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
 // ===== World: core engine state =====
 
 pub struct World {
@@ -133,6 +417,30 @@ pub struct World {
     height: i32,
     cells: Vec<Cell>,
     rng: Rng,
+    // what the RNG was seeded with (see rng_seed)
+    seed: u64,
+    // chunks of the last snapshot taken, shared with new ones when unchanged
+    snapshot_cache: Vec<Arc<[Cell]>>,
+    autosnap: Option<autosave::AutoSnapshots>,
+    events: VecDeque<WorldEvent>,
+    census: census::Census,
+    actors: actors::ActorTable,
+    config: SimConfig,
+    edge_mode: EdgeMode,
+    weather: Weather,
+    tick: u64,
+    schedule: BTreeMap<u64, Vec<(Action, u64)>>,
+    frozen: Vec<bool>,
+    gravity: Vec<(i8, i8)>,
+    ports: Vec<Port>,
+    // counters for the step in progress, and for the last finished one
+    stats: StepStats,
+    last_stats: StepStats,
+    overlays: overlay::Overlays,
+    depth: depth::DepthMap,
+    provenance: provenance::ProvenanceLog,
+    watchers: watch::Watchers,
+    undo: command::UndoStack,
 }
 
 impl World {
@@ -147,15 +455,46 @@ impl World {
             height: h,
             cells: vec![Cell::default(); size],
             rng: Rng::new(seed),
+            seed,
+            snapshot_cache: Vec::new(),
+            autosnap: None,
+            events: VecDeque::new(),
+            census: census::Census::new(size),
+            actors: actors::ActorTable::default(),
+            config: SimConfig::DEFAULT,
+            edge_mode: EdgeMode::Solid,
+            weather: Weather::Clear,
+            tick: 0,
+            schedule: BTreeMap::new(),
+            frozen: Vec::new(),
+            gravity: Vec::new(),
+            ports: Vec::new(),
+            stats: StepStats::default(),
+            last_stats: StepStats::default(),
+            overlays: overlay::Overlays::default(),
+            depth: depth::DepthMap::default(),
+            provenance: provenance::ProvenanceLog::default(),
+            watchers: watch::Watchers::default(),
+            undo: command::UndoStack::default(),
         }
     }
 
     /// Resize the world, clearing all contents.
     pub fn resize(&mut self, width: i32, height: i32) {
-        self.width = width.max(0);
-        self.height = height.max(0);
-        let size = (self.width * self.height).max(0) as usize;
-        self.cells = vec![Cell::default(); size];
+        let (width, height) = (width.max(0), height.max(0));
+        let size = (width * height).max(0) as usize;
+        self.replace_grid(width, height, vec![Cell::default(); size]);
+        self.reset_tracking();
+    }
+
+    /// Swap in a whole new grid. The frozen and gravity masks belong to the
+    /// old one, so they go too; callers follow up with reset_tracking().
+    pub(crate) fn replace_grid(&mut self, width: i32, height: i32, cells: Vec<Cell>) {
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+        self.frozen.clear();
+        self.gravity.clear();
     }
 
     /// World width.
@@ -168,6 +507,29 @@ impl World {
         self.height
     }
 
+    /// The seed this world's RNG was created with (kept by saves).
+    pub fn rng_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The RNG's current state. Together with the cells it determines every
+    /// future step; see set_rng_state.
+    pub fn rng_state(&self) -> u64 {
+        self.rng.state
+    }
+
+    /// Put the RNG in `state` (a value from rng_state), so the world
+    /// continues exactly as the one it came from. rng_seed is unchanged.
+    pub fn set_rng_state(&mut self, state: u64) {
+        self.rng.state = state;
+    }
+
+    /// Number of step() calls completed since the world was created. Saved
+    /// with the world and rewound by snapshot restores.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
     /// Get an immutable view of a cell (returns Empty for out-of-bounds).
     pub fn get_cell(&self, x: i32, y: i32) -> Cell {
         if !self.in_bounds(x, y) {
@@ -176,50 +538,80 @@ impl World {
         self.cells[self.idx(x, y)]
     }
 
+    /// All cells, row-major (`y * width + x`).
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    /// Row `y` as a slice of `width` cells; empty if `y` is out of range.
+    pub fn row(&self, y: i32) -> &[Cell] {
+        if y < 0 || y >= self.height {
+            return &[];
+        }
+        // not idx(): it wraps x modulo the width, which is zero here when
+        // a wrapped world has no columns
+        let start = y as usize * self.width as usize;
+        &self.cells[start..start + self.width as usize]
+    }
+
     /// Get a mutable reference to a cell. Returns None for out-of-bounds.
     pub fn get_cell_mut(&mut self, x: i32, y: i32) -> Option<&mut Cell> {
         if !self.in_bounds(x, y) {
             return None;
         }
         let i = self.idx(x, y);
+        self.cells_changed();
         Some(&mut self.cells[i])
     }
 
+    /// Place a single fresh `elem` cell at (x, y), with the same starting
+    /// life `place_brush` gives it (so Fire and gases don't vanish on the
+    /// next tick). Returns false if (x, y) is out of bounds.
+    pub fn spawn(&mut self, x: i32, y: i32, elem: Element) -> bool {
+        if !self.in_bounds(x, y) {
+            return false;
+        }
+        let i = self.idx(x, y);
+        self.put(i, elem, default_life(elem));
+        true
+    }
+
     /// Clear the world to Empty.
     pub fn clear(&mut self) {
         for c in &mut self.cells {
             *c = Cell::default();
         }
+        self.reset_tracking();
+    }
+
+    /// Stable 64-bit hash (FNV-1a) of the size, every cell and the RNG state.
+    /// Identical on every platform, so replays and network peers can compare
+    /// hashes to detect divergence.
+    pub fn state_hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+
+        let mut h = FNV_OFFSET;
+        let mut feed = |bytes: &[u8]| {
+            for &b in bytes {
+                h ^= b as u64;
+                h = h.wrapping_mul(FNV_PRIME);
+            }
+        };
+        feed(&self.width.to_le_bytes());
+        feed(&self.height.to_le_bytes());
+        for c in &self.cells {
+            feed(&(c.elem as i32).to_le_bytes());
+            feed(&c.life.to_le_bytes());
+        }
+        feed(&self.rng.state.to_le_bytes());
+        h
     }
 
     /// Place a circular brush of element `elem` at (cx, cy) with radius `rad`.
     /// Lightning is treated specially (vertical bolt).
     pub fn place_brush(&mut self, cx: i32, cy: i32, rad: i32, elem: Element) {
-        if elem == Element::Lightning {
-            self.place_lightning(cx, cy);
-            return;
-        }
-
-        let r2 = rad * rad;
-        for dy in -rad..=rad {
-            for dx in -rad..=rad {
-                if dx * dx + dy * dy > r2 {
-                    continue;
-                }
-                let x = cx + dx;
-                let y = cy + dy;
-                if !self.in_bounds(x, y) {
-                    continue;
-                }
-                let idx = self.idx(x, y);
-                self.cells[idx].elem = elem;
-                self.cells[idx].life = match elem {
-                    Element::Fire => 20,
-                    e if is_gas(e) => 25,
-                    _ => 0,
-                };
-            }
-        }
+        self.place_brush_shape(cx, cy, rad, elem, BrushShape::Circle);
     }
 
     /// Single simulation tick: updates all cells in-place.
@@ -230,9 +622,23 @@ impl World {
             return;
         }
 
+        let started = self.begin_stats();
+        self.provenance.cause = ChangeCause::Scheduled;
+        self.run_schedule();
+        self.refresh_census();
+        self.refresh_actors();
+        self.provenance.cause = ChangeCause::Reaction;
+        self.collapse_unsupported();
         let w = self.width;
         let h = self.height;
-        let mut updated = vec![false; (w * h) as usize];
+        let mut updated = self.initial_updated();
+        let resting = Element::ALL.map(|e| !self.config.updates_on(e, self.tick));
+        // per-cell streams borrow self.rng; the world's own generator only
+        // advances by the key
+        let streams = self.config.rng_streams.then(|| {
+            let key = self.rng.next_u64();
+            (key, self.rng.clone())
+        });
 
         // Bottom-up traversal matches original C++ stepping order
         for y in (0..h).rev() {
@@ -243,10 +649,14 @@ impl World {
                 }
 
                 let elem = self.cells[idx0].elem;
-                if elem == Element::Empty || elem == Element::Wall {
+                if elem == Element::Empty || elem == Element::Wall || resting[elem as usize] {
                     updated[idx0] = true;
                     continue;
                 }
+                self.stats.cells_visited += 1;
+                if let Some((key, _)) = streams {
+                    self.rng = Rng::for_cell(key, x, y);
+                }
 
                 // POWDERS
                 if is_sand_like(elem) {
@@ -290,6 +700,30 @@ impl World {
                     continue;
                 }
 
+                // CORPSES / BONES
+                if elem == Element::Corpse || elem == Element::Bone {
+                    self.step_remains(x, y, &mut updated);
+                    continue;
+                }
+
+                // PLAYERS
+                if elem == Element::Player {
+                    self.step_player(x, y, &mut updated);
+                    continue;
+                }
+
+                // ANIMALS
+                if elem == Element::Animal {
+                    self.step_animal(x, y, &mut updated);
+                    continue;
+                }
+
+                // SPAWNERS
+                if elem == Element::HumanSpawner || elem == Element::ZombieSpawner {
+                    self.step_spawner(x, y, &mut updated);
+                    continue;
+                }
+
                 // WET DIRT
                 if elem == Element::WetDirt {
                     self.step_wet_dirt(x, y, &mut updated);
@@ -330,26 +764,93 @@ impl World {
                 updated[idx0] = true;
             }
         }
+        if let Some((_, rng)) = streams {
+            self.rng = rng;
+        }
+
+        self.provenance.cause = ChangeCause::Weather;
+        self.precipitate();
+        self.provenance.cause = ChangeCause::Boundary;
+        self.run_ports();
+        self.drain_open_edges();
+        self.provenance.cause = ChangeCause::Edit;
+        self.notify_watchers();
+        self.tick += 1;
+        self.refresh_depth();
+        self.autosnapshot_tick();
+        self.end_stats(started);
     }
 
     // ===== Internal helpers =====
 
     fn in_bounds(&self, x: i32, y: i32) -> bool {
+        if self.edge_mode == EdgeMode::Wrap {
+            return self.width > 0 && self.height > 0;
+        }
         x >= 0 && x < self.width && y >= 0 && y < self.height
     }
 
     fn idx(&self, x: i32, y: i32) -> usize {
+        let (x, y) = if self.edge_mode == EdgeMode::Wrap {
+            (x.rem_euclid(self.width), y.rem_euclid(self.height))
+        } else {
+            (x, y)
+        };
         (y as usize) * (self.width as usize) + (x as usize)
     }
 
+    /// Roll for a chance-based reaction: `pct` percent scaled by the
+    /// config's reactivity.
+    fn react(&mut self, pct: u32) -> bool {
+        self.rng.chance(self.config.reaction_chance(pct))
+    }
+
+    /// Overwrite cell `i`, keeping the census and actor table in step.
+    /// Simulation code changes elements only through here (swaps don't need
+    /// it, except actors moving, see move_actor).
+    fn put(&mut self, i: usize, elem: Element, life: i32) {
+        let old = self.cells[i].elem;
+        self.census.retally(old, elem);
+        self.track_actor(i, old, elem);
+        self.cells[i] = Cell { elem, life };
+        if elem != old {
+            self.stats.reactions += 1;
+            self.provenance.record(i, self.tick);
+        }
+        if elem == Element::Fire && is_flammable(old) {
+            let (x, y) = self.pos_of(i);
+            self.emit(WorldEvent::Ignited { x, y, elem: old });
+        }
+    }
+
+    /// Cells were edited outside step()/put(): recount and re-index the
+    /// derived per-cell state before the next tick.
+    pub(crate) fn cells_changed(&mut self) {
+        self.census.invalidate();
+        self.actors.invalidate();
+        if self.provenance.cells().len() != self.cells.len() {
+            self.provenance_reset();
+        }
+        self.depth_changed();
+    }
+
+    /// The grid was just reset to all Empty.
+    pub(crate) fn reset_tracking(&mut self) {
+        self.clear_undo();
+        self.census = census::Census::new(self.cells.len());
+        self.actors = actors::ActorTable::default();
+        self.provenance_reset();
+        self.depth_changed();
+    }
+
     /// Place a vertical lightning bolt that travels downward until it hits
     /// non-air / non-gas or the bottom.
-    fn place_lightning(&mut self, cx: i32, cy: i32) {
+    pub(crate) fn place_lightning(&mut self, cx: i32, cy: i32) {
         if !self.in_bounds(cx, cy) {
             return;
         }
 
-        let mut x = cx;
+        let x = cx;
         let mut y = cy;
 
         while y + 1 < self.height {
@@ -363,8 +864,7 @@ impl World {
 
         for yy in cy..=y {
             let idx = self.idx(x, yy);
-            self.cells[idx].elem = Element::Lightning;
-            self.cells[idx].life = 2;
+            self.put(idx, Element::Lightning, default_life(Element::Lightning));
         }
 
         if y + 1 < self.height {
@@ -376,57 +876,19 @@ impl World {
         }
     }
 
-    fn explode(&mut self, cx: i32, cy: i32, r: i32) {
-        let r2 = r * r;
-        for dy in -r..=r {
-            for dx in -r..=r {
-                if dx * dx + dy * dy > r2 {
-                    continue;
-                }
-                let x = cx + dx;
-                let y = cy + dy;
-                if !self.in_bounds(x, y) {
-                    continue;
-                }
-                let idx = self.idx(x, y);
-                let cell = &mut self.cells[idx];
-                match cell.elem {
-                    Element::Wall
-                    | Element::Stone
-                    | Element::Glass
-                    | Element::Metal
-                    | Element::Wire
-                    | Element::Ice => {}
-                    _ => {
-                        let roll = self.rng.range_i32(1, 100);
-                        if roll <= 50 {
-                            cell.elem = Element::Fire;
-                            cell.life = 15 + self.rng.range_i32(0, 10);
-                        } else if roll <= 80 {
-                            cell.elem = Element::Smoke;
-                            cell.life = 20;
-                        } else {
-                            cell.elem = Element::Gas;
-                            cell.life = 20;
-                        }
-                    }
-                }
-            }
-        }
-    }
-
     // ===== Step categories =====
 
     fn step_powder(&mut self, x: i32, y: i32, updated: &mut [bool]) {
         let idx0 = self.idx(x, y);
         let t = self.cells[idx0].elem;
+        let g = self.gravity(idx0);
         let mut moved = false;
 
-        if self.in_bounds(x, y + 1) {
-            let idx_below = self.idx(x, y + 1);
+        if self.in_bounds(x + g.0, y + g.1) {
+            let idx_below = self.idx(x + g.0, y + g.1);
             let below = self.cells[idx_below].elem;
             if below == Element::Empty || is_liquid(below) {
-                self.cells.swap(idx0, idx_below);
+                self.swap_cells(idx0, idx_below);
                 updated[idx_below] = true;
                 moved = true;
             }
@@ -435,15 +897,16 @@ impl World {
         if !moved {
             let dir = if self.rng.chance(50) { 1 } else { -1 };
             for i in 0..2 {
-                let nx = x + if i == 0 { dir } else { -dir };
-                let ny = y + 1;
+                let (sx, sy) = gravity::slide(g, if i == 0 { dir } else { -dir });
+                let nx = x + sx;
+                let ny = y + sy;
                 if !self.in_bounds(nx, ny) {
                     continue;
                 }
                 let idx_n = self.idx(nx, ny);
                 let e = self.cells[idx_n].elem;
                 if e == Element::Empty || is_liquid(e) {
-                    self.cells.swap(idx0, idx_n);
+                    self.swap_cells(idx0, idx_n);
                     updated[idx_n] = true;
                     moved = true;
                     break;
@@ -475,9 +938,8 @@ impl World {
                 }
             }
             if melt {
-                let c = &mut self.cells[idx0];
-                c.elem = Element::Water;
-                c.life = 0;
+                self.put(idx0, Element::Water, 0);
+                self.emit_converted(x, y, Element::Snow, Element::Water);
             }
         }
 
@@ -510,8 +972,7 @@ impl World {
                         && self.cells[self.idx(x, y - 1)].elem == Element::Water
                     {
                         let idx_above = self.idx(x, y - 1);
-                        self.cells[idx_above].elem = Element::Seaweed;
-                        self.cells[idx_above].life = 0;
+                        self.put(idx_above, Element::Seaweed, 0);
                     }
                     life = 0;
                 }
@@ -525,17 +986,14 @@ impl World {
     fn step_liquid(&mut self, x: i32, y: i32, updated: &mut [bool]) {
         let idx0 = self.idx(x, y);
         let t = self.cells[idx0].elem;
+        let g = self.gravity(idx0);
         let mut moved = false;
 
-        if self.in_bounds(x, y + 1) {
-            let idx_b = self.idx(x, y + 1);
+        if self.in_bounds(x + g.0, y + g.1) {
+            let idx_b = self.idx(x + g.0, y + g.1);
             let b = self.cells[idx_b].elem;
-            if b == Element::Empty || is_gas(b) {
-                self.cells.swap(idx0, idx_b);
-                updated[idx_b] = true;
-                moved = true;
-            } else if is_liquid(b) && density(t) > density(b) {
-                self.cells.swap(idx0, idx_b);
+            if b == Element::Empty || is_gas(b) || (is_liquid(b) && density(t) > density(b)) {
+                self.swap_cells(idx0, idx_b);
                 updated[idx_b] = true;
                 moved = true;
             }
@@ -547,19 +1005,18 @@ impl World {
                 order.swap(0, 1);
             }
             for &dx in &order {
-                let nx = x + dx;
-                if !self.in_bounds(nx, y) {
+                let (ax, ay) = gravity::across(g, dx);
+                let (nx, ny) = (x + ax, y + ay);
+                if !self.in_bounds(nx, ny) {
                     continue;
                 }
-                let idx_n = self.idx(nx, y);
+                let idx_n = self.idx(nx, ny);
                 let e = self.cells[idx_n].elem;
-                if e == Element::Empty || is_gas(e) {
-                    self.cells.swap(idx0, idx_n);
-                    updated[idx_n] = true;
-                    moved = true;
-                    break;
-                } else if is_liquid(e) && density(t) > density(e) && self.rng.chance(50) {
-                    self.cells.swap(idx0, idx_n);
+                if e == Element::Empty
+                    || is_gas(e)
+                    || (is_liquid(e) && density(t) > density(e) && self.rng.chance(50))
+                {
+                    self.swap_cells(idx0, idx_n);
                     updated[idx_n] = true;
                     moved = true;
                     break;
@@ -584,94 +1041,67 @@ impl World {
                 let n_idx = self.idx(nx, ny);
                 let n = self.cells[n_idx];
 
+                if self.cells[idx0].elem == t && self.try_mix(idx0, nx, ny) {
+                    return;
+                }
+
                 if t == Element::Water || t == Element::SaltWater {
                     if n.elem == Element::Fire {
-                        let c = &mut self.cells[n_idx];
-                        c.elem = Element::Smoke;
-                        c.life = 15;
+                        self.put(n_idx, Element::Smoke, 15);
                     } else if n.elem == Element::Lava {
-                        {
-                            let c = &mut self.cells[n_idx];
-                            c.elem = Element::Stone;
-                            c.life = 0;
-                        }
-                        let self_cell = &mut self.cells[idx0];
+                        self.put(n_idx, Element::Stone, 0);
+                        self.emit_converted(nx, ny, Element::Lava, Element::Stone);
                         if self.rng.chance(50) {
-                            self_cell.elem = Element::Steam;
-                            self_cell.life = 20;
+                            self.put(idx0, Element::Steam, 20);
                         } else {
-                            self_cell.elem = Element::Stone;
-                            self_cell.life = 0;
+                            self.put(idx0, Element::Stone, 0);
                         }
                     }
                 }
 
-                if t == Element::Oil || t == Element::Ethanol {
-                    if n.elem == Element::Fire || n.elem == Element::Lava {
-                        let self_cell = &mut self.cells[idx0];
-                        self_cell.elem = Element::Fire;
-                        self_cell.life = 25;
-                    }
+                if (t == Element::Oil || t == Element::Ethanol)
+                    && (n.elem == Element::Fire || n.elem == Element::Lava)
+                    && self.react(100)
+                {
+                    self.put(idx0, Element::Fire, 25);
                 }
 
-                if t == Element::Acid {
-                    if is_dissolvable(n.elem) {
+                if t == Element::Acid || t == Element::WeakAcid {
+                    let bite = if t == Element::Acid { 100 } else { 20 };
+                    if is_dissolvable(n.elem) && self.react(bite) {
+                        self.emit(WorldEvent::Dissolved {
+                            x: nx,
+                            y: ny,
+                            elem: n.elem,
+                        });
                         if self.rng.chance(30) {
-                            let c = &mut self.cells[n_idx];
-                            c.elem = Element::ToxicGas;
-                            c.life = 25;
+                            self.put(n_idx, Element::ToxicGas, default_life(Element::ToxicGas));
                         } else {
-                            let c = &mut self.cells[n_idx];
-                            c.elem = Element::Empty;
-                            c.life = 0;
+                            self.put(n_idx, Element::Empty, 0);
                         }
                         if self.rng.chance(25) {
-                            let c = &mut self.cells[idx0];
-                            c.elem = Element::Empty;
-                            c.life = 0;
-                        }
-                    }
-                    if n.elem == Element::Water && self.rng.chance(30) {
-                        {
-                            let c = &mut self.cells[idx0];
-                            c.elem = Element::SaltWater;
-                            c.life = 0;
-                        }
-                        if self.rng.chance(30) {
-                            let c = &mut self.cells[n_idx];
-                            c.elem = Element::Steam;
-                            c.life = 20;
+                            self.put(idx0, Element::Empty, 0);
                         }
                     }
                 }
 
                 if t == Element::Lava {
                     if is_flammable(n.elem) {
-                        let c = &mut self.cells[n_idx];
-                        c.elem = Element::Fire;
-                        c.life = 25;
+                        self.put(n_idx, Element::Fire, 25);
                     } else if n.elem == Element::Sand || n.elem == Element::Snow {
-                        let c = &mut self.cells[n_idx];
-                        c.elem = Element::Glass;
-                        c.life = 0;
+                        self.put(n_idx, Element::Glass, 0);
+                        self.emit_converted(nx, ny, n.elem, Element::Glass);
                     } else if n.elem == Element::Water || n.elem == Element::SaltWater {
-                        {
-                            let c = &mut self.cells[n_idx];
-                            c.elem = Element::Stone;
-                            c.life = 0;
-                        }
-                        let self_cell = &mut self.cells[idx0];
+                        self.put(n_idx, Element::Stone, 0);
+                        self.emit_converted(nx, ny, n.elem, Element::Stone);
                         if self.rng.chance(50) {
-                            self_cell.elem = Element::Steam;
-                            self_cell.life = 20;
+                            self.put(idx0, Element::Steam, 20);
                         } else {
-                            self_cell.elem = Element::Stone;
-                            self_cell.life = 0;
+                            self.put(idx0, Element::Stone, 0);
                         }
                     } else if n.elem == Element::Ice {
-                        let c = &mut self.cells[n_idx];
-                        c.elem = Element::Water;
-                        c.life = 0;
+                        self.put(n_idx, Element::Water, 0);
+                        self.emit_converted(nx, ny, Element::Ice, Element::Water);
                     }
                 }
             }
@@ -680,9 +1110,9 @@ impl World {
         if t == Element::Lava {
             let c = &mut self.cells[idx0];
             c.life += 1;
-            if c.life > 200 {
-                c.elem = Element::Stone;
-                c.life = 0;
+            if c.life > self.config.lava_cool_ticks {
+                self.put(idx0, Element::Stone, 0);
+                self.emit_converted(x, y, Element::Lava, Element::Stone);
             }
         }
 
@@ -695,10 +1125,9 @@ impl World {
                         continue;
                     }
                     let idx_n = self.idx(nx, ny);
-                    let n = &mut self.cells[idx_n];
-                    if n.elem == Element::Dirt || n.elem == Element::WetDirt {
-                        n.elem = Element::WetDirt;
-                        n.life = 300;
+                    let e = self.cells[idx_n].elem;
+                    if e == Element::Dirt || e == Element::WetDirt {
+                        self.put(idx_n, Element::WetDirt, 300);
                     }
                 }
             }
@@ -719,17 +1148,21 @@ impl World {
                     let idx_n = self.idx(nx, ny);
                     let mut n = self.cells[idx_n];
 
-                    if n.elem == Element::Water || n.elem == Element::SaltWater {
-                        if n.life < q - 1 {
-                            n.life = q - 1;
-                        }
+                    if (n.elem == Element::Water || n.elem == Element::SaltWater) && n.life < q - 1 {
+                        n.life = q - 1;
                     }
-                    if n.elem == Element::Human || n.elem == Element::Zombie {
-                        n.elem = Element::Ash;
+                    if actors::is_actor(n.elem) && self.hurt(idx_n, actors::SHOCK_DAMAGE) {
+                        if n.elem == Element::Human {
+                            self.emit(WorldEvent::HumanDied { x: nx, y: ny });
+                        }
+                        n.elem = match n.elem {
+                            Element::Human | Element::Player => Element::Corpse,
+                            _ => Element::Ash,
+                        };
                         n.life = 0;
                     }
 
-                    self.cells[idx_n] = n;
+                    self.put(idx_n, n.elem, n.life);
                 }
             }
             let c = &mut self.cells[idx0];
@@ -745,13 +1178,15 @@ impl World {
         let t = self.cells[idx0].elem;
         let mut moved = false;
 
+        let g = self.gravity(idx0);
+        let (ux, uy) = (x - g.0, y - g.1);
         let tries = if t == Element::Hydrogen { 2 } else { 1 };
         for _ in 0..tries {
-            if self.in_bounds(x, y - 1)
-                && self.cells[self.idx(x, y - 1)].elem == Element::Empty
+            if self.in_bounds(ux, uy)
+                && self.cells[self.idx(ux, uy)].elem == Element::Empty
             {
-                let idx_up = self.idx(x, y - 1);
-                self.cells.swap(idx0, idx_up);
+                let idx_up = self.idx(ux, uy);
+                self.swap_cells(idx0, idx_up);
                 updated[idx_up] = true;
                 moved = true;
                 break;
@@ -764,13 +1199,15 @@ impl World {
                 order.swap(0, 1);
             }
             for &dx in &order {
-                let nx = x + dx;
-                let ny = y - if self.rng.chance(50) { 1 } else { 0 };
+                let (ax, ay) = gravity::across(g, dx);
+                let rise = if self.rng.chance(50) { 1 } else { 0 };
+                let nx = x + ax - g.0 * rise;
+                let ny = y + ay - g.1 * rise;
                 if self.in_bounds(nx, ny)
                     && self.cells[self.idx(nx, ny)].elem == Element::Empty
                 {
                     let idx_n = self.idx(nx, ny);
-                    self.cells.swap(idx0, idx_n);
+                    self.swap_cells(idx0, idx_n);
                     updated[idx_n] = true;
                     moved = true;
                     break;
@@ -790,13 +1227,11 @@ impl World {
                         continue;
                     }
                     let e = self.cells[self.idx(nx, ny)].elem;
-                    if e == Element::Fire || e == Element::Lava {
+                    if (e == Element::Fire || e == Element::Lava) && self.react(100) {
                         if t == Element::Hydrogen {
-                            self.explode(x, y, 4);
+                            self.detonate(x, y, self.config.hydrogen_blast);
                         } else {
-                            let c = &mut self.cells[idx0];
-                            c.elem = Element::Fire;
-                            c.life = 12;
+                            self.put(idx0, Element::Fire, 12);
                         }
                     }
                 }
@@ -812,10 +1247,8 @@ impl World {
                         continue;
                     }
                     let idx_n = self.idx(nx, ny);
-                    let n = &mut self.cells[idx_n];
-                    if n.elem == Element::Plant && self.rng.chance(35) {
-                        n.elem = Element::ToxicGas;
-                        n.life = 25;
+                    if self.cells[idx_n].elem == Element::Plant && self.react(35) {
+                        self.put(idx_n, Element::ToxicGas, default_life(Element::ToxicGas));
                     }
                 }
             }
@@ -825,27 +1258,14 @@ impl World {
         c.life -= 1;
         if c.life <= 0 {
             match t {
-                Element::Steam => {
-                    if self.rng.chance(15) {
-                        c.elem = Element::Water;
-                        c.life = 0;
-                    } else {
-                        c.elem = Element::Empty;
-                        c.life = 0;
-                    }
+                Element::Steam if self.rng.chance(15) => {
+                    self.put(idx0, Element::Water, 0);
                 }
-                Element::Smoke => {
-                    if self.rng.chance(8) {
-                        c.elem = Element::Ash;
-                        c.life = 0;
-                    } else {
-                        c.elem = Element::Empty;
-                        c.life = 0;
-                    }
+                Element::Smoke if self.rng.chance(8) => {
+                    self.put(idx0, Element::Ash, 0);
                 }
                 _ => {
-                    c.elem = Element::Empty;
-                    c.life = 0;
+                    self.put(idx0, Element::Empty, 0);
                 }
             }
         } else if !moved {
@@ -860,7 +1280,7 @@ impl World {
             let idx_up = self.idx(x, y - 1);
             let e_up = self.cells[idx_up].elem;
             if (e_up == Element::Empty || is_gas(e_up)) && self.rng.chance(50) {
-                self.cells.swap(idx0, idx_up);
+                self.swap_cells(idx0, idx_up);
                 updated[idx_up] = true;
             }
         }
@@ -878,34 +1298,29 @@ impl World {
                 let idx_n = self.idx(nx, ny);
                 let mut n = self.cells[idx_n];
 
-                if is_flammable(n.elem) && self.rng.chance(40) {
+                if is_flammable(n.elem) && self.react(40) {
                     if n.elem == Element::Gunpowder {
-                        self.explode(nx, ny, 5);
+                        self.detonate(nx, ny, self.config.gunpowder_blast);
                     } else {
                         n.elem = Element::Fire;
                         n.life = 15 + self.rng.range_i32(0, 10);
                     }
                 }
                 if n.elem == Element::Water || n.elem == Element::SaltWater {
-                    let c = &mut self.cells[idx0];
-                    c.elem = Element::Smoke;
-                    c.life = 15;
+                    self.put(idx0, Element::Smoke, 15);
                 }
-                if n.elem == Element::Wire || n.elem == Element::Metal {
-                    if self.rng.chance(5) {
-                        n.life = n.life.max(5);
-                    }
+                if (n.elem == Element::Wire || n.elem == Element::Metal) && self.rng.chance(5) {
+                    n.life = n.life.max(5);
                 }
 
-                self.cells[idx_n] = n;
+                self.put(idx_n, n.elem, n.life);
             }
         }
 
         let c = &mut self.cells[idx0];
         c.life -= 1;
         if c.life <= 0 {
-            c.elem = Element::Smoke;
-            c.life = 15;
+            self.put(idx0, Element::Smoke, 15);
         }
         updated[idx0] = true;
     }
@@ -935,25 +1350,24 @@ impl World {
                 }
                 if is_flammable(e) {
                     if e == Element::Gunpowder {
-                        self.explode(nx, ny, 6);
+                        self.detonate(nx, ny, self.config.gunpowder_blast.widened(1));
                     } else {
                         n.elem = Element::Fire;
                         n.life = 20 + self.rng.range_i32(0, 10);
                     }
                 }
                 if e == Element::Hydrogen || e == Element::Gas {
-                    self.explode(nx, ny, 4);
+                    self.detonate(nx, ny, self.config.hydrogen_blast);
                 }
 
-                self.cells[idx_n] = n;
+                self.put(idx_n, n.elem, n.life);
             }
         }
 
         let c = &mut self.cells[idx0];
         c.life -= 1;
         if c.life <= 0 {
-            c.elem = Element::Empty;
-            c.life = 0;
+            self.put(idx0, Element::Empty, 0);
         }
         updated[idx0] = true;
     }
@@ -961,7 +1375,8 @@ impl World {
     fn step_human(&mut self, x: i32, y: i32, updated: &mut [bool]) {
         let idx0 = self.idx(x, y);
 
-        let mut killed = false;
+        // one hit per tick, however many hazards are touching
+        let (mut hit, mut killed) = (false, false);
         for dy in -1..=1 {
             for dx in -1..=1 {
                 let nx = x + dx;
@@ -974,14 +1389,16 @@ impl World {
                 if is_hazard(n.elem)
                     || ((n.elem == Element::Water || n.elem == Element::SaltWater) && n.life > 0)
                 {
-                    let c = &mut self.cells[idx0];
-                    c.elem = Element::Ash;
-                    c.life = 0;
-                    killed = true;
+                    hit = true;
+                    if self.hurt(idx0, actors::HAZARD_DAMAGE) {
+                        self.put(idx0, Element::Corpse, 0);
+                        killed = true;
+                        self.emit(WorldEvent::HumanDied { x, y });
+                    }
                     break;
                 }
             }
-            if killed {
+            if hit {
                 break;
             }
         }
@@ -995,37 +1412,21 @@ impl World {
             c.life += 1;
         }
 
+        if self.swim_human(x, y, updated) {
+            return;
+        }
+
         if self.in_bounds(x, y + 1) {
             let idx_b = self.idx(x, y + 1);
             let b = self.cells[idx_b].elem;
             if b == Element::Empty || is_gas(b) {
-                self.cells.swap(idx0, idx_b);
+                self.move_actor(idx0, idx_b);
                 updated[idx_b] = true;
                 return;
             }
         }
 
-        let mut zx = 0;
-        let mut zy = 0;
-        let mut seen = false;
-        for ry in -6..=6 {
-            for rx in -6..=6 {
-                let nx = x + rx;
-                let ny = y + ry;
-                if !self.in_bounds(nx, ny) {
-                    continue;
-                }
-                if self.cells[self.idx(nx, ny)].elem == Element::Zombie {
-                    zx = nx;
-                    zy = ny;
-                    seen = true;
-                    break;
-                }
-            }
-            if seen {
-                break;
-            }
-        }
+        let zombie = self.spot(x, y, self.config.human_vision, Element::Zombie);
 
         for dy in -1..=1 {
             for dx in -1..=1 {
@@ -1039,7 +1440,13 @@ impl World {
                 }
                 let idx_n = self.idx(nx, ny);
                 let mut n = self.cells[idx_n];
-                if n.elem == Element::Zombie && self.rng.chance(35) {
+                if n.elem == Element::Zombie
+                    && self.rng.chance(35)
+                    && self.hurt(idx_n, actors::HIT_DAMAGE)
+                {
+                    if let (Some(id), Some(by)) = (self.actor_id(idx_n), self.actor_id(idx0)) {
+                        self.emit(WorldEvent::ZombieKilled { x: nx, y: ny, id, by });
+                    }
                     if self.rng.chance(60) {
                         n.elem = Element::Fire;
                         n.life = 10 + self.rng.range_i32(0, 10);
@@ -1048,15 +1455,34 @@ impl World {
                         n.life = 0;
                     }
                 }
-                self.cells[idx_n] = n;
+                self.put(idx_n, n.elem, n.life);
+            }
+        }
+
+        if self.fight_fire(x, y) {
+            updated[idx0] = true;
+            return;
+        }
+
+        if self.threatened(x, y) {
+            if let Some((tx, ty)) = self.flee_step(x, y) {
+                if self.try_walk(x, y, tx, ty) {
+                    updated[self.idx(tx, ty)] = true;
+                    return;
+                }
             }
         }
 
+        if self.tend_shelter(x, y, zombie) {
+            updated[idx0] = true;
+            return;
+        }
+
         let mut dir = if self.rng.chance(50) { 1 } else { -1 };
-        if seen {
-            let _ = zy; // unused but kept to mirror logic; could be used for fancier AI
+        if let Some((zx, _)) = zombie {
             dir = if zx < x { 1 } else { -1 };
         }
+        dir = self.wary_dir(x, y, dir);
 
         if !self.try_walk(x, y, x + dir, y) {
             if self.in_bounds(x + dir, y - 1)
@@ -1065,7 +1491,7 @@ impl World {
                 && self.rng.chance(70)
             {
                 let idx_up = self.idx(x, y - 1);
-                self.cells.swap(idx0, idx_up);
+                self.move_actor(idx0, idx_up);
             } else {
                 let alt_dir = if self.rng.chance(50) { 1 } else { -1 };
                 self.try_walk(x, y, x + alt_dir, y);
@@ -1079,7 +1505,8 @@ impl World {
         let idx0 = self.idx(x, y);
 
         {
-            let mut killed = false;
+            // as for humans, at most one hit per tick
+            let mut hit = false;
             for dy in -1..=1 {
                 for dx in -1..=1 {
                     let nx = x + dx;
@@ -1093,14 +1520,14 @@ impl World {
                         || ((n.elem == Element::Water || n.elem == Element::SaltWater)
                             && n.life > 0)
                     {
-                        let c = &mut self.cells[idx0];
-                        c.elem = Element::Fire;
-                        c.life = 15;
-                        killed = true;
+                        hit = true;
+                        if self.hurt(idx0, actors::HAZARD_DAMAGE) {
+                            self.put(idx0, Element::Fire, 15);
+                        }
                         break;
                     }
                 }
-                if killed {
+                if hit {
                     break;
                 }
             }
@@ -1115,37 +1542,24 @@ impl World {
             c.life += 1;
         }
 
+        if self.sink_zombie(x, y, updated) {
+            return;
+        }
+
         if self.in_bounds(x, y + 1) {
             let idx_b = self.idx(x, y + 1);
             let b = self.cells[idx_b].elem;
             if b == Element::Empty || is_gas(b) {
-                self.cells.swap(idx0, idx_b);
+                self.move_actor(idx0, idx_b);
                 updated[idx_b] = true;
                 return;
             }
         }
 
-        let mut hx = 0;
-        let mut hy = 0;
-        let mut seen = false;
-        for ry in -6..=6 {
-            for rx in -6..=6 {
-                let nx = x + rx;
-                let ny = y + ry;
-                if !self.in_bounds(nx, ny) {
-                    continue;
-                }
-                if self.cells[self.idx(nx, ny)].elem == Element::Human {
-                    hx = nx;
-                    hy = ny;
-                    seen = true;
-                    break;
-                }
-            }
-            if seen {
-                break;
-            }
-        }
+        let r = self.config.zombie_vision;
+        let human = self
+            .spot(x, y, r, Element::Human)
+            .or_else(|| self.spot(x, y, r, Element::Corpse));
 
         for dy in -1..=1 {
             for dx in -1..=1 {
@@ -1160,35 +1574,39 @@ impl World {
                 let idx_n = self.idx(nx, ny);
                 let mut n = self.cells[idx_n];
                 if n.elem == Element::Human {
-                    if self.rng.chance(70) {
+                    if self.react(70) {
                         n.elem = Element::Zombie;
                         n.life = 0;
+                        self.emit_converted(nx, ny, Element::Human, Element::Zombie);
                     } else {
                         n.elem = Element::Fire;
                         n.life = 10;
                     }
+                    self.emit(WorldEvent::HumanDied { x: nx, y: ny });
                 }
-                self.cells[idx_n] = n;
+                self.put(idx_n, n.elem, n.life);
             }
         }
 
+        let pack = self.horde_dir(x, y, human);
         let mut dir = if self.rng.chance(50) { 1 } else { -1 };
-        if seen {
-            let _ = hy;
+        if let Some((hx, _)) = human {
             dir = if hx > x { 1 } else { -1 };
+        } else if let Some(d) = pack {
+            dir = d;
         }
 
-        if !self.try_walk(x, y, x + dir, y) {
+        if !self.try_wade(x, y, x + dir, y) {
             if self.in_bounds(x + dir, y - 1)
                 && self.cells[self.idx(x + dir, y - 1)].elem == Element::Empty
                 && self.cells[self.idx(x, y - 1)].elem == Element::Empty
                 && self.rng.chance(70)
             {
                 let idx_up = self.idx(x, y - 1);
-                self.cells.swap(idx0, idx_up);
+                self.move_actor(idx0, idx_up);
             } else {
                 let alt_dir = if self.rng.chance(50) { 1 } else { -1 };
-                self.try_walk(x, y, x + alt_dir, y);
+                self.try_wade(x, y, x + alt_dir, y);
             }
         }
 
@@ -1220,8 +1638,7 @@ impl World {
             let c = &mut self.cells[idx0];
             c.life -= 1;
             if c.life <= 0 {
-                c.elem = Element::Dirt;
-                c.life = 0;
+                self.put(idx0, Element::Dirt, 0);
             }
         }
 
@@ -1243,10 +1660,8 @@ impl World {
                     continue;
                 }
                 let e = self.cells[self.idx(nx, ny)].elem;
-                if e == Element::Fire || e == Element::Lava {
-                    let c = &mut self.cells[idx0];
-                    c.elem = Element::Fire;
-                    c.life = 20;
+                if (e == Element::Fire || e == Element::Lava) && self.react(100) {
+                    self.put(idx0, Element::Fire, default_life(Element::Fire));
                 }
             }
         }
@@ -1266,8 +1681,7 @@ impl World {
                     && self.cells[self.idx(gx, gy)].elem == Element::Empty
                 {
                     let idx_g = self.idx(gx, gy);
-                    self.cells[idx_g].elem = Element::Plant;
-                    self.cells[idx_g].life = 0;
+                    self.put(idx_g, Element::Plant, 0);
                 }
             }
         } else {
@@ -1282,8 +1696,7 @@ impl World {
                     let idx_g = self.idx(x, gy);
                     let e = self.cells[idx_g].elem;
                     if e == Element::Water || e == Element::SaltWater {
-                        self.cells[idx_g].elem = Element::Seaweed;
-                        self.cells[idx_g].life = 0;
+                        self.put(idx_g, Element::Seaweed, 0);
                     }
                 }
             }
@@ -1307,10 +1720,8 @@ impl World {
                     continue;
                 }
                 let e = self.cells[self.idx(nx, ny)].elem;
-                if e == Element::Fire || e == Element::Lava {
-                    let c = &mut self.cells[idx0];
-                    c.elem = Element::Fire;
-                    c.life = if t == Element::Coal { 35 } else { 25 };
+                if (e == Element::Fire || e == Element::Lava) && self.react(100) {
+                    self.put(idx0, Element::Fire, if t == Element::Coal { 35 } else { 25 });
                 }
             }
         }
@@ -1332,7 +1743,7 @@ impl World {
                 }
                 let e = self.cells[self.idx(nx, ny)].elem;
                 if e == Element::Fire || e == Element::Lava {
-                    self.explode(x, y, 5);
+                    self.detonate(x, y, self.config.gunpowder_blast);
                     break;
                 }
             }
@@ -1358,31 +1769,25 @@ impl World {
                     let idx_n = self.idx(nx, ny);
                     let mut n = self.cells[idx_n];
 
-                    if n.elem == Element::Wire || n.elem == Element::Metal {
-                        if n.life < q - 1 {
-                            n.life = q - 1;
-                        }
+                    if (n.elem == Element::Wire || n.elem == Element::Metal) && n.life < q - 1 {
+                        n.life = q - 1;
                     }
-                    if n.elem == Element::Water || n.elem == Element::SaltWater {
-                        if n.life < q - 1 {
-                            n.life = q - 1;
-                        }
+                    if (n.elem == Element::Water || n.elem == Element::SaltWater) && n.life < q - 1 {
+                        n.life = q - 1;
                     }
-                    if is_flammable(n.elem) && self.rng.chance(15) {
+                    if is_flammable(n.elem) && self.react(15) {
                         if n.elem == Element::Gunpowder {
-                            self.explode(nx, ny, 5);
+                            self.detonate(nx, ny, self.config.gunpowder_blast);
                         } else {
                             n.elem = Element::Fire;
                             n.life = 15 + self.rng.range_i32(0, 10);
                         }
                     }
-                    if n.elem == Element::Hydrogen || n.elem == Element::Gas {
-                        if self.rng.chance(35) {
-                            self.explode(nx, ny, 4);
-                        }
+                    if (n.elem == Element::Hydrogen || n.elem == Element::Gas) && self.react(35) {
+                        self.detonate(nx, ny, self.config.hydrogen_blast);
                     }
 
-                    self.cells[idx_n] = n;
+                    self.put(idx_n, n.elem, n.life);
                 }
             }
             let c = &mut self.cells[idx0];
@@ -1406,11 +1811,11 @@ impl World {
                     continue;
                 }
                 let e = self.cells[self.idx(nx, ny)].elem;
-                if e == Element::Fire || e == Element::Lava || e == Element::Steam {
-                    if self.rng.chance(25) {
-                        melt = true;
-                        break;
-                    }
+                if (e == Element::Fire || e == Element::Lava || e == Element::Steam)
+                    && self.react(25)
+                {
+                    melt = true;
+                    break;
                 }
             }
             if melt {
@@ -1419,9 +1824,8 @@ impl World {
         }
 
         if melt {
-            let c = &mut self.cells[idx0];
-            c.elem = Element::Water;
-            c.life = 0;
+            self.put(idx0, Element::Water, 0);
+            self.emit_converted(x, y, Element::Ice, Element::Water);
         }
 
         updated[idx0] = true;
@@ -1436,7 +1840,7 @@ impl World {
         let idx_to = self.idx(tx, ty);
         let dst = self.cells[idx_to].elem;
         if dst == Element::Empty || is_gas(dst) {
-            self.cells.swap(idx_from, idx_to);
+            self.move_actor(idx_from, idx_to);
             true
         } else {
             false
@@ -1446,8 +1850,25 @@ impl World {
 
 // ===== Element classification & meta =====
 
+/// Life given to freshly placed cells: fire and gases need a lifetime or
+/// they vanish on the next tick, everything else starts at 0. Brushes,
+/// spawn, loaders and reactions that create a "normal" cell all use this;
+/// reactions that deliberately make short- or long-lived cells (a quick
+/// puff of smoke, a long oil fire) keep their own numbers.
+pub fn default_life(e: Element) -> i32 {
+    match e {
+        Element::Fire => 20,
+        Element::Lightning => 2,
+        e if is_gas(e) => 25,
+        _ => 0,
+    }
+}
+
 fn is_sand_like(e: Element) -> bool {
-    matches!(e, Element::Sand | Element::Gunpowder | Element::Ash | Element::Snow)
+    matches!(
+        e,
+        Element::Sand | Element::Gunpowder | Element::Ash | Element::Snow | Element::Rubble
+    )
 }
 
 fn is_liquid(e: Element) -> bool {
@@ -1460,6 +1881,8 @@ fn is_liquid(e: Element) -> bool {
             | Element::Acid
             | Element::Lava
             | Element::Mercury
+            | Element::DilutedEthanol
+            | Element::WeakAcid
     )
 }
 
@@ -1485,6 +1908,8 @@ fn is_flammable(e: Element) -> bool {
             | Element::Gunpowder
             | Element::Coal
             | Element::Seaweed
+            | Element::Corpse
+            | Element::DilutedEthanol
     )
 }
 
@@ -1503,6 +1928,9 @@ fn is_dissolvable(e: Element) -> bool {
             | Element::Seaweed
             | Element::Dirt
             | Element::WetDirt
+            | Element::Corpse
+            | Element::Bone
+            | Element::Rubble
     )
 }
 
@@ -1511,12 +1939,14 @@ fn density(e: Element) -> i32 {
     match e {
         Element::Ethanol => 85,
         Element::Oil => 90,
+        Element::DilutedEthanol => 95,
         Element::Gas | Element::Hydrogen => 1,
         Element::Steam => 2,
         Element::Smoke => 3,
         Element::Chlorine => 5,
         Element::Water => 100,
         Element::SaltWater => 103,
+        Element::WeakAcid => 105,
         Element::Acid => 110,
         Element::Lava => 160,
         Element::Mercury => 200,
@@ -1530,6 +1960,7 @@ fn is_hazard(e: Element) -> bool {
         Element::Fire
             | Element::Lava
             | Element::Acid
+            | Element::WeakAcid
             | Element::ToxicGas
             | Element::Chlorine
             | Element::Lightning
@@ -1575,36 +2006,100 @@ pub fn name_of(e: Element) -> &'static str {
         Element::Lightning => "Lightning",
         Element::Human => "Human",
         Element::Zombie => "Zombie",
+        Element::HumanSpawner => "Human Spawner",
+        Element::ZombieSpawner => "Zombie Spawner",
+        Element::Animal => "Animal",
+        Element::Player => "Player",
+        Element::Corpse => "Corpse",
+        Element::Bone => "Bone",
+        Element::Rubble => "Rubble",
+        Element::DilutedEthanol => "Diluted Ethanol",
+        Element::WeakAcid => "Weak Acid",
     }
 }
 
 /// Simple numeric "palette index" the frontend can map to colors.
 /// Values mirror the C++ classic ncurses color pairs (1..9).
-pub fn color_of(e: Element, life: i32) -> u8 {
-    if (e == Element::Water || e == Element::SaltWater) && life > 0 {
+pub const fn color_of(e: Element, life: i32) -> u8 {
+    if matches!(e, Element::Water | Element::SaltWater) && life > 0 {
         return 9;
     }
 
     match e {
         Element::Empty => 1,
-        Element::Sand | Element::Gunpowder | Element::Snow | Element::Dirt => 2,
+        Element::Sand | Element::Gunpowder | Element::Snow | Element::Dirt | Element::Animal => 2,
         Element::Water
         | Element::SaltWater
         | Element::Steam
         | Element::Ice
-        | Element::Ethanol => 3,
+        | Element::Ethanol
+        | Element::DilutedEthanol => 3,
         Element::Stone
         | Element::Glass
         | Element::Wall
         | Element::Metal
         | Element::Wire
         | Element::Coal
-        | Element::WetDirt => 4,
-        Element::Wood | Element::Plant | Element::Seaweed | Element::Human => 5,
-        Element::Fire | Element::Lava | Element::Zombie => 6,
-        Element::Smoke | Element::Ash | Element::Gas | Element::Hydrogen => 7,
-        Element::Oil | Element::Mercury => 8,
-        Element::Acid | Element::ToxicGas | Element::Chlorine | Element::Lightning => 9,
+        | Element::WetDirt
+        | Element::Rubble => 4,
+        Element::Wood
+        | Element::Plant
+        | Element::Seaweed
+        | Element::Human
+        | Element::HumanSpawner
+        | Element::Player => 5,
+        Element::Fire | Element::Lava | Element::Zombie | Element::ZombieSpawner => 6,
+        Element::Smoke | Element::Ash | Element::Gas | Element::Hydrogen | Element::Bone => 7,
+        Element::Oil | Element::Mercury | Element::Corpse => 8,
+        Element::Acid
+        | Element::WeakAcid
+        | Element::ToxicGas
+        | Element::Chlorine
+        | Element::Lightning => 9,
+    }
+}
+
+/// Brightness for animating hot cells, 0-255 (255 = color_of's color as
+/// is). Fire flickers and dims as its life runs out, lava pulses slowly and
+/// lightning strobes; everything else is a steady 255. Pass the world's
+/// tick() so every frontend flickers the same way.
+pub fn shade_of(e: Element, life: i32, tick: u64) -> u8 {
+    let jitter = || (flicker(life, tick) >> 60) as i32; // 0..=15
+    let shade = match e {
+        Element::Fire => 160 + life.clamp(0, 20) * 4 + jitter(),
+        Element::Lava => {
+            let phase = (tick.wrapping_add((life as u64).wrapping_mul(7)) % 32) as i32;
+            200 + (phase - 16).abs() * 3 + jitter() / 2
+        }
+        Element::Lightning => {
+            if jitter() < 8 {
+                255
+            } else {
+                190
+            }
+        }
+        _ => 255,
+    };
+    shade.clamp(0, 255) as u8
+}
+
+/// Hash of tick and life for flicker effects. Timers count life down as the
+/// tick goes up, so plain arithmetic on the two would stay constant; this
+/// changes every tick and differs between neighbouring lives.
+fn flicker(life: i32, tick: u64) -> u64 {
+    (tick ^ (life as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_mul(0xBF58_476D_1CE4_E5B9)
+}
+
+/// Light given off by a cell, 0-255: fire (brighter with more life left),
+/// lava and lightning; 0 for everything else. For bloom and light halos in
+/// frontends; see World::render_emission.
+pub fn emission_of(e: Element, life: i32) -> u8 {
+    match e {
+        Element::Fire => (120 + life.clamp(0, 20) * 6) as u8,
+        Element::Lava => 200,
+        Element::Lightning => 255,
+        _ => 0,
     }
 }
 
@@ -1657,167 +2152,61 @@ pub fn glyph_of(e: Element, life: i32) -> char {
                 'T'
             }
         }
+        Element::HumanSpawner => 'H',
+        Element::ZombieSpawner => 'Z',
+        Element::Animal => 'A',
+        Element::Player => 'P',
+        Element::Corpse => 'C',
+        Element::Bone => 'B',
+        Element::Rubble => 'r',
+        Element::DilutedEthanol => 'E',
+        Element::WeakAcid => 'q',
     }
 }
 
-// ===== C ABI LAYER (for any language via FFI) =====
-//
-// Build as cdylib/staticlib and use these from C, C++, Python, Nim, Kotlin, etc.
-// All functions are null-safe and do nothing if passed a null pointer.
-
-/// Opaque handle type when viewed from C/other languages.
-pub type PowderWorldHandle = *mut c_void;
-
-#[no_mangle]
-pub extern "C" fn powder_world_new(width: i32, height: i32, seed: u64) -> PowderWorldHandle {
-    let w = World::new(width, height, seed);
-    let boxed: Box<World> = Box::new(w);
-    Box::into_raw(boxed) as PowderWorldHandle
-}
-
-#[no_mangle]
-pub extern "C" fn powder_world_free(handle: PowderWorldHandle) {
-    if handle.is_null() {
-        return;
-    }
-    unsafe {
-        drop(Box::from_raw(handle as *mut World));
-    }
-}
-
-#[no_mangle]
-pub extern "C" fn powder_world_step(handle: PowderWorldHandle) {
-    if handle.is_null() {
-        return;
-    }
-    let w = unsafe { &mut *(handle as *mut World) };
-    w.step();
-}
-
-#[no_mangle]
-pub extern "C" fn powder_world_clear(handle: PowderWorldHandle) {
-    if handle.is_null() {
-        return;
-    }
-    let w = unsafe { &mut *(handle as *mut World) };
-    w.clear();
-}
-
-#[no_mangle]
-pub extern "C" fn powder_world_get_size(
-    handle: PowderWorldHandle,
-    out_width: *mut i32,
-    out_height: *mut i32,
-) {
-    if handle.is_null() || out_width.is_null() || out_height.is_null() {
-        return;
-    }
-    let w = unsafe { &*(handle as *const World) };
-    unsafe {
-        *out_width = w.width();
-        *out_height = w.height();
-    }
-}
-
-#[no_mangle]
-pub extern "C" fn powder_world_resize(
-    handle: PowderWorldHandle,
-    width: i32,
-    height: i32,
-) {
-    if handle.is_null() {
-        return;
-    }
-    let w = unsafe { &mut *(handle as *mut World) };
-    w.resize(width, height);
-}
-
-#[no_mangle]
-pub extern "C" fn powder_world_place_brush(
-    handle: PowderWorldHandle,
-    cx: i32,
-    cy: i32,
-    rad: i32,
-    elem: Element,
-) {
-    if handle.is_null() {
-        return;
-    }
-    let w = unsafe { &mut *(handle as *mut World) };
-    w.place_brush(cx, cy, rad, elem);
-}
-
-#[no_mangle]
-pub extern "C" fn powder_world_get_cell(
-    handle: PowderWorldHandle,
-    x: i32,
-    y: i32,
-    out_cell: *mut Cell,
-) -> i32 {
-    if handle.is_null() || out_cell.is_null() {
-        return 0;
-    }
-    let w = unsafe { &*(handle as *const World) };
-    if !w.in_bounds(x, y) {
-        return 0;
-    }
-    let c = w.get_cell(x, y);
-    unsafe {
-        *out_cell = c;
+/// glyph_of with more animation, for text frontends that redraw every
+/// step: fire flickers between `*` and `+`, water ripples between `~` and
+/// `_`, and gases shimmer, blanking now and then. Pass the cell's position
+/// and the world's tick(); water's life is nearly always 0, so the position
+/// is what keeps its cells out of step with one another. Everything else,
+/// and humans and zombies, look as in glyph_of.
+pub fn glyph_of_animated(e: Element, life: i32, x: i32, y: i32, tick: u64) -> char {
+    // cells rather than whole rows or columns ripple together
+    let spot = || life ^ x.wrapping_mul(0x2545_F491) ^ y.wrapping_mul(0x0193_3A27);
+    match e {
+        Element::Fire if flicker(life, tick) >> 63 == 1 => '+',
+        Element::Water if flicker(spot(), tick / 6) >> 62 == 0 => '_',
+        _ if is_gas(e) && flicker(life, tick) >> 61 == 0 => ' ',
+        _ => glyph_of(e, life),
     }
-    1
 }
 
-#[no_mangle]
-pub extern "C" fn powder_world_set_cell(
-    handle: PowderWorldHandle,
-    x: i32,
-    y: i32,
-    cell: Cell,
-) -> i32 {
-    if handle.is_null() {
-        return 0;
-    }
-    let w = unsafe { &mut *(handle as *mut World) };
-    if let Some(c) = w.get_cell_mut(x, y) {
-        *c = cell;
-        1
-    } else {
-        0
-    }
+/// Broad element family, for tooltips, palettes and filters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Category {
+    Empty,
+    Powder,
+    Liquid,
+    Gas,
+    Solid,
+    /// Fire and lightning.
+    Energy,
+    /// Humans, zombies, animals and players.
+    Creature,
 }
 
-/// Export the internal cell buffer in row-major order (y * width + x).
-/// `out_cells` must point to a buffer of at least `max_len` Cells.
-/// Returns the number of cells written.
-#[no_mangle]
-pub extern "C" fn powder_world_export_cells(
-    handle: PowderWorldHandle,
-    out_cells: *mut Cell,
-    max_len: usize,
-) -> usize {
-    if handle.is_null() || out_cells.is_null() {
-        return 0;
-    }
-    let w = unsafe { &*(handle as *const World) };
-    let total = (w.width().max(0) * w.height().max(0)) as usize;
-    let n = total.min(max_len);
-    unsafe {
-        ptr::copy_nonoverlapping(w.cells.as_ptr(), out_cells, n);
+/// Which family an element belongs to.
+pub fn category_of(e: Element) -> Category {
+    match e {
+        Element::Empty => Category::Empty,
+        Element::Fire | Element::Lightning => Category::Energy,
+        Element::Corpse | Element::Bone => Category::Powder,
+        Element::Human | Element::Zombie | Element::Animal | Element::Player => {
+            Category::Creature
+        }
+        e if is_sand_like(e) => Category::Powder,
+        e if is_liquid(e) => Category::Liquid,
+        e if is_gas(e) => Category::Gas,
+        _ => Category::Solid,
     }
-    n
-}
-
-/// Cheap wrappers for glyph/color so other languages can use the same mapping
-/// without re-implementing logic, if they want. i tried my best
-
-#[no_mangle]
-pub extern "C" fn powder_color_of(elem: Element, life: i32) -> u8 {
-    color_of(elem, life)
-}
-
-#[no_mangle]
-pub extern "C" fn powder_glyph_of(elem: Element, life: i32) -> u8 {
-    glyph_of(elem, life) as u8
 }
-// please file an issue in github if there is any sort of issue, thanks
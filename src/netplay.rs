@@ -0,0 +1,324 @@
+// PowderCore - lock-step netplay
+//
+// Two (or more) frontends share one sandbox by running identical worlds
+// and exchanging only inputs. The engine is deterministic (see replay.rs
+// and the PRNG notes in lib.rs), so peers that apply the same commands
+// before the same step() stay bit-identical without ever sending cells.
+//
+// Each peer calls local_frame() once per tick to package what its user
+// drew as a NetFrame for tick `now + input_delay`, sends frame.to_bytes()
+// over whatever transport it likes, and feeds frames from the others to
+// receive(). advance() steps the world once every peer's frame for the
+// current tick is in, applying the commands in canonical order (by peer
+// id, then in the order each peer queued them) so everyone mutates the
+// world identically. The delay hides the round trip: with a delay of N
+// ticks, inputs have N frames to arrive before anyone has to wait.
+//
+// Frames also carry the sender's state_hash() for the last tick it
+// stepped. A hash that differs from ours for the same tick means the
+// worlds have diverged; desync() reports the first one so the frontend can
+// resync (send a save, say). Hashes are kept for HASH_HISTORY ticks.
+//
+// Wire format (see save.rs for the varint/zigzag helpers):
+//
+//   magic    4 bytes  "PWNF"
+//   version  u8
+//   peer     u8
+//   tick     u64 LE
+//   hash     u8 flag, then (if 1) tick u64 LE, hash u64 LE
+//   commands varint count, then per command:
+//              zigzag varint x, zigzag varint y, u8 element id,
+//              brush (see write_brush in brush.rs)
+
+use std::collections::BTreeMap;
+
+use crate::brush::{read_brush, write_brush};
+use crate::save::{unzigzag, write_varint, zigzag, ByteSource, Reader};
+use crate::{Brush, Element, LoadError, World};
+
+const FRAME_MAGIC: &[u8; 4] = b"PWNF";
+const FRAME_VERSION: u8 = 1;
+
+/// Ticks of local hashes kept for comparing against late peer frames.
+pub const HASH_HISTORY: u64 = 1024;
+
+/// One brush stroke from a peer, applied like `World::apply_brush`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NetCommand {
+    pub x: i32,
+    pub y: i32,
+    pub elem: Element,
+    pub brush: Brush,
+}
+
+impl NetCommand {
+    /// A `place_brush` stroke: solid circle of radius `rad`.
+    pub fn brush(x: i32, y: i32, rad: i32, elem: Element) -> Self {
+        NetCommand {
+            x,
+            y,
+            elem,
+            brush: Brush::new(rad),
+        }
+    }
+}
+
+/// Everything one peer did on one tick, plus its latest state hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetFrame {
+    pub peer: u8,
+    /// Tick the commands apply before.
+    pub tick: u64,
+    /// (tick, state_hash) of the sender's world after its last step.
+    pub hash: Option<(u64, u64)>,
+    pub commands: Vec<NetCommand>,
+}
+
+/// First disagreement between our world and a peer's.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Desync {
+    pub tick: u64,
+    pub peer: u8,
+    pub local_hash: u64,
+    pub remote_hash: u64,
+}
+
+/// One peer's side of a lock-step session; see the notes above.
+#[derive(Clone, Debug)]
+pub struct LockstepSession {
+    local: u8,
+    peers: u8,
+    delay: u64,
+    /// Ticks before this one run without frames.
+    start: u64,
+    /// Next tick advance() will apply.
+    applied: u64,
+    /// Tick the next local frame is for.
+    next_local: u64,
+    queued: Vec<NetCommand>,
+    /// Per tick, each peer's commands once its frame is in.
+    frames: BTreeMap<u64, Vec<Option<Vec<NetCommand>>>>,
+    /// Our state hashes by tick.
+    hashes: BTreeMap<u64, u64>,
+    /// Peer hashes for ticks we haven't reached yet: (tick, peer, hash).
+    early: Vec<(u64, u8, u64)>,
+    last_hash: Option<(u64, u64)>,
+    desync: Option<Desync>,
+}
+
+impl LockstepSession {
+    /// Start a session for `world`, which every peer must hold in the same
+    /// state (same seed and edits, or the same save). This side is peer
+    /// `local_peer` of `peer_count`; ids must be unique and below the count.
+    /// The first `input_delay` ticks run without inputs.
+    pub fn new(world: &World, local_peer: u8, peer_count: u8, input_delay: u64) -> Self {
+        let now = world.tick();
+        LockstepSession {
+            local: local_peer,
+            peers: peer_count.max(local_peer.saturating_add(1)),
+            delay: input_delay,
+            start: now + input_delay,
+            applied: now,
+            next_local: now + input_delay,
+            queued: Vec::new(),
+            frames: BTreeMap::new(),
+            hashes: BTreeMap::new(),
+            early: Vec::new(),
+            last_hash: None,
+            desync: None,
+        }
+    }
+
+    pub fn local_peer(&self) -> u8 {
+        self.local
+    }
+
+    pub fn peer_count(&self) -> u8 {
+        self.peers
+    }
+
+    pub fn input_delay(&self) -> u64 {
+        self.delay
+    }
+
+    /// Queue a local command for the next local_frame().
+    pub fn queue(&mut self, command: NetCommand) {
+        self.queued.push(command);
+    }
+
+    /// Package the queued commands as this peer's frame for the next
+    /// input tick. Call once per tick (with nothing queued too: peers wait
+    /// for every frame) and send the result to every other peer.
+    pub fn local_frame(&mut self) -> NetFrame {
+        let frame = NetFrame {
+            peer: self.local,
+            tick: self.next_local,
+            hash: self.last_hash,
+            commands: std::mem::take(&mut self.queued),
+        };
+        self.next_local += 1;
+        let local = self.local as usize;
+        self.slot(frame.tick)[local] = Some(frame.commands.clone());
+        frame
+    }
+
+    /// Take a frame from another peer. Returns false (and ignores it) if
+    /// the peer id is ours or out of range, the tick has already been
+    /// applied or falls in the opening delay, or that peer's frame for the
+    /// tick is already in.
+    pub fn receive(&mut self, frame: NetFrame) -> bool {
+        let stale = frame.tick < self.applied.max(self.start);
+        if frame.peer == self.local || frame.peer >= self.peers || stale {
+            return false;
+        }
+        let slot = &mut self.slot(frame.tick)[frame.peer as usize];
+        if slot.is_some() {
+            return false;
+        }
+        *slot = Some(frame.commands);
+        if let Some((tick, hash)) = frame.hash {
+            self.check_hash(frame.peer, tick, hash);
+        }
+        true
+    }
+
+    /// Whether every peer's frame for the world's current tick is in.
+    pub fn ready(&self, world: &World) -> bool {
+        let tick = world.tick();
+        tick < self.start
+            || self
+                .frames
+                .get(&tick)
+                .is_some_and(|f| f.iter().all(Option::is_some))
+    }
+
+    /// Peers whose frame for the world's current tick hasn't arrived.
+    pub fn waiting_on(&self, world: &World) -> Vec<u8> {
+        if self.ready(world) {
+            return Vec::new();
+        }
+        let frames = self.frames.get(&world.tick());
+        (0..self.peers)
+            .filter(|&p| frames.is_none_or(|f| f[p as usize].is_none()))
+            .collect()
+    }
+
+    /// Apply every peer's commands for the current tick and step `world`
+    /// once. Returns false, leaving the world alone, while frames are
+    /// missing.
+    pub fn advance(&mut self, world: &mut World) -> bool {
+        if !self.ready(world) {
+            return false;
+        }
+        let tick = world.tick();
+        for commands in self.frames.remove(&tick).into_iter().flatten().flatten() {
+            for c in commands {
+                world.apply_brush(c.x, c.y, c.elem, &c.brush);
+            }
+        }
+        world.step();
+        self.applied = world.tick();
+
+        let (now, hash) = (world.tick(), world.state_hash());
+        self.hashes.insert(now, hash);
+        self.last_hash = Some((now, hash));
+        self.hashes = self.hashes.split_off(&now.saturating_sub(HASH_HISTORY));
+        for (t, peer, remote) in std::mem::take(&mut self.early) {
+            self.check_hash(peer, t, remote);
+        }
+        true
+    }
+
+    /// The first tick on which a peer's hash differed from ours, if any.
+    pub fn desync(&self) -> Option<Desync> {
+        self.desync
+    }
+
+    fn slot(&mut self, tick: u64) -> &mut Vec<Option<Vec<NetCommand>>> {
+        let peers = self.peers as usize;
+        self.frames.entry(tick).or_insert_with(|| vec![None; peers])
+    }
+
+    fn check_hash(&mut self, peer: u8, tick: u64, remote: u64) {
+        match self.hashes.get(&tick) {
+            Some(&local) if local != remote && self.desync.is_none() => {
+                self.desync = Some(Desync {
+                    tick,
+                    peer,
+                    local_hash: local,
+                    remote_hash: remote,
+                });
+            }
+            Some(_) => {}
+            None if tick > self.applied => self.early.push((tick, peer, remote)),
+            // older than our history: nothing left to compare against
+            None => {}
+        }
+    }
+}
+
+impl NetFrame {
+    /// Encode for sending over the wire.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(FRAME_MAGIC);
+        out.push(FRAME_VERSION);
+        out.push(self.peer);
+        out.extend_from_slice(&self.tick.to_le_bytes());
+        match self.hash {
+            Some((tick, hash)) => {
+                out.push(1);
+                out.extend_from_slice(&tick.to_le_bytes());
+                out.extend_from_slice(&hash.to_le_bytes());
+            }
+            None => out.push(0),
+        }
+        write_varint(&mut out, self.commands.len() as u64);
+        for c in &self.commands {
+            write_varint(&mut out, zigzag(c.x));
+            write_varint(&mut out, zigzag(c.y));
+            out.push(c.elem as i32 as u8);
+            write_brush(&mut out, &c.brush);
+        }
+        out
+    }
+
+    /// Decode bytes produced by `to_bytes`. Unknown element or shape ids
+    /// are Corrupt: guessing would desync the peers.
+    pub fn from_bytes(bytes: &[u8]) -> Result<NetFrame, LoadError> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(4)? != FRAME_MAGIC {
+            return Err(LoadError::BadMagic);
+        }
+        let version = r.u8()?;
+        if version != FRAME_VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
+        }
+        let peer = r.u8()?;
+        let tick = r.u64()?;
+        let hash = match r.u8()? {
+            0 => None,
+            1 => Some((r.u64()?, r.u64()?)),
+            _ => return Err(LoadError::Corrupt),
+        };
+        let count = r.varint()?;
+        // every command takes at least 7 bytes
+        if count > (bytes.len() - r.pos) as u64 / 7 {
+            return Err(LoadError::Truncated);
+        }
+        let mut commands = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let x = unzigzag(r.varint()?);
+            let y = unzigzag(r.varint()?);
+            let elem = Element::from_id(r.u8()? as i32).ok_or(LoadError::Corrupt)?;
+            let brush = read_brush(&mut r)?;
+            commands.push(NetCommand { x, y, elem, brush });
+        }
+        Ok(NetFrame {
+            peer,
+            tick,
+            hash,
+            commands,
+        })
+    }
+}
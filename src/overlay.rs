@@ -0,0 +1,96 @@
+// PowderCore - debug overlays
+//
+// Per-cell buffers for heatmap-style debug views, row-major like cells():
+//
+//   - temperature_map: each element's nominal temperature in °C. There is
+//     no heat diffusion; reactions (melting, boiling, ignition) look at
+//     which elements are adjacent, so this is the heat they respond to.
+//   - pressure_map: hydrostatic pressure, the weight of the unbroken
+//     column of liquid above and including each liquid cell, in cells of
+//     water (oil counts 0.9, mercury 2.0). Measured straight down; gravity
+//     zones aren't taken into account. Zero outside liquids.
+//   - charge_map: charge carried by wire, metal and water (their `life`).
+//   - activity_map: steps since each cell last changed element or moved,
+//     saturating at i16::MAX. It reads the provenance record (see
+//     provenance.rs), turning tracking on with the first call if needed;
+//     cells untouched since then count from that call.
+//
+// The buffers are scratch space on the World, refilled on every call and
+// valid until the next one.
+
+use crate::{density, is_liquid, Element, World};
+
+#[derive(Default)]
+pub(crate) struct Overlays {
+    floats: Vec<f32>,
+    ints: Vec<i16>,
+}
+
+/// Nominal temperature of `e` in °C.
+fn temperature_of(e: Element) -> f32 {
+    match e {
+        Element::Lightning => 3000.0,
+        Element::Lava => 1200.0,
+        Element::Fire => 800.0,
+        Element::Steam => 100.0,
+        Element::Smoke => 60.0,
+        Element::Ice => -10.0,
+        Element::Snow => -5.0,
+        _ => 20.0,
+    }
+}
+
+impl World {
+    /// Nominal temperature per cell, in °C.
+    pub fn temperature_map(&mut self) -> &[f32] {
+        let temps = self.cells.iter().map(|c| temperature_of(c.elem));
+        self.overlays.floats.clear();
+        self.overlays.floats.extend(temps);
+        &self.overlays.floats
+    }
+
+    /// Hydrostatic pressure per cell, in cells of water.
+    pub fn pressure_map(&mut self) -> &[f32] {
+        let w = self.width as usize;
+        let out = &mut self.overlays.floats;
+        out.clear();
+        out.resize(self.cells.len(), 0.0);
+        for (i, c) in self.cells.iter().enumerate() {
+            if is_liquid(c.elem) {
+                let above = if i >= w { out[i - w] } else { 0.0 };
+                out[i] = above + density(c.elem) as f32 / 100.0;
+            }
+        }
+        out
+    }
+
+    /// Charge per cell: the life of wire, metal and water, 0 elsewhere.
+    pub fn charge_map(&mut self) -> &[i16] {
+        let charges = self.cells.iter().map(|c| match c.elem {
+            Element::Wire | Element::Metal | Element::Water | Element::SaltWater => {
+                c.life.clamp(0, i16::MAX as i32) as i16
+            }
+            _ => 0,
+        });
+        self.overlays.ints.clear();
+        self.overlays.ints.extend(charges);
+        &self.overlays.ints
+    }
+
+    /// Steps since each cell last changed. Turns provenance tracking on if
+    /// it's off, in which case this call returns all zeros.
+    pub fn activity_map(&mut self) -> &[i16] {
+        if !self.provenance_tracking() {
+            self.set_provenance_tracking(true);
+        }
+        let now = self.tick;
+        let ages = self
+            .provenance
+            .cells()
+            .iter()
+            .map(|p| now.saturating_sub(p.tick).min(i16::MAX as u64) as i16);
+        self.overlays.ints.clear();
+        self.overlays.ints.extend(ages);
+        &self.overlays.ints
+    }
+}
@@ -0,0 +1,128 @@
+// PowderCore - hazard-aware pathfinding for humans
+//
+// Humans used to pick a direction and walk, fire or not. When a hazard is
+// close, step_human now asks for a route instead: a breadth-first search
+// over the cells a human could stand in, out to SimConfig::flee_radius,
+// for the nearest one with no hazard nearby. Moves are a step sideways,
+// one cell up (with headroom) or one cell down, the same moves try_walk
+// and the climb in step_human can make; cells without a floor (gaps) are
+// never entered. Away from danger, humans still wander, but turn around
+// rather than walk into a hazard or off a drop higher than MAX_DROP.
+//
+// The search only runs for humans near danger, so quiet worlds cost
+// nothing extra.
+
+use std::collections::VecDeque;
+
+use crate::{is_gas, is_hazard, Cell, Element, World};
+
+/// Chebyshev distance at which a hazard makes a cell unsafe.
+const DANGER_RADIUS: i32 = 2;
+/// Highest ledge a wandering human will step off.
+const MAX_DROP: i32 = 3;
+
+/// Room a human can move through.
+fn open(e: Element) -> bool {
+    e == Element::Empty || is_gas(e)
+}
+
+/// Anything that hurts a human standing next to it.
+fn dangerous(c: Cell) -> bool {
+    is_hazard(c.elem) || ((c.elem == Element::Water || c.elem == Element::SaltWater) && c.life > 0)
+}
+
+impl World {
+    /// True if a hazard is within DANGER_RADIUS of (x, y).
+    pub(crate) fn threatened(&self, x: i32, y: i32) -> bool {
+        let r = DANGER_RADIUS;
+        (-r..=r).any(|dy| {
+            (-r..=r).any(|dx| {
+                let (nx, ny) = (x + dx, y + dy);
+                self.in_bounds(nx, ny) && dangerous(self.cells[self.idx(nx, ny)])
+            })
+        })
+    }
+
+    /// Could a human stand at (x, y)? The cell must be open (empty or a
+    /// harmless gas) with something solid below it; the bottom edge counts
+    /// as floor.
+    fn standable(&self, x: i32, y: i32) -> bool {
+        if !self.in_bounds(x, y) {
+            return false;
+        }
+        let c = self.cells[self.idx(x, y)];
+        if !open(c.elem) || dangerous(c) {
+            return false;
+        }
+        if !self.in_bounds(x, y + 1) {
+            return true;
+        }
+        let below = self.cells[self.idx(x, y + 1)];
+        !open(below.elem) && !dangerous(below)
+    }
+
+    /// Would walking into the open cell (x, y) end somewhere safe? The
+    /// human falls until it lands, so follow the drop down.
+    fn safe_landing(&self, x: i32, y: i32) -> bool {
+        (y..=y + MAX_DROP)
+            .find(|&ly| self.standable(x, ly))
+            .is_some_and(|ly| !self.threatened(x, ly))
+    }
+
+    /// Direction a wandering human at (x, y) should take given it wants to
+    /// go `dir`: turned around if the way ahead is open but leads into
+    /// danger or off a cliff, and the way back doesn't.
+    pub(crate) fn wary_dir(&self, x: i32, y: i32, dir: i32) -> i32 {
+        let walkable = |px: i32| self.in_bounds(px, y) && open(self.cells[self.idx(px, y)].elem);
+        if walkable(x + dir)
+            && !self.safe_landing(x + dir, y)
+            && walkable(x - dir)
+            && self.safe_landing(x - dir, y)
+        {
+            -dir
+        } else {
+            dir
+        }
+    }
+
+    /// First move on the shortest route from a threatened (x, y) to a
+    /// standable cell out of danger, searching within `config.flee_radius`.
+    /// None if there is nowhere to go.
+    pub(crate) fn flee_step(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let r = self.config.flee_radius.max(1);
+        let side = (2 * r + 1) as usize;
+        let local = |px: i32, py: i32| (py - y + r) as usize * side + (px - x + r) as usize;
+        // first step taken to reach each visited cell
+        let mut first: Vec<Option<(i32, i32)>> = vec![None; side * side];
+        let mut queue = VecDeque::new();
+
+        first[local(x, y)] = Some((x, y));
+        queue.push_back((x, y));
+        while let Some((cx, cy)) = queue.pop_front() {
+            for (dx, dy) in [(-1, 0), (1, 0), (-1, -1), (1, -1), (-1, 1), (1, 1)] {
+                let (nx, ny) = (cx + dx, cy + dy);
+                if (nx - x).abs() > r || (ny - y).abs() > r || first[local(nx, ny)].is_some() {
+                    continue;
+                }
+                if !self.standable(nx, ny) {
+                    continue;
+                }
+                // climbing needs headroom above the current cell
+                if dy < 0 && self.get_cell(cx, cy - 1).elem != Element::Empty {
+                    continue;
+                }
+                let step = if (cx, cy) == (x, y) {
+                    (nx, ny)
+                } else {
+                    first[local(cx, cy)].unwrap_or((nx, ny))
+                };
+                if !self.threatened(nx, ny) {
+                    return Some(step);
+                }
+                first[local(nx, ny)] = Some(step);
+                queue.push_back((nx, ny));
+            }
+        }
+        None
+    }
+}
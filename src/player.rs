@@ -0,0 +1,110 @@
+// PowderCore - player-controlled actor
+//
+// A Player cell moves only when told to. Frontends look up its actor id
+// (World::actors / actor_at) and call World::set_player_input with the
+// buttons currently held; the input sticks until it is replaced, so a
+// frontend can call it once per frame or only when keys change.
+//
+// Movement is deliberately simple platformer physics on the cell grid:
+// one cell sideways per tick, a step up onto one-cell ledges, falling one
+// cell per tick, and a jump that rises JUMP_HEIGHT cells unless something
+// is in the way. Hazards hurt the player like any other actor.
+
+use crate::actors::HAZARD_DAMAGE;
+use crate::{is_hazard, Element, World};
+
+/// Cells a jump rises before gravity takes over.
+const JUMP_HEIGHT: i32 = 4;
+
+/// Buttons held for a Player actor, see `World::set_player_input`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PlayerInput {
+    pub left: bool,
+    pub right: bool,
+    pub jump: bool,
+}
+
+impl World {
+    /// Set the held buttons for the Player actor with `id`. Returns false
+    /// if there is no such player (it died, or the id isn't a Player).
+    pub fn set_player_input(&mut self, id: u64, input: PlayerInput) -> bool {
+        let Some((x, y)) = self.find_actor(id) else {
+            return false;
+        };
+        let i = self.idx(x, y);
+        if self.cells[i].elem != Element::Player {
+            return false;
+        }
+        match self.actor_mut(i) {
+            Some(a) => {
+                a.input = input;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn step_player(&mut self, x: i32, y: i32, updated: &mut [bool]) {
+        let idx0 = self.idx(x, y);
+        updated[idx0] = true;
+
+        let touching = (-1..=1).any(|dy| {
+            (-1..=1).any(|dx| {
+                let (nx, ny) = (x + dx, y + dy);
+                self.in_bounds(nx, ny) && is_hazard(self.cells[self.idx(nx, ny)].elem)
+            })
+        });
+        if touching && self.hurt(idx0, HAZARD_DAMAGE) {
+            self.put(idx0, Element::Corpse, 0);
+            return;
+        }
+        self.cells[idx0].life += 1;
+
+        let Some(a) = self.actor_mut(idx0) else {
+            return;
+        };
+        let (input, rise) = (a.input, a.rise);
+        // standing on something, or on the bottom edge
+        let grounded = !self.in_bounds(x, y + 1) || !self.open_at(x, y + 1);
+
+        // vertical: keep rising, start a jump, or fall
+        let mut rise = if grounded && input.jump {
+            JUMP_HEIGHT
+        } else {
+            rise
+        };
+        let mut cy = y;
+        if rise > 0 {
+            if self.open_at(x, cy - 1) {
+                cy -= 1;
+                rise -= 1;
+            } else {
+                rise = 0;
+            }
+        } else if self.open_at(x, cy + 1) {
+            cy += 1;
+        }
+
+        // horizontal: walk, or step up a one-cell ledge
+        let dir = input.right as i32 - input.left as i32;
+        let mut cx = x;
+        if dir != 0 {
+            if self.open_at(x + dir, cy) {
+                cx += dir;
+            } else if grounded && self.open_at(x, cy - 1) && self.open_at(x + dir, cy - 1) {
+                cx += dir;
+                cy -= 1;
+            }
+        }
+
+        let mut at = idx0;
+        if (cx, cy) != (x, y) {
+            at = self.idx(cx, cy);
+            self.move_actor(idx0, at);
+            updated[at] = true;
+        }
+        if let Some(a) = self.actor_mut(at) {
+            a.rise = rise;
+        }
+    }
+}
@@ -0,0 +1,122 @@
+// PowderCore - PNG screenshots (feature "image")
+//
+// screenshot_png renders the world with render_rgba and writes it as an
+// 8-bit RGBA PNG, for headless batch runs that want a picture of the final
+// state.
+//
+// The crate stays dependency-free, so the encoder is the smallest valid
+// one: the pixel rows go into stored (uncompressed) deflate blocks. Files
+// come out about the size of the raw RGBA; run them through any PNG
+// optimizer if that matters.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::{Palette, World};
+
+const SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/// Largest stored deflate block.
+const STORED_MAX: usize = 65_535;
+
+impl World {
+    /// Render with `palette` and write a PNG to `path`.
+    pub fn screenshot_png(&self, path: impl AsRef<Path>, palette: &Palette) -> io::Result<()> {
+        let mut buf = Vec::new();
+        self.write_png(&mut buf, palette)?;
+        fs::write(path, buf)
+    }
+
+    /// Render with `palette` and write a PNG to `writer`. Fails with
+    /// InvalidInput for a world with no cells, which PNG can't represent.
+    pub fn write_png<W: Write>(&self, mut writer: W, palette: &Palette) -> io::Result<()> {
+        if self.width <= 0 || self.height <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot write an empty world as PNG",
+            ));
+        }
+        let rgba = self.render_rgba(palette);
+        let row = self.width as usize * 4;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlace
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        // each scanline is a filter byte (0, none) and the pixels
+        let mut raw = Vec::with_capacity(rgba.len() + self.height as usize);
+        for line in rgba.chunks_exact(row) {
+            raw.push(0);
+            raw.extend_from_slice(line);
+        }
+
+        writer.write_all(SIGNATURE)?;
+        write_chunk(&mut writer, b"IHDR", &header)?;
+        write_chunk(&mut writer, b"IDAT", &zlib_stored(&raw))?;
+        write_chunk(&mut writer, b"IEND", &[])?;
+        writer.flush()
+    }
+}
+
+/// Length, type, data and CRC of the type and data.
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(crc32(!0, kind), data);
+    writer.write_all(&(!crc).to_be_bytes())
+}
+
+/// `data` as a zlib stream of stored deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len() / STORED_MAX + 1;
+    let mut out = Vec::with_capacity(data.len() + blocks * 5 + 6);
+    out.extend_from_slice(&[0x78, 0x01]);
+    let mut chunks = data.chunks(STORED_MAX).peekable();
+    if chunks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Continue a CRC-32 (the PNG/zlib polynomial) over `data`. Start from !0
+/// and invert the result.
+fn crc32(mut crc: u32, data: &[u8]) -> u32 {
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before b could overflow
+    for chunk in data.chunks(5552) {
+        for &x in chunk {
+            a += x as u32;
+            b += a;
+        }
+        a %= 65_521;
+        b %= 65_521;
+    }
+    (b << 16) | a
+}
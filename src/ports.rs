@@ -0,0 +1,89 @@
+// PowderCore - boundary ports
+//
+// Stretches of the world's edge connected to a larger environment off
+// screen: a river feeding in at the top, a sand hopper, a sewer draining
+// the bottom. At the end of every step() each port works on the cells of
+// its edge row or column within its range:
+//
+//   - Source(elem) fills every empty one with a fresh `elem`,
+//   - Drain removes every liquid.
+//
+// Ports don't use the RNG. Like the edge mode, they are a property of the
+// World and aren't saved with it.
+
+use std::ops::Range;
+
+use crate::{default_life, is_liquid, Element, World};
+
+/// One side of the world.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PortKind {
+    /// Keep the port's cells topped up with this element.
+    Source(Element),
+    /// Swallow liquid reaching the port.
+    Drain,
+}
+
+/// A stretch of edge connected to the outside.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Port {
+    pub edge: Edge,
+    /// Columns (Top/Bottom) or rows (Left/Right) covered; clipped to the
+    /// world when applied.
+    pub range: Range<i32>,
+    pub kind: PortKind,
+}
+
+impl World {
+    /// Connect `range` of `edge` to the outside.
+    pub fn add_port(&mut self, edge: Edge, range: Range<i32>, kind: PortKind) {
+        self.ports.push(Port { edge, range, kind });
+    }
+
+    pub fn ports(&self) -> &[Port] {
+        &self.ports
+    }
+
+    pub fn clear_ports(&mut self) {
+        self.ports.clear();
+    }
+
+    /// Let every port inject or drain for this tick.
+    pub(crate) fn run_ports(&mut self) {
+        if self.width <= 0 || self.height <= 0 {
+            return;
+        }
+        for p in 0..self.ports.len() {
+            let Port { edge, range, kind } = self.ports[p].clone();
+            let (len, fixed) = match edge {
+                Edge::Top => (self.width, 0),
+                Edge::Bottom => (self.width, self.height - 1),
+                Edge::Left => (self.height, 0),
+                Edge::Right => (self.height, self.width - 1),
+            };
+            for along in range.start.max(0)..range.end.min(len) {
+                let (x, y) = match edge {
+                    Edge::Top | Edge::Bottom => (along, fixed),
+                    Edge::Left | Edge::Right => (fixed, along),
+                };
+                let i = self.idx(x, y);
+                let here = self.cells[i].elem;
+                match kind {
+                    PortKind::Source(elem) if here == Element::Empty => {
+                        self.put(i, elem, default_life(elem));
+                    }
+                    PortKind::Drain if is_liquid(here) => self.put(i, Element::Empty, 0),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+// PowderCore - cell provenance
+//
+// Debug mode for "why did my wire vanish": while on, the world remembers
+// for every cell the tick its element last changed and what changed it.
+// Element changes go through put() and moves through swap_cells(), so
+// those two record; step() says which phase is running (reactions,
+// scheduled actions, weather, boundary ports), and anything outside step()
+// counts as an edit. Changes to a cell's life alone (charge, wetness,
+// fuel) aren't recorded.
+//
+// Off by default. Turning it on costs 16 bytes per cell and a write per
+// change; cells untouched since then report Unchanged at the tick tracking
+// started. Edits that bypass put() (get_cell_mut, loading, restoring a
+// snapshot) aren't seen, and resizing restarts the record (a ChunkedWorld
+// growing keeps it, with the new cells Unchanged).
+
+use crate::World;
+
+/// What last changed a cell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChangeCause {
+    /// Nothing since tracking started.
+    Unchanged,
+    /// Brushes, drawing, spawn, paste and other edits between steps.
+    Edit,
+    /// Falling, flowing, rising or a creature walking (into or out of it).
+    Movement,
+    /// A reaction: burning, melting, acid, explosions, cave-ins, ...
+    Reaction,
+    /// A scheduled action.
+    Scheduled,
+    /// Rain, snow or a meteor.
+    Weather,
+    /// A boundary port or an open edge.
+    Boundary,
+}
+
+/// When and how a cell last changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Provenance {
+    pub tick: u64,
+    pub cause: ChangeCause,
+}
+
+pub(crate) struct ProvenanceLog {
+    /// One per cell while tracking, empty otherwise.
+    cells: Vec<Provenance>,
+    /// Cause recorded by put() right now.
+    pub(crate) cause: ChangeCause,
+}
+
+impl Default for ProvenanceLog {
+    fn default() -> Self {
+        ProvenanceLog {
+            cells: Vec::new(),
+            cause: ChangeCause::Edit,
+        }
+    }
+}
+
+impl ProvenanceLog {
+    pub(crate) fn enabled(&self) -> bool {
+        !self.cells.is_empty()
+    }
+
+    /// Start over with every cell at `tick`, marked `cause`.
+    pub(crate) fn reset(&mut self, len: usize, tick: u64, cause: ChangeCause) {
+        self.cells = vec![Provenance { tick, cause }; len];
+    }
+
+    /// Record a change to cell `i` by the current phase.
+    pub(crate) fn record(&mut self, i: usize, tick: u64) {
+        let cause = self.cause;
+        self.record_as(i, tick, cause);
+    }
+
+    pub(crate) fn record_as(&mut self, i: usize, tick: u64, cause: ChangeCause) {
+        if let Some(p) = self.cells.get_mut(i) {
+            *p = Provenance { tick, cause };
+        }
+    }
+
+    pub(crate) fn cells(&self) -> &[Provenance] {
+        &self.cells
+    }
+
+    /// Replace the record with `cells`, re-keyed for a new grid.
+    pub(crate) fn set_cells(&mut self, cells: Vec<Provenance>) {
+        self.cells = cells;
+    }
+}
+
+impl World {
+    /// Turn provenance tracking on or off. Turning it on starts a fresh
+    /// record; turning it off frees it.
+    pub fn set_provenance_tracking(&mut self, on: bool) {
+        if on {
+            self.provenance
+                .reset(self.cells.len(), self.tick, ChangeCause::Unchanged);
+        } else {
+            self.provenance.cells = Vec::new();
+        }
+    }
+
+    pub fn provenance_tracking(&self) -> bool {
+        self.provenance.enabled()
+    }
+
+    /// When and how (x, y) last changed. None if tracking is off or (x, y)
+    /// is out of bounds.
+    pub fn provenance(&self, x: i32, y: i32) -> Option<Provenance> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        self.provenance.cells().get(self.idx(x, y)).copied()
+    }
+
+    /// The grid was replaced wholesale (cleared or resized): every cell
+    /// was just edited.
+    pub(crate) fn provenance_reset(&mut self) {
+        if self.provenance.enabled() {
+            self.provenance
+                .reset(self.cells.len(), self.tick, ChangeCause::Edit);
+        }
+    }
+}
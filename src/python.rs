@@ -0,0 +1,253 @@
+// PowderCore - native Python module (feature "python")
+//
+// A PyO3 extension exposing World as a Python class, with no C ABI or
+// ctypes marshalling in between:
+//
+//   import numpy as np, powdercore as pc
+//   w = pc.World(200, 120, seed=1)
+//   w.place_brush(100, 10, 4, pc.SAND)
+//   w.step(60)
+//   grid = np.asarray(w)        # (height, width), fields elem/life, no copy
+//
+// Build with `cargo build --release --features python` and copy
+// target/release/libpowdercore.so to powdercore.so (powdercore.pyd on
+// Windows) somewhere on sys.path, or point maturin at the crate.
+//
+// World supports the buffer protocol: memoryview(w) and np.asarray(w) see
+// the cell grid directly, read-only (a stray write could store an element
+// id the engine doesn't know). The view is live, so stepping shows through
+// it. While any view is held, resize() and load() raise BufferError
+// instead of moving the grid out from under it.
+//
+// Element constants (SAND, SALT_WATER, ...) and the ELEMENTS name -> id
+// dict are built from the element table, so they always match the build.
+
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+use pyo3::exceptions::{PyBufferError, PyValueError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::save::MAX_CELLS;
+use crate::{glyph_of, name_of, Cell, Element, Palette, World};
+
+/// PEP 3118 format of one Cell: two native int32s named like its fields.
+const CELL_FORMAT: &[u8] = b"T{=i:elem:=i:life:}\0";
+
+fn element(id: i32) -> PyResult<Element> {
+    Element::try_from(id).map_err(|_| PyValueError::new_err(format!("invalid element id {id}")))
+}
+
+fn check_size(width: i32, height: i32) -> PyResult<()> {
+    if width < 0 || height < 0 || width as u64 * height as u64 > MAX_CELLS {
+        return Err(PyValueError::new_err(format!(
+            "world size {width}x{height} must be non-negative and at most {MAX_CELLS} cells"
+        )));
+    }
+    Ok(())
+}
+
+/// A simulation grid. Mirrors the Rust `World`.
+#[pyclass(name = "World", module = "powdercore")]
+pub struct PyWorld {
+    world: World,
+    /// Buffer views currently exported.
+    exports: usize,
+    /// Shape then strides of the exported grid, kept here so the pointers
+    /// handed to Python stay valid for as long as the view does.
+    layout: [ffi::Py_ssize_t; 4],
+}
+
+impl PyWorld {
+    fn unshared(&self, what: &str) -> PyResult<()> {
+        if self.exports > 0 {
+            return Err(PyBufferError::new_err(format!(
+                "cannot {what} while a buffer view of the world is held"
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl PyWorld {
+    #[new]
+    #[pyo3(signature = (width, height, seed = 0))]
+    fn new(width: i32, height: i32, seed: u64) -> PyResult<Self> {
+        check_size(width, height)?;
+        Ok(PyWorld {
+            world: World::new(width, height, seed),
+            exports: 0,
+            layout: [0; 4],
+        })
+    }
+
+    #[getter]
+    fn width(&self) -> i32 {
+        self.world.width()
+    }
+
+    #[getter]
+    fn height(&self) -> i32 {
+        self.world.height()
+    }
+
+    /// (width, height)
+    #[getter]
+    fn size(&self) -> (i32, i32) {
+        (self.world.width(), self.world.height())
+    }
+
+    #[getter]
+    fn tick(&self) -> u64 {
+        self.world.tick()
+    }
+
+    /// Advance the simulation `n` ticks.
+    #[pyo3(signature = (n = 1))]
+    fn step(&mut self, n: u32) {
+        for _ in 0..n {
+            self.world.step();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.world.clear();
+    }
+
+    /// Replace the grid with an empty one of the new size.
+    fn resize(&mut self, width: i32, height: i32) -> PyResult<()> {
+        check_size(width, height)?;
+        self.unshared("resize")?;
+        self.world.resize(width, height);
+        Ok(())
+    }
+
+    fn place_brush(&mut self, x: i32, y: i32, radius: i32, elem: i32) -> PyResult<()> {
+        self.world.place_brush(x, y, radius, element(elem)?);
+        Ok(())
+    }
+
+    /// Put an actor (human, zombie, animal) or plain element at (x, y).
+    fn spawn(&mut self, x: i32, y: i32, elem: i32) -> PyResult<bool> {
+        Ok(self.world.spawn(x, y, element(elem)?))
+    }
+
+    /// (elem, life) of the cell at (x, y); out of bounds reads as Empty.
+    fn get_cell(&self, x: i32, y: i32) -> (i32, i32) {
+        let c = self.world.get_cell(x, y);
+        (c.elem as i32, c.life)
+    }
+
+    #[pyo3(signature = (x, y, elem, life = 0))]
+    fn set_cell(&mut self, x: i32, y: i32, elem: i32, life: i32) -> PyResult<()> {
+        let elem = element(elem)?;
+        if let Some(c) = self.world.get_cell_mut(x, y) {
+            *c = Cell { elem, life };
+        }
+        Ok(())
+    }
+
+    /// Row `y` as a string of glyphs, for terminal frontends.
+    fn row_glyphs(&self, y: i32) -> String {
+        self.world
+            .row(y)
+            .iter()
+            .map(|c| glyph_of(c.elem, c.life))
+            .collect()
+    }
+
+    /// RGBA image (width * height * 4 bytes) in the default palette.
+    fn render_rgba<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        let mut rgba = vec![0; self.world.cells.len() * 4];
+        self.world.render_rgba_into(&mut rgba, &Palette::DEFAULT);
+        PyBytes::new(py, &rgba)
+    }
+
+    /// The save format (see World::save_to_bytes).
+    fn save<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.world.save_to_bytes())
+    }
+
+    /// Replace this world with one loaded from `data`.
+    fn load(&mut self, data: &[u8]) -> PyResult<()> {
+        self.unshared("load")?;
+        self.world =
+            World::load_from_bytes(data).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(())
+    }
+
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("null buffer view"));
+        }
+        if flags & ffi::PyBUF_WRITABLE == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("the world grid is read-only"));
+        }
+        let mut me = slf.borrow_mut();
+        let (w, h) = (
+            me.world.width() as ffi::Py_ssize_t,
+            me.world.height() as ffi::Py_ssize_t,
+        );
+        let item = std::mem::size_of::<Cell>() as ffi::Py_ssize_t;
+        me.layout = [h, w, w * item, item];
+        me.exports += 1;
+
+        let v = &mut *view;
+        v.buf = me.world.cells.as_ptr() as *mut c_void;
+        v.len = me.world.cells.len() as ffi::Py_ssize_t * item;
+        v.readonly = 1;
+        v.itemsize = item;
+        v.format = if flags & ffi::PyBUF_FORMAT == ffi::PyBUF_FORMAT {
+            CELL_FORMAT.as_ptr() as *mut c_char
+        } else {
+            ptr::null_mut()
+        };
+        v.ndim = 2;
+        v.shape = if flags & ffi::PyBUF_ND == ffi::PyBUF_ND {
+            me.layout.as_mut_ptr()
+        } else {
+            ptr::null_mut()
+        };
+        v.strides = if flags & ffi::PyBUF_STRIDES == ffi::PyBUF_STRIDES {
+            me.layout[2..].as_mut_ptr()
+        } else {
+            ptr::null_mut()
+        };
+        v.suboffsets = ptr::null_mut();
+        v.internal = ptr::null_mut();
+        drop(me);
+        v.obj = slf.into_any().into_ptr();
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&mut self, _view: *mut ffi::Py_buffer) {
+        self.exports -= 1;
+    }
+}
+
+/// Display name of an element id, e.g. element_name(1) == "Sand".
+#[pyfunction]
+fn element_name(elem: i32) -> PyResult<&'static str> {
+    Ok(name_of(element(elem)?))
+}
+
+#[pymodule]
+fn powdercore(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyWorld>()?;
+    m.add_function(wrap_pyfunction!(element_name, m)?)?;
+    let elements = PyDict::new(m.py());
+    for elem in Element::ALL {
+        let name = name_of(elem);
+        elements.set_item(name, elem as i32)?;
+        m.add(name.to_uppercase().replace(' ', "_").as_str(), elem as i32)?;
+    }
+    m.add("ELEMENTS", elements)?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+// PowderCore - spatial queries
+//
+// Read-only questions about the grid that tools and game logic keep asking:
+// what does a ray hit first, which cells form one connected blob.
+
+use std::collections::VecDeque;
+
+use crate::draw::line_points;
+use crate::{Cell, World};
+
+impl World {
+    /// Walk from (x0, y0) in direction (dx, dy) and return the first cell
+    /// for which `filter` is true, with its position. The start cell itself
+    /// is skipped and the ray stops at the world edge. Rays starting
+    /// outside the world, or with a zero direction, hit nothing.
+    pub fn raycast<F>(
+        &self,
+        x0: i32,
+        y0: i32,
+        dx: i32,
+        dy: i32,
+        filter: F,
+    ) -> Option<(i32, i32, Cell)>
+    where
+        F: Fn(&Cell) -> bool,
+    {
+        if (dx == 0 && dy == 0) || !self.in_bounds(x0, y0) {
+            return None;
+        }
+        // keep huge direction vectors from overflowing the walk below
+        let big = dx.unsigned_abs().max(dy.unsigned_abs());
+        let shrink = (big >> 15) as i32 + 1;
+        let (dx, dy) = (dx / shrink, dy / shrink);
+        let len = dx.abs().max(dy.abs()).max(1);
+        // scale the direction until it's certainly past the far edge
+        let reach = (self.width + self.height) / len + 1;
+        let (x1, y1) = (x0 + dx * reach, y0 + dy * reach);
+
+        line_points(x0, y0, x1, y1)
+            .skip(1)
+            .take_while(|&(x, y)| self.in_bounds(x, y))
+            .map(|(x, y)| (x, y, self.cells[self.idx(x, y)]))
+            .find(|(_, _, c)| filter(c))
+    }
+
+    /// All cells 4-connected to (x, y) holding the same element, start
+    /// included, in breadth-first order. Selects "this whole lake" or
+    /// measures a blob; empty for out-of-bounds starts.
+    pub fn connected_region(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
+        if !self.in_bounds(x, y) {
+            return Vec::new();
+        }
+        let elem = self.cells[self.idx(x, y)].elem;
+        let mut seen = vec![false; self.cells.len()];
+        let mut queue = VecDeque::from([(x, y)]);
+        let mut out = Vec::new();
+        seen[self.idx(x, y)] = true;
+
+        while let Some((cx, cy)) = queue.pop_front() {
+            out.push((cx, cy));
+            for (nx, ny) in [(cx + 1, cy), (cx - 1, cy), (cx, cy + 1), (cx, cy - 1)] {
+                if !self.in_bounds(nx, ny) {
+                    continue;
+                }
+                let i = self.idx(nx, ny);
+                if !seen[i] && self.cells[i].elem == elem {
+                    seen[i] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        out
+    }
+}
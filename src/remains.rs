@@ -0,0 +1,41 @@
+// PowderCore - corpses and bones
+//
+// Humans (and players) that die to hazards, shocks or drowning leave a
+// Corpse instead of vanishing into Ash. Remains fall straight down, sink
+// through liquids and decay in two stages:
+//
+//   Corpse --(SimConfig::corpse_decay_ticks)--> Bone
+//   Bone   --(SimConfig::bone_decay_ticks)----> Ash
+//
+// The cell's life counts its age. Corpses burn and draw zombies that have
+// no living human in sight.
+
+use crate::{is_gas, is_liquid, Element, World};
+
+impl World {
+    pub(crate) fn step_remains(&mut self, x: i32, y: i32, updated: &mut [bool]) {
+        let idx0 = self.idx(x, y);
+        updated[idx0] = true;
+
+        let c = self.cells[idx0];
+        let (limit, next) = match c.elem {
+            Element::Corpse => (self.config.corpse_decay_ticks, Element::Bone),
+            _ => (self.config.bone_decay_ticks, Element::Ash),
+        };
+        if c.life + 1 >= limit {
+            self.put(idx0, next, 0);
+            self.emit_converted(x, y, c.elem, next);
+            return;
+        }
+        self.cells[idx0].life += 1;
+
+        if self.in_bounds(x, y + 1) {
+            let idx_b = self.idx(x, y + 1);
+            let b = self.cells[idx_b].elem;
+            if b == Element::Empty || is_gas(b) || is_liquid(b) {
+                self.swap_cells(idx0, idx_b);
+                updated[idx_b] = true;
+            }
+        }
+    }
+}
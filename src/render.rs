@@ -0,0 +1,463 @@
+// PowderCore - RGBA framebuffer and palettes
+//
+// Turns the grid into a packed width*height*4 byte image (row-major, RGBA)
+// so frontends can blit the world in one call instead of mapping cells
+// themselves.
+//
+// A Palette holds one color per element, plus one for charged water. The
+// built-in ones:
+//
+//   - classic: the nine color_of() indices as the C++ ncurses build drew
+//     them, so elements sharing an index share a color,
+//   - xterm256: each element's color256_of() entry from the xterm
+//     256-color table, for terminals that have it,
+//   - rich: hand-picked truecolor values per element (the default).
+//
+// overlay_rgba draws a translucent debug layer of state the colors don't
+// show (charge, life, what changed last step, which chunks are busy), to
+// blend over a normal render.
+//
+// render_emission gives the light each cell gives off (emission_of), one
+// byte per cell, for bloom and halos.
+//
+// Tinting is optional: it animates fire, lava and lightning with
+// shade_of() at the world's tick, fades gases by their remaining life and
+// adds a small fixed per-position grain so large blobs of one element don't
+// look flat.
+
+use crate::{
+    color_of, default_life, emission_of, is_gas, shade_of, ChangeCause, Element, World, CHUNK_SIZE,
+};
+
+/// Number of palette colors: one per element id.
+pub const PALETTE_LEN: usize = 43;
+
+const _: () = assert!(PALETTE_LEN == Element::COUNT);
+
+/// Per-element colors, plus whether to apply per-cell tint.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    /// RGBA for each element, indexed by element id.
+    pub colors: [[u8; 4]; PALETTE_LEN],
+    /// Water and salt water carrying charge.
+    pub charged: [u8; 4],
+    /// Nonzero to apply per-cell tint.
+    pub tint: i32,
+}
+
+impl Palette {
+    /// The rich palette.
+    pub const DEFAULT: Palette = Palette::rich();
+
+    /// The ncurses colors of the original TUI, one per color_of() index.
+    /// Untinted, like a terminal.
+    pub const fn classic() -> Palette {
+        // black, yellow, blue, white, green, red, grey, magenta, cyan
+        const SLOTS: [[u8; 3]; 10] = [
+            [0, 0, 0],
+            [0, 0, 0],
+            [205, 205, 0],
+            [0, 0, 238],
+            [229, 229, 229],
+            [0, 205, 0],
+            [205, 0, 0],
+            [127, 127, 127],
+            [205, 0, 205],
+            [0, 205, 205],
+        ];
+        let mut colors = [[0; 4]; PALETTE_LEN];
+        let mut i = 0;
+        while i < PALETTE_LEN {
+            colors[i] = opaque(SLOTS[color_of(Element::ALL[i], 0) as usize]);
+            i += 1;
+        }
+        Palette {
+            colors,
+            charged: opaque(SLOTS[9]),
+            tint: 0,
+        }
+    }
+
+    /// color256_of() for every element, as RGB. Untinted.
+    pub const fn xterm256() -> Palette {
+        let mut colors = [[0; 4]; PALETTE_LEN];
+        let mut i = 0;
+        while i < PALETTE_LEN {
+            colors[i] = opaque(xterm_rgb(color256_of(Element::ALL[i], 0)));
+            i += 1;
+        }
+        Palette {
+            colors,
+            charged: opaque(xterm_rgb(color256_of(Element::Water, 1))),
+            tint: 0,
+        }
+    }
+
+    /// A distinct truecolor per element, tinted.
+    pub const fn rich() -> Palette {
+        let mut colors = [[0; 4]; PALETTE_LEN];
+        let mut i = 0;
+        while i < PALETTE_LEN {
+            colors[i] = opaque(rich_rgb(Element::ALL[i]));
+            i += 1;
+        }
+        Palette {
+            colors,
+            charged: opaque([90, 230, 255]),
+            tint: 1,
+        }
+    }
+
+    /// Built-in palette by id, for the C API: 0 classic, 1 xterm256,
+    /// 2 rich.
+    pub(crate) fn from_id(id: i32) -> Option<Palette> {
+        match id {
+            0 => Some(Palette::classic()),
+            1 => Some(Palette::xterm256()),
+            2 => Some(Palette::rich()),
+            _ => None,
+        }
+    }
+
+    /// Untinted color of a cell holding `e` with `life`.
+    pub fn rgba_of(&self, e: Element, life: i32) -> [u8; 4] {
+        if matches!(e, Element::Water | Element::SaltWater) && life > 0 {
+            self.charged
+        } else {
+            self.colors[e as usize]
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::DEFAULT
+    }
+}
+
+const fn opaque([r, g, b]: [u8; 3]) -> [u8; 4] {
+    [r, g, b, 255]
+}
+
+/// xterm 256-color index for `e`; charged water gets bright cyan.
+pub const fn color256_of(e: Element, life: i32) -> u8 {
+    if matches!(e, Element::Water | Element::SaltWater) && life > 0 {
+        return 51;
+    }
+    match e {
+        Element::Empty => 16,
+        Element::Sand => 180,
+        Element::Gunpowder => 240,
+        Element::Ash => 246,
+        Element::Snow => 231,
+        Element::Water => 27,
+        Element::SaltWater => 68,
+        Element::Oil => 94,
+        Element::Ethanol => 153,
+        Element::Acid => 112,
+        Element::Lava => 202,
+        Element::Mercury => 250,
+        Element::Stone => 244,
+        Element::Glass => 152,
+        Element::Wall => 239,
+        Element::Wood => 130,
+        Element::Plant => 70,
+        Element::Metal => 248,
+        Element::Wire => 173,
+        Element::Ice => 159,
+        Element::Coal => 236,
+        Element::Dirt => 95,
+        Element::WetDirt => 58,
+        Element::Seaweed => 29,
+        Element::Smoke => 243,
+        Element::Steam => 254,
+        Element::Gas => 143,
+        Element::ToxicGas => 76,
+        Element::Hydrogen => 189,
+        Element::Chlorine => 185,
+        Element::Fire => 208,
+        Element::Lightning => 229,
+        Element::Human => 223,
+        Element::Zombie => 65,
+        Element::HumanSpawner => 216,
+        Element::ZombieSpawner => 22,
+        Element::Animal => 137,
+        Element::Player => 39,
+        Element::Corpse => 131,
+        Element::Bone => 230,
+        Element::Rubble => 242,
+        Element::DilutedEthanol => 189,
+        Element::WeakAcid => 150,
+    }
+}
+
+/// RGB of an xterm 256-color index: the 16 system colors, the 6x6x6 cube,
+/// then the grey ramp.
+const fn xterm_rgb(n: u8) -> [u8; 3] {
+    const SYSTEM: [[u8; 3]; 16] = [
+        [0, 0, 0],
+        [205, 0, 0],
+        [0, 205, 0],
+        [205, 205, 0],
+        [0, 0, 238],
+        [205, 0, 205],
+        [0, 205, 205],
+        [229, 229, 229],
+        [127, 127, 127],
+        [255, 0, 0],
+        [0, 255, 0],
+        [255, 255, 0],
+        [92, 92, 255],
+        [255, 0, 255],
+        [0, 255, 255],
+        [255, 255, 255],
+    ];
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    match n {
+        0..=15 => SYSTEM[n as usize],
+        16..=231 => {
+            let c = (n - 16) as usize;
+            [LEVELS[c / 36], LEVELS[c / 6 % 6], LEVELS[c % 6]]
+        }
+        _ => {
+            let v = 8 + 10 * (n - 232);
+            [v, v, v]
+        }
+    }
+}
+
+/// Truecolor for `e` in the rich palette.
+const fn rich_rgb(e: Element) -> [u8; 3] {
+    match e {
+        Element::Empty => [0, 0, 0],
+        Element::Sand => [222, 196, 120],
+        Element::Gunpowder => [80, 80, 75],
+        Element::Ash => [150, 145, 140],
+        Element::Snow => [240, 245, 255],
+        Element::Water => [50, 110, 230],
+        Element::SaltWater => [80, 140, 220],
+        Element::Oil => [90, 60, 40],
+        Element::Ethanol => [170, 200, 240],
+        Element::Acid => [150, 230, 40],
+        Element::Lava => [255, 100, 20],
+        Element::Mercury => [190, 195, 205],
+        Element::Stone => [128, 128, 128],
+        Element::Glass => [190, 225, 235],
+        Element::Wall => [90, 90, 95],
+        Element::Wood => [140, 90, 45],
+        Element::Plant => [60, 180, 60],
+        Element::Metal => [165, 170, 180],
+        Element::Wire => [200, 120, 60],
+        Element::Ice => [170, 220, 255],
+        Element::Coal => [40, 40, 40],
+        Element::Dirt => [120, 85, 55],
+        Element::WetDirt => [85, 60, 40],
+        Element::Seaweed => [30, 120, 70],
+        Element::Smoke => [110, 110, 115],
+        Element::Steam => [210, 215, 225],
+        Element::Gas => [170, 190, 120],
+        Element::ToxicGas => [120, 200, 60],
+        Element::Hydrogen => [200, 210, 255],
+        Element::Chlorine => [200, 220, 90],
+        Element::Fire => [255, 140, 30],
+        Element::Lightning => [255, 255, 160],
+        Element::Human => [240, 190, 150],
+        Element::Zombie => [110, 160, 90],
+        Element::HumanSpawner => [250, 210, 170],
+        Element::ZombieSpawner => [80, 120, 60],
+        Element::Animal => [170, 120, 80],
+        Element::Player => [80, 200, 255],
+        Element::Corpse => [120, 80, 80],
+        Element::Bone => [230, 225, 205],
+        Element::Rubble => [110, 105, 100],
+        Element::DilutedEthanol => [150, 190, 235],
+        Element::WeakAcid => [175, 225, 110],
+    }
+}
+
+/// Hidden state `overlay_rgba` can show.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Overlay {
+    /// Charge on wire, metal and water (their life), yellow.
+    Charge,
+    /// Life of every non-empty cell: green above zero, red below.
+    Life,
+    /// Cells that changed during the last step or since, colored by
+    /// cause: blue movement, orange reactions, white edits, purple
+    /// scheduled actions, cyan weather, green ports and edges. Needs
+    /// provenance tracking.
+    Updated,
+    /// Each CHUNK_SIZE block shaded by how many of its cells changed
+    /// during the last step or since. Needs provenance tracking.
+    ChunkActivity,
+}
+
+impl Overlay {
+    /// Overlay by id, for the C API, in declaration order.
+    pub(crate) fn from_id(id: i32) -> Option<Overlay> {
+        match id {
+            0 => Some(Overlay::Charge),
+            1 => Some(Overlay::Life),
+            2 => Some(Overlay::Updated),
+            3 => Some(Overlay::ChunkActivity),
+            _ => None,
+        }
+    }
+}
+
+/// Draw `overlay` for `world` into `out` (row-major RGBA, at least
+/// width*height*4 bytes): translucent where there is something to show,
+/// fully transparent elsewhere. Returns false if `out` is too small.
+pub fn overlay_rgba(world: &World, overlay: Overlay, out: &mut [u8]) -> bool {
+    let cells = &world.cells;
+    if out.len() < cells.len() * 4 {
+        return false;
+    }
+    // changed during the last step (recorded at tick - 1) or since
+    let recent = |i: usize| {
+        world
+            .provenance
+            .cells()
+            .get(i)
+            .filter(|p| p.cause != ChangeCause::Unchanged && p.tick + 1 >= world.tick)
+    };
+    let chunks = if overlay == Overlay::ChunkActivity {
+        chunk_activity(world, |i| recent(i).is_some())
+    } else {
+        Vec::new()
+    };
+    let chunks_across = (world.width + CHUNK_SIZE - 1) / CHUNK_SIZE;
+    for (i, (c, px)) in cells.iter().zip(out.chunks_exact_mut(4)).enumerate() {
+        let rgba = match overlay {
+            Overlay::Charge => match c.elem {
+                Element::Wire | Element::Metal | Element::Water | Element::SaltWater
+                    if c.life > 0 =>
+                {
+                    [255, 230, 0, (64 + c.life.min(12) * 16) as u8]
+                }
+                _ => [0; 4],
+            },
+            Overlay::Life if c.elem == Element::Empty || c.life == 0 => [0; 4],
+            Overlay::Life => {
+                let alpha = (32 + c.life.unsigned_abs().min(28) * 8) as u8;
+                if c.life > 0 {
+                    [0, 255, 0, alpha]
+                } else {
+                    [255, 0, 0, alpha]
+                }
+            }
+            Overlay::Updated => match recent(i).map(|p| p.cause) {
+                Some(ChangeCause::Movement) => [60, 120, 255, 160],
+                Some(ChangeCause::Reaction) => [255, 140, 0, 160],
+                Some(ChangeCause::Edit) => [255, 255, 255, 160],
+                Some(ChangeCause::Scheduled) => [180, 80, 255, 160],
+                Some(ChangeCause::Weather) => [0, 220, 255, 160],
+                Some(ChangeCause::Boundary) => [0, 220, 80, 160],
+                _ => [0; 4],
+            },
+            Overlay::ChunkActivity => {
+                let (x, y) = world.pos_of(i);
+                let changed = chunks[(y / CHUNK_SIZE * chunks_across + x / CHUNK_SIZE) as usize];
+                if changed == 0 {
+                    [0; 4]
+                } else {
+                    [255, 0, 0, (48 + changed.min(28) * 4) as u8]
+                }
+            }
+        };
+        px.copy_from_slice(&rgba);
+    }
+    true
+}
+
+/// Cells per CHUNK_SIZE block (row-major over blocks) for which `hit`
+/// holds.
+fn chunk_activity(world: &World, hit: impl Fn(usize) -> bool) -> Vec<u32> {
+    let across = (world.width + CHUNK_SIZE - 1) / CHUNK_SIZE;
+    let down = (world.height + CHUNK_SIZE - 1) / CHUNK_SIZE;
+    let mut counts = vec![0; (across * down).max(0) as usize];
+    for i in (0..world.cells.len()).filter(|&i| hit(i)) {
+        let (x, y) = world.pos_of(i);
+        counts[(y / CHUNK_SIZE * across + x / CHUNK_SIZE) as usize] += 1;
+    }
+    counts
+}
+
+/// Render `world` into `out` (row-major RGBA, at least width*height*4
+/// bytes), e.g. straight into a pixels frame. Same as
+/// `World::render_rgba_into`; returns false if `out` is too small.
+pub fn world_to_rgba(world: &World, palette: &Palette, out: &mut [u8]) -> bool {
+    world.render_rgba_into(out, palette)
+}
+
+impl World {
+    /// Render the world into a new RGBA buffer of width*height*4 bytes.
+    pub fn render_rgba(&self, palette: &Palette) -> Vec<u8> {
+        let mut out = vec![0; self.cells.len() * 4];
+        self.render_rgba_into(&mut out, palette);
+        out
+    }
+
+    /// Render into `out`, which must hold at least width*height*4 bytes.
+    /// Returns false (and writes nothing) if it is too small.
+    pub fn render_rgba_into(&self, out: &mut [u8], palette: &Palette) -> bool {
+        if out.len() < self.cells.len() * 4 {
+            return false;
+        }
+        for (i, px) in out.chunks_exact_mut(4).take(self.cells.len()).enumerate() {
+            px.copy_from_slice(&self.cell_rgba(i, palette));
+        }
+        true
+    }
+
+    /// Color of cell `i`, tinted as `palette` asks.
+    pub(crate) fn cell_rgba(&self, i: usize, palette: &Palette) -> [u8; 4] {
+        let c = self.cells[i];
+        let mut rgba = palette.rgba_of(c.elem, c.life);
+        if palette.tint != 0 && c.elem != Element::Empty {
+            tint(&mut rgba, c.elem, c.life, i, self.tick);
+        }
+        rgba
+    }
+
+    /// emission_of() for every cell, one byte each, row-major.
+    pub fn render_emission(&self) -> Vec<u8> {
+        let mut out = vec![0; self.cells.len()];
+        self.render_emission_into(&mut out);
+        out
+    }
+
+    /// Like `render_emission`, into `out` (at least width*height bytes).
+    /// Returns false (and writes nothing) if it is too small.
+    pub fn render_emission_into(&self, out: &mut [u8]) -> bool {
+        if out.len() < self.cells.len() {
+            return false;
+        }
+        for (c, px) in self.cells.iter().zip(out.iter_mut()) {
+            *px = emission_of(c.elem, c.life);
+        }
+        true
+    }
+}
+
+fn tint(rgba: &mut [u8; 4], elem: Element, life: i32, index: usize, tick: u64) {
+    let fade = match elem {
+        Element::Fire | Element::Lava | Element::Lightning => shade_of(elem, life, tick) as u32 + 1,
+        // gases count life down to zero; dim them as they thin out
+        _ if is_gas(elem) => {
+            let full = default_life(elem);
+            let left = life.clamp(0, full) as u32;
+            128 + left * 128 / full as u32
+        }
+        _ => 256,
+    };
+
+    // Stable grain from the cell index (doesn't flicker between frames).
+    let h = (index as u32).wrapping_mul(0x9E37_79B1) >> 28;
+    let grain = 240 + h; // 240..=255
+
+    for ch in rgba.iter_mut().take(3) {
+        *ch = (*ch as u32 * fade / 256 * grain / 255).min(255) as u8;
+    }
+}
@@ -0,0 +1,143 @@
+// PowderCore - deterministic replays
+//
+// A run of the engine is fully determined by:
+// - the world size and RNG seed it was created with, plus the settings
+//   that aren't cells: SimConfig, edge mode and weather
+// - every edit made from outside, and the tick it happened on
+// - how many times step() was called
+//
+// step() is single threaded and visits cells in a fixed order, so the RNG is
+// consumed in the same order on every platform. Some edits draw from the RNG
+// too (brush density and spray rolls), but playback applies them at the same
+// ticks in the same order, so they draw the same numbers. Recording the
+// three things above is therefore enough to re-simulate a run bit for bit.
+
+use crate::{Cell, EdgeMode, Element, SimConfig, Weather, World};
+
+/// One recorded edit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReplayInput {
+    Brush {
+        x: i32,
+        y: i32,
+        rad: i32,
+        elem: Element,
+    },
+    SetCell {
+        x: i32,
+        y: i32,
+        cell: Cell,
+    },
+    Clear,
+}
+
+/// Input log plus the starting conditions needed to play it back.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    width: i32,
+    height: i32,
+    seed: u64,
+    config: SimConfig,
+    edge_mode: EdgeMode,
+    weather: Weather,
+    inputs: Vec<(u64, ReplayInput)>,
+    ticks: u64,
+}
+
+impl Replay {
+    /// Start an empty recording for a world of this size and seed, with
+    /// the default config, edge mode and weather.
+    pub fn new(width: i32, height: i32, seed: u64) -> Self {
+        Replay {
+            width,
+            height,
+            seed,
+            config: SimConfig::default(),
+            edge_mode: EdgeMode::default(),
+            weather: Weather::default(),
+            inputs: Vec::new(),
+            ticks: 0,
+        }
+    }
+
+    /// Start an empty recording matching `world`'s size, seed, config, edge
+    /// mode and weather. `world` should be freshly created (and configured):
+    /// its cells and RNG position aren't recorded.
+    pub fn for_world(world: &World) -> Self {
+        Replay {
+            config: *world.config(),
+            edge_mode: world.edge_mode(),
+            weather: world.weather(),
+            ..Replay::new(world.width(), world.height(), world.rng_seed())
+        }
+    }
+
+    /// Fresh world matching the recording's starting conditions.
+    /// Record into this one (not a world that has already been edited).
+    pub fn new_world(&self) -> World {
+        let mut world = World::new(self.width, self.height, self.seed);
+        world.set_config(self.config);
+        world.set_edge_mode(self.edge_mode);
+        world.set_weather(self.weather);
+        world
+    }
+
+    /// RNG seed the recording starts from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Simulation config the recording runs under.
+    pub fn config(&self) -> &SimConfig {
+        &self.config
+    }
+
+    /// Number of recorded steps.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Recorded edits with the tick they were applied before.
+    pub fn inputs(&self) -> &[(u64, ReplayInput)] {
+        &self.inputs
+    }
+
+    /// Apply `input` to `world` and log it at the current tick.
+    pub fn record(&mut self, world: &mut World, input: ReplayInput) {
+        apply_input(world, input);
+        self.inputs.push((self.ticks, input));
+    }
+
+    /// Step `world` once and log the tick.
+    pub fn record_step(&mut self, world: &mut World) {
+        world.step();
+        self.ticks += 1;
+    }
+
+    /// Reset `world` to the starting conditions and re-simulate the run.
+    pub fn play_into(&self, world: &mut World) {
+        *world = self.new_world();
+        let mut next = 0;
+        for tick in 0..=self.ticks {
+            while next < self.inputs.len() && self.inputs[next].0 == tick {
+                apply_input(world, self.inputs[next].1);
+                next += 1;
+            }
+            if tick < self.ticks {
+                world.step();
+            }
+        }
+    }
+}
+
+fn apply_input(world: &mut World, input: ReplayInput) {
+    match input {
+        ReplayInput::Brush { x, y, rad, elem } => world.place_brush(x, y, rad, elem),
+        ReplayInput::SetCell { x, y, cell } => {
+            if let Some(c) = world.get_cell_mut(x, y) {
+                *c = cell;
+            }
+        }
+        ReplayInput::Clear => world.clear(),
+    }
+}
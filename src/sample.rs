@@ -0,0 +1,37 @@
+// PowderCore - cell sampling
+//
+// Everything a hover tooltip or eyedropper wants to know about one cell,
+// in one call.
+
+use crate::{category_of, color_of, name_of, Category, Element, World};
+
+/// Snapshot of one cell plus its element's metadata.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CellInfo {
+    pub x: i32,
+    pub y: i32,
+    pub elem: Element,
+    pub life: i32,
+    /// Display name, as `name_of`.
+    pub name: &'static str,
+    pub category: Category,
+    /// Palette index, as `color_of`.
+    pub color: u8,
+}
+
+impl World {
+    /// Look at the cell at (x, y). Out-of-bounds positions read as Empty,
+    /// like `get_cell`.
+    pub fn sample(&self, x: i32, y: i32) -> CellInfo {
+        let c = self.get_cell(x, y);
+        CellInfo {
+            x,
+            y,
+            elem: c.elem,
+            life: c.life,
+            name: name_of(c.elem),
+            category: category_of(c.elem),
+            color: color_of(c.elem, c.life),
+        }
+    }
+}
@@ -0,0 +1,341 @@
+// PowderCore - binary save format
+//
+// Layout (all integers little-endian):
+//
+//   magic     4 bytes   "PWDR"
+//   version   u8        SAVE_VERSION
+//   elements  u8        number of elements the writer knew about
+//   width     u32
+//   height    u32
+//   tick      varint    World::tick (version 2 and up)
+//   seed      u64       World::rng_seed (version 3 and up)
+//   rng       u64       World::rng_state (version 3 and up)
+//   runs      until width * height cells are covered:
+//               varint  run length (>= 1)
+//               u8      element id
+//               varint  life (zigzag encoded)
+//
+// Consecutive cells with the same element AND life are stored as one run,
+// so empty space (and big slabs of wall/stone) costs a few bytes per row.
+//
+// Forward compatibility: element ids this build doesn't know (written by a
+// newer engine with more elements) load as Empty instead of failing.
+// Files with a version newer than SAVE_VERSION are rejected. Version 1
+// files (no tick) load at tick 0, and files before version 3 (no RNG) with
+// the RNG freshly seeded from 0.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::{Cell, Element, World};
+
+const MAGIC: &[u8; 4] = b"PWDR";
+const SAVE_VERSION: u8 = 3;
+
+/// Upper bound on cells accepted by the loader (keeps hostile files from
+/// asking for absurd allocations).
+pub(crate) const MAX_CELLS: u64 = 1 << 28;
+
+/// Why a save buffer could not be loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    /// Buffer doesn't start with the PowderCore magic bytes.
+    BadMagic,
+    /// Save was written by a newer, incompatible format version.
+    UnsupportedVersion(u8),
+    /// Buffer ended before the whole grid was decoded.
+    Truncated,
+    /// Width/height are negative-looking or too large.
+    BadDimensions,
+    /// Run lengths don't add up to the grid size.
+    Corrupt,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::BadMagic => write!(f, "not a PowderCore save"),
+            LoadError::UnsupportedVersion(v) => {
+                write!(f, "unsupported save version {} (newer than this build)", v)
+            }
+            LoadError::Truncated => write!(f, "save data is truncated"),
+            LoadError::BadDimensions => write!(f, "save has invalid world dimensions"),
+            LoadError::Corrupt => write!(f, "save data is corrupt"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl World {
+    /// Serialize the grid into the compact versioned save format.
+    pub fn save_to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(16 + self.cells.len() / 8);
+        write_header(&mut out, MAGIC, SAVE_VERSION, self.width, self.height);
+        self.write_clock(&mut out);
+        encode_cells(&mut out, &self.cells);
+        out
+    }
+
+    /// Rebuild a world from bytes produced by `save_to_bytes`, RNG state
+    /// included, so it steps exactly like the world that was saved.
+    pub fn load_from_bytes(bytes: &[u8]) -> Result<World, LoadError> {
+        let mut r = Reader { bytes, pos: 0 };
+        let (version, width, height) = read_header(&mut r, MAGIC, SAVE_VERSION)?;
+        let clock = read_clock(&mut r, version)?;
+        let cells = decode_cells(&mut r, width as usize * height as usize)?;
+        Ok(World::from_save(width, height, clock, cells))
+    }
+
+    fn from_save(width: i32, height: i32, clock: Clock, cells: Vec<Cell>) -> World {
+        let mut world = World::new(width, height, clock.seed);
+        world.cells = cells;
+        world.tick = clock.tick;
+        if let Some(state) = clock.rng {
+            world.rng.state = state;
+        }
+        world.cells_changed();
+        world
+    }
+
+    /// Tick, seed and RNG state, as stored after the header.
+    fn write_clock(&self, out: &mut Vec<u8>) {
+        write_varint(out, self.tick);
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.extend_from_slice(&self.rng.state.to_le_bytes());
+    }
+
+    /// Stream the save format into `writer` a chunk of cells at a time,
+    /// without building the whole buffer first.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(STREAM_CHUNK);
+        write_header(&mut buf, MAGIC, SAVE_VERSION, self.width, self.height);
+        self.write_clock(&mut buf);
+        for chunk in self.cells.chunks(STREAM_CHUNK) {
+            encode_cells(&mut buf, chunk);
+            writer.write_all(&buf)?;
+            buf.clear();
+        }
+        writer.write_all(&buf)?;
+        writer.flush()
+    }
+
+    /// Read a save written by `write_to` (or `save_to_bytes`) from `reader`.
+    /// Reads exactly the save's bytes, one at a time, so wrap unbuffered
+    /// sources in a `BufReader`. Format errors come back as `InvalidData`.
+    pub fn read_from<R: Read>(reader: R) -> io::Result<World> {
+        let mut r = StreamReader { inner: reader, err: None };
+        let result = read_header(&mut r, MAGIC, SAVE_VERSION).and_then(|(v, w, h)| {
+            let clock = read_clock(&mut r, v)?;
+            Ok((w, h, clock, decode_cells(&mut r, w as usize * h as usize)?))
+        });
+        match result {
+            Ok((width, height, clock, cells)) => Ok(World::from_save(width, height, clock, cells)),
+            Err(e) => Err(match r.err {
+                Some(io_err) => io_err,
+                None => io::Error::new(io::ErrorKind::InvalidData, e),
+            }),
+        }
+    }
+}
+
+// ===== Encoding helpers =====
+
+/// Cells encoded per write when streaming.
+const STREAM_CHUNK: usize = 64 * 1024;
+
+/// Magic, version, element count and dimensions.
+pub(crate) fn write_header(
+    out: &mut Vec<u8>,
+    magic: &[u8; 4],
+    version: u8,
+    width: i32,
+    height: i32,
+) {
+    out.extend_from_slice(magic);
+    out.push(version);
+    out.push(Element::COUNT as u8);
+    out.extend_from_slice(&(width as u32).to_le_bytes());
+    out.extend_from_slice(&(height as u32).to_le_bytes());
+}
+
+/// Check magic and version (1 to `max_version`), returning the version and
+/// the stored dimensions.
+pub(crate) fn read_header<S: ByteSource>(
+    r: &mut S,
+    magic: &[u8; 4],
+    max_version: u8,
+) -> Result<(u8, i32, i32), LoadError> {
+    for &m in magic {
+        if r.u8()? != m {
+            return Err(LoadError::BadMagic);
+        }
+    }
+    let version = r.u8()?;
+    if version == 0 || version > max_version {
+        return Err(LoadError::UnsupportedVersion(version));
+    }
+    let _writer_elements = r.u8()?;
+    let (width, height) = read_dims(r)?;
+    Ok((version, width, height))
+}
+
+/// Time-related state stored between the header and the cells.
+struct Clock {
+    tick: u64,
+    seed: u64,
+    /// None for saves from before the RNG was stored.
+    rng: Option<u64>,
+}
+
+fn read_clock<S: ByteSource>(r: &mut S, version: u8) -> Result<Clock, LoadError> {
+    let tick = if version >= 2 { r.varint()? } else { 0 };
+    if version < 3 {
+        return Ok(Clock {
+            tick,
+            seed: 0,
+            rng: None,
+        });
+    }
+    Ok(Clock {
+        tick,
+        seed: r.u64()?,
+        rng: Some(r.u64()?),
+    })
+}
+
+/// Read width/height (u32 each) and check them against MAX_CELLS.
+pub(crate) fn read_dims<S: ByteSource>(r: &mut S) -> Result<(i32, i32), LoadError> {
+    let width = r.u32()?;
+    let height = r.u32()?;
+    if width > i32::MAX as u32 || height > i32::MAX as u32 {
+        return Err(LoadError::BadDimensions);
+    }
+    if width as u64 * height as u64 > MAX_CELLS {
+        return Err(LoadError::BadDimensions);
+    }
+    Ok((width as i32, height as i32))
+}
+
+/// Append `cells` as (run length, element id, life) runs.
+pub(crate) fn encode_cells(out: &mut Vec<u8>, cells: &[Cell]) {
+    let mut i = 0;
+    while i < cells.len() {
+        let c = cells[i];
+        let mut run = 1;
+        while i + run < cells.len() && cells[i + run] == c {
+            run += 1;
+        }
+        write_varint(out, run as u64);
+        out.push(c.elem as i32 as u8);
+        write_varint(out, zigzag(c.life));
+        i += run;
+    }
+}
+
+/// Decode exactly `total` cells worth of runs. Unknown ids become Empty.
+/// The buffer grows run by run rather than trusting `total` up front, so a
+/// short file claiming a huge grid fails before it allocates much.
+pub(crate) fn decode_cells<S: ByteSource>(r: &mut S, total: usize) -> Result<Vec<Cell>, LoadError> {
+    let mut cells = Vec::new();
+    while cells.len() < total {
+        let run = r.varint()?;
+        if run == 0 || run > (total - cells.len()) as u64 {
+            return Err(LoadError::Corrupt);
+        }
+        let id = r.u8()?;
+        let life = unzigzag(r.varint()?);
+        let cell = match Element::from_id(id as i32) {
+            Some(elem) => Cell { elem, life },
+            None => Cell::default(),
+        };
+        cells.resize(cells.len() + run as usize, cell);
+    }
+    Ok(cells)
+}
+
+pub(crate) fn zigzag(v: i32) -> u64 {
+    ((v << 1) ^ (v >> 31)) as u32 as u64
+}
+
+pub(crate) fn unzigzag(v: u64) -> i32 {
+    let v = v as u32;
+    ((v >> 1) as i32) ^ -((v & 1) as i32)
+}
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+/// Byte-at-a-time input shared by the slice and stream decoders.
+pub(crate) trait ByteSource {
+    fn u8(&mut self) -> Result<u8, LoadError>;
+
+    fn u32(&mut self) -> Result<u32, LoadError> {
+        let b = [self.u8()?, self.u8()?, self.u8()?, self.u8()?];
+        Ok(u32::from_le_bytes(b))
+    }
+
+    fn u64(&mut self) -> Result<u64, LoadError> {
+        let lo = self.u32()? as u64;
+        let hi = self.u32()? as u64;
+        Ok(lo | hi << 32)
+    }
+
+    fn varint(&mut self) -> Result<u64, LoadError> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.u8()?;
+            v |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(LoadError::Corrupt)
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    pub(crate) bytes: &'a [u8],
+    pub(crate) pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], LoadError> {
+        if self.bytes.len() - self.pos < n {
+            return Err(LoadError::Truncated);
+        }
+        let s = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(s)
+    }
+}
+
+impl ByteSource for Reader<'_> {
+    fn u8(&mut self) -> Result<u8, LoadError> {
+        Ok(self.take(1)?[0])
+    }
+}
+
+/// Adapts `Read`, keeping the underlying I/O error (if any) for the caller.
+struct StreamReader<R> {
+    inner: R,
+    err: Option<io::Error>,
+}
+
+impl<R: Read> ByteSource for StreamReader<R> {
+    fn u8(&mut self) -> Result<u8, LoadError> {
+        let mut b = [0u8; 1];
+        match self.inner.read_exact(&mut b) {
+            Ok(()) => Ok(b[0]),
+            Err(e) => {
+                self.err = Some(e);
+                Err(LoadError::Truncated)
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+// PowderCore - scheduled actions
+//
+// Scenario scripting inside the engine: queue an Action for a tick and
+// step() carries it out, so a level can erupt a volcano at tick 600 or send
+// a zombie wave every minute without the frontend counting frames.
+//
+// Actions due on tick N run at the start of the step() that takes the
+// world from tick N to N + 1 (so before any cell moves), in the order they
+// were scheduled. Actions for a tick already passed run on the next step.
+// Repeating actions go back in the queue `period` ticks after each run.
+// The schedule isn't saved with the world.
+
+use crate::{Element, ExplosionSpec, Weather, World, WorldEvent};
+
+/// Something a scenario can make happen at a given tick.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Paint a circle of `elem`, like `place_brush`.
+    Spawn {
+        x: i32,
+        y: i32,
+        rad: i32,
+        elem: Element,
+    },
+    /// Blast a circle into fire, smoke and gas, like detonated gunpowder.
+    Explode { x: i32, y: i32, radius: i32 },
+    /// Change the weather.
+    Weather { weather: Weather },
+}
+
+impl World {
+    /// Run `action` when the world reaches `tick` (see `World::tick`).
+    pub fn schedule(&mut self, tick: u64, action: Action) {
+        self.schedule.entry(tick).or_default().push((action, 0));
+    }
+
+    /// Run `action` at `first_tick` and every `period` ticks after that,
+    /// until the schedule is cleared. A period of 0 runs it once.
+    pub fn schedule_every(&mut self, first_tick: u64, period: u64, action: Action) {
+        self.schedule
+            .entry(first_tick)
+            .or_default()
+            .push((action, period));
+    }
+
+    /// Number of actions still waiting to run (repeating ones count once).
+    pub fn scheduled_count(&self) -> usize {
+        self.schedule.values().map(Vec::len).sum()
+    }
+
+    /// Drop every pending action.
+    pub fn clear_schedule(&mut self) {
+        self.schedule.clear();
+    }
+
+    /// Carry out everything due by the current tick.
+    pub(crate) fn run_schedule(&mut self) {
+        while let Some(entry) = self.schedule.first_entry() {
+            if *entry.key() > self.tick {
+                break;
+            }
+            for (action, period) in entry.remove() {
+                self.perform(action);
+                if period > 0 {
+                    self.schedule_every(self.tick + period, period, action);
+                }
+            }
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::Spawn { x, y, rad, elem } => {
+                self.place_brush(x, y, rad, elem);
+                self.emit(WorldEvent::ElementCreated { x, y, elem });
+            }
+            Action::Explode { x, y, radius } => {
+                let spec = ExplosionSpec {
+                    radius,
+                    ..ExplosionSpec::DEFAULT
+                };
+                self.detonate(x, y, spec);
+            }
+            Action::Weather { weather } => self.set_weather(weather),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+// PowderCore - actor senses
+//
+// Actors look for each other within a per-type square radius
+// (SimConfig::human_vision / zombie_vision / animal_vision), and only see
+// what's in line of sight: solids and powders block the view, while air,
+// gases, liquids, fire and other actors don't. Sight lines are traced with
+// World::raycast, so hiding behind a wall actually works.
+
+use crate::{category_of, Category, Element, World};
+
+fn blocks_sight(e: Element) -> bool {
+    matches!(category_of(e), Category::Solid | Category::Powder)
+}
+
+impl World {
+    /// Is there a clear line of sight from (x0, y0) to (x1, y1)? The cells
+    /// at either end don't count as blockers.
+    pub(crate) fn can_see(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> bool {
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let dist = dx.abs().max(dy.abs());
+        match self.raycast(x0, y0, dx, dy, |c| blocks_sight(c.elem)) {
+            Some((hx, hy, _)) => (hx - x0).abs().max((hy - y0).abs()) >= dist,
+            None => true,
+        }
+    }
+
+    /// First cell holding `target` within `r` of (x, y) that the actor
+    /// there can see, scanning row by row from the top left.
+    pub(crate) fn spot(&self, x: i32, y: i32, r: i32, target: Element) -> Option<(i32, i32)> {
+        for ry in -r..=r {
+            for rx in -r..=r {
+                let (nx, ny) = (x + rx, y + ry);
+                if !self.in_bounds(nx, ny) || self.cells[self.idx(nx, ny)].elem != target {
+                    continue;
+                }
+                if self.can_see(x, y, nx, ny) {
+                    return Some((nx, ny));
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,83 @@
+// PowderCore - human shelter building
+//
+// Humans gather loose Wood and Stone while things are quiet and, once a
+// zombie is in sight, spend their turns putting it back down as a wall
+// between themselves and the zombie. A human carries one cell of material
+// at a time (Actor::carrying).
+//
+// "Loose" means a block lying at the human's feet level with nothing on
+// top of it, so humans don't dig out the floor they stand on or pull the
+// bottom out of a wall.
+
+use crate::{is_gas, Element, World};
+
+/// Percent chance per tick that an idle human picks up a loose block next
+/// to it.
+const PICKUP_CHANCE: u32 = 10;
+
+fn material(e: Element) -> bool {
+    matches!(e, Element::Wood | Element::Stone)
+}
+
+fn open(e: Element) -> bool {
+    e == Element::Empty || is_gas(e)
+}
+
+impl World {
+    /// Is (x, y) in the world and free to move into?
+    pub(crate) fn open_at(&self, x: i32, y: i32) -> bool {
+        self.in_bounds(x, y) && open(self.cells[self.idx(x, y)].elem)
+    }
+
+    fn loose_at(&self, x: i32, y: i32) -> bool {
+        self.in_bounds(x, y)
+            && material(self.cells[self.idx(x, y)].elem)
+            && (!self.in_bounds(x, y - 1) || self.open_at(x, y - 1))
+    }
+
+    /// Let the human at (x, y) pick up or place building material.
+    /// `zombie` is the zombie it can see, if any. Returns true if that used
+    /// up its turn.
+    pub(crate) fn tend_shelter(&mut self, x: i32, y: i32, zombie: Option<(i32, i32)>) -> bool {
+        let idx0 = self.idx(x, y);
+        let carrying = match self.actor_mut(idx0) {
+            Some(a) => a.carrying,
+            None => return false,
+        };
+
+        match (carrying, zombie) {
+            (Some(elem), Some((zx, _))) => {
+                // wall off the side the zombie is on, stacking upwards
+                let side = if zx < x { -1 } else { 1 };
+                let spot = [(x + side, y), (x + side, y - 1)]
+                    .into_iter()
+                    .find(|&(bx, by)| self.open_at(bx, by));
+                let Some((bx, by)) = spot else {
+                    return false;
+                };
+                let i = self.idx(bx, by);
+                self.put(i, elem, 0);
+                if let Some(a) = self.actor_mut(idx0) {
+                    a.carrying = None;
+                }
+                true
+            }
+            (None, None) => {
+                let Some(bx) = [x - 1, x + 1].into_iter().find(|&bx| self.loose_at(bx, y)) else {
+                    return false;
+                };
+                if !self.rng.chance(PICKUP_CHANCE) {
+                    return false;
+                }
+                let i = self.idx(bx, y);
+                let elem = self.cells[i].elem;
+                self.put(i, Element::Empty, 0);
+                if let Some(a) = self.actor_mut(idx0) {
+                    a.carrying = Some(elem);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
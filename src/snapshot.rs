@@ -0,0 +1,73 @@
+// PowderCore - snapshots for undo / rewind
+//
+// A snapshot splits the grid into fixed-size chunks held behind Arc.
+// When a new snapshot is taken, every chunk that is unchanged since the
+// previous snapshot is shared instead of copied, so keeping a long undo
+// history of a mostly-static scene costs little more than one grid.
+
+use std::sync::Arc;
+
+use crate::{Cell, Rng, World};
+
+/// Cells per shared chunk.
+const CHUNK_CELLS: usize = 4096;
+
+/// Frozen copy of a world's grid, RNG state and tick.
+#[derive(Clone)]
+pub struct WorldSnapshot {
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    pub(crate) chunks: Vec<Arc<[Cell]>>,
+    rng: Rng,
+    tick: u64,
+}
+
+impl WorldSnapshot {
+    /// Width of the world when the snapshot was taken.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Height of the world when the snapshot was taken.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+}
+
+impl World {
+    /// Capture the current state. Chunks unchanged since the previous
+    /// snapshot are shared with it rather than copied.
+    pub fn snapshot(&mut self) -> WorldSnapshot {
+        let mut chunks = Vec::with_capacity(self.cells.len().div_ceil(CHUNK_CELLS));
+        for (i, cur) in self.cells.chunks(CHUNK_CELLS).enumerate() {
+            match self.snapshot_cache.get(i) {
+                Some(prev) if **prev == *cur => chunks.push(prev.clone()),
+                _ => chunks.push(Arc::from(cur)),
+            }
+        }
+        self.snapshot_cache = chunks.clone();
+
+        WorldSnapshot {
+            width: self.width,
+            height: self.height,
+            chunks,
+            rng: self.rng.clone(),
+            tick: self.tick,
+        }
+    }
+
+    /// Roll the world (size, cells, RNG and tick) back to `snap`.
+    pub fn restore(&mut self, snap: &WorldSnapshot) {
+        self.width = snap.width;
+        self.height = snap.height;
+        self.cells.clear();
+        for chunk in &snap.chunks {
+            self.cells.extend_from_slice(chunk);
+        }
+        self.rng = snap.rng.clone();
+        self.tick = snap.tick;
+        self.cells_changed();
+        self.clear_undo();
+        self.snapshot_cache = snap.chunks.clone();
+    }
+}
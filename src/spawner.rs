@@ -0,0 +1,51 @@
+// PowderCore - actor spawners
+//
+// HumanSpawner and ZombieSpawner are static solids that keep a survival
+// scenario going without the frontend: every SimConfig::spawner_interval
+// ticks each one drops an actor into a random empty neighbour, as long as
+// the world holds fewer than SimConfig::spawner_cap actors of that kind.
+// The spawner's life counts the ticks since it last fired.
+
+use crate::{default_life, Element, World};
+
+impl World {
+    pub(crate) fn step_spawner(&mut self, x: i32, y: i32, updated: &mut [bool]) {
+        let idx0 = self.idx(x, y);
+        updated[idx0] = true;
+
+        self.cells[idx0].life += 1;
+        if self.cells[idx0].life < self.config.spawner_interval {
+            return;
+        }
+        let actor = match self.cells[idx0].elem {
+            Element::ZombieSpawner => Element::Zombie,
+            _ => Element::Human,
+        };
+        if self.count(actor) >= self.config.spawner_cap {
+            return;
+        }
+
+        let mut free = [0usize; 8];
+        let mut n = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = (x + dx, y + dy);
+                if (dx, dy) == (0, 0) || !self.in_bounds(nx, ny) {
+                    continue;
+                }
+                let i = self.idx(nx, ny);
+                if self.cells[i].elem == Element::Empty {
+                    free[n] = i;
+                    n += 1;
+                }
+            }
+        }
+        if n == 0 {
+            return;
+        }
+        let i = free[self.rng.range_i32(0, n as i32 - 1) as usize];
+        self.put(i, actor, default_life(actor));
+        updated[i] = true;
+        self.cells[idx0].life = 0;
+    }
+}
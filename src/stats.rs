@@ -0,0 +1,85 @@
+// PowderCore - per-tick statistics
+//
+// Cheap counters for people profiling their scenes: how much of the grid
+// was actually simulated, how much moved, how much reacted and how long
+// the tick took. step() resets them when it starts and publishes them
+// when it finishes; last_step_stats() returns the most recent tick's.
+//
+// Counting is a few integer increments per cell, always on. The clock is
+// std::time::Instant, which wasm32-unknown-unknown doesn't have, so there
+// the duration is always zero.
+
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use crate::{ChangeCause, World};
+
+/// What the last step() did.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StepStats {
+    /// Cells handed to an update routine (Empty, Wall, frozen cells and
+    /// cells already moved this tick are skipped).
+    pub cells_visited: u64,
+    /// Swaps of two cells: falling, flowing, rising and creatures walking.
+    pub cells_moved: u64,
+    /// Cells that changed element in place: burning, melting, dissolving,
+    /// decaying, blast debris, plus anything scheduled, rained or piped in.
+    pub reactions: u64,
+    pub explosions: u64,
+    /// Wall-clock time spent in step() (zero on wasm32).
+    pub duration: Duration,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type Clock = Instant;
+#[cfg(target_arch = "wasm32")]
+type Clock = ();
+
+#[cfg(not(target_arch = "wasm32"))]
+fn start_clock() -> Clock {
+    Instant::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn elapsed(started: Clock) -> Duration {
+    started.elapsed()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn start_clock() -> Clock {}
+
+#[cfg(target_arch = "wasm32")]
+fn elapsed(_: Clock) -> Duration {
+    Duration::ZERO
+}
+
+impl World {
+    /// Statistics for the most recent step(); all zero before the first.
+    pub fn last_step_stats(&self) -> StepStats {
+        self.last_stats
+    }
+
+    /// Zero the counters and start the clock for a new tick.
+    pub(crate) fn begin_stats(&mut self) -> Clock {
+        self.stats = StepStats::default();
+        start_clock()
+    }
+
+    /// Publish the tick's counters.
+    pub(crate) fn end_stats(&mut self, started: Clock) {
+        self.stats.duration = elapsed(started);
+        self.last_stats = self.stats;
+    }
+
+    /// Swap two cells, counting the move and noting it in the provenance
+    /// record.
+    pub(crate) fn swap_cells(&mut self, a: usize, b: usize) {
+        self.cells.swap(a, b);
+        self.stats.cells_moved += 1;
+        self.provenance
+            .record_as(a, self.tick, ChangeCause::Movement);
+        self.provenance
+            .record_as(b, self.tick, ChangeCause::Movement);
+    }
+}
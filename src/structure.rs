@@ -0,0 +1,109 @@
+// PowderCore - structural integrity
+//
+// Optional cave-ins, off unless SimConfig::support_distance is set. With a
+// distance of N, stone, dirt (wet or dry) and wood must hang on to
+// something: at the start of every step() each such cell has to reach an
+// anchor through a 4-connected chain of structural cells with at most N
+// sideways or downward links (stacking straight up is free, so pillars
+// and walls of any height stand). A structural cell is an anchor when it
+//
+//   - is on the bottom row,
+//   - rests on a powder, creature or non-structural solid, or
+//   - touches a non-structural solid (wall, metal, glass, ...) on any side.
+//
+// Everything that can't reach an anchor crumbles into Rubble, a powder, and
+// falls. Tunnels wider than 2N cells cave in, and knocking out a pillar
+// brings down what it held up.
+//
+// The check is a breadth-first search over the whole grid, so it costs one
+// pass per tick while enabled.
+
+use std::collections::VecDeque;
+
+use crate::{category_of, Category, Element, World};
+
+/// Elements that need support.
+fn structural(e: Element) -> bool {
+    matches!(
+        e,
+        Element::Stone | Element::Dirt | Element::WetDirt | Element::Wood
+    )
+}
+
+/// Can something rest on `e`?
+fn bears(e: Element) -> bool {
+    match category_of(e) {
+        Category::Powder | Category::Creature => true,
+        Category::Solid => !structural(e),
+        _ => false,
+    }
+}
+
+impl World {
+    fn anchored(&self, x: i32, y: i32) -> bool {
+        if y == self.height - 1 {
+            return true;
+        }
+        if self.in_bounds(x, y + 1) && bears(self.cells[self.idx(x, y + 1)].elem) {
+            return true;
+        }
+        [(-1, 0), (1, 0), (0, -1)].iter().any(|&(dx, dy)| {
+            let (nx, ny) = (x + dx, y + dy);
+            self.in_bounds(nx, ny) && {
+                let e = self.cells[self.idx(nx, ny)].elem;
+                category_of(e) == Category::Solid && !structural(e)
+            }
+        })
+    }
+
+    /// Turn every structural cell too far from an anchor into Rubble.
+    pub(crate) fn collapse_unsupported(&mut self) {
+        let reach = self.config.support_distance;
+        if reach <= 0 {
+            return;
+        }
+        let (w, h) = (self.width, self.height);
+        let mut dist = vec![i32::MAX; self.cells.len()];
+        let mut queue = VecDeque::new();
+        for y in 0..h {
+            for x in 0..w {
+                let i = self.idx(x, y);
+                if structural(self.cells[i].elem) && self.anchored(x, y) {
+                    dist[i] = 0;
+                    queue.push_back((x, y));
+                }
+            }
+        }
+        // 0-1 breadth-first search: free links go to the front of the queue
+        while let Some((x, y)) = queue.pop_front() {
+            let d = dist[self.idx(x, y)];
+            for (dx, dy) in [(0, -1), (-1, 0), (1, 0), (0, 1)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if !self.in_bounds(nx, ny) {
+                    continue;
+                }
+                let ni = self.idx(nx, ny);
+                let nd = if dy < 0 { d } else { d + 1 };
+                if nd > reach || nd >= dist[ni] || !structural(self.cells[ni].elem) {
+                    continue;
+                }
+                dist[ni] = nd;
+                if dy < 0 {
+                    queue.push_front((nx, ny));
+                } else {
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        for y in 0..h {
+            for x in 0..w {
+                let i = self.idx(x, y);
+                let e = self.cells[i].elem;
+                if structural(e) && dist[i] == i32::MAX {
+                    self.put(i, Element::Rubble, 0);
+                    self.emit_converted(x, y, e, Element::Rubble);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// PowderCore - swimming and drowning
+//
+// Water, salt water, oil and ethanol are "swimmable": liquids that don't
+// hurt by themselves. Actors in them behave differently:
+//
+//   - a human with swimmable liquid around its head and no air swims up
+//     one cell per tick while there is liquid above, holding its breath;
+//     after SimConfig::human_breath ticks under it drowns. Breath comes
+//     back as soon as its head is out,
+//   - zombies don't breathe or float: they sink through swimmable liquid
+//     and walk along the bottom as if it were air.
+//
+// Humans still stand on the surface, since liquids only flow into empty
+// space and never displace an actor.
+
+use crate::{is_gas, is_hazard, is_liquid, Element, World, WorldEvent};
+
+fn swimmable(e: Element) -> bool {
+    is_liquid(e) && !is_hazard(e)
+}
+
+impl World {
+    /// Swim or drown for the human at (x, y). Returns true if that used up
+    /// its turn.
+    pub(crate) fn swim_human(&mut self, x: i32, y: i32, updated: &mut [bool]) -> bool {
+        let idx0 = self.idx(x, y);
+        let around = [(x, y - 1), (x - 1, y), (x + 1, y)].map(|(nx, ny)| {
+            if self.in_bounds(nx, ny) {
+                self.cells[self.idx(nx, ny)].elem
+            } else {
+                Element::Wall
+            }
+        });
+        // no air next to its head and water somewhere around it
+        let submerged = around.iter().any(|&e| swimmable(e))
+            && !around.iter().any(|&e| e == Element::Empty || is_gas(e));
+        let breath = self.config.human_breath;
+        let Some(a) = self.actor_mut(idx0) else {
+            return false;
+        };
+        if !submerged {
+            a.underwater = 0;
+            return false;
+        }
+        a.underwater += 1;
+        if a.underwater > breath {
+            self.put(idx0, Element::Corpse, 0);
+            self.emit(WorldEvent::HumanDied { x, y });
+            updated[idx0] = true;
+            return true;
+        }
+
+        if swimmable(around[0]) {
+            let idx_up = self.idx(x, y - 1);
+            self.move_actor(idx0, idx_up);
+            updated[idx_up] = true;
+            return true;
+        }
+        false
+    }
+
+    /// Sink the zombie at (x, y) through swimmable liquid below it.
+    /// Returns true if it moved.
+    pub(crate) fn sink_zombie(&mut self, x: i32, y: i32, updated: &mut [bool]) -> bool {
+        if !self.in_bounds(x, y + 1) {
+            return false;
+        }
+        let idx_b = self.idx(x, y + 1);
+        if !swimmable(self.cells[idx_b].elem) {
+            return false;
+        }
+        let idx0 = self.idx(x, y);
+        self.move_actor(idx0, idx_b);
+        updated[idx_b] = true;
+        true
+    }
+
+    /// try_walk for zombies, which also wade into swimmable liquid.
+    pub(crate) fn try_wade(&mut self, x: i32, y: i32, tx: i32, ty: i32) -> bool {
+        if self.try_walk(x, y, tx, ty) {
+            return true;
+        }
+        if !self.in_bounds(tx, ty) || !swimmable(self.cells[self.idx(tx, ty)].elem) {
+            return false;
+        }
+        let (from, to) = (self.idx(x, y), self.idx(tx, ty));
+        self.move_actor(from, to);
+        true
+    }
+}
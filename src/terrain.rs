@@ -0,0 +1,75 @@
+// PowderCore - heightmap terrain
+//
+// Turns a 1D heightmap (one height per column, 0.0 = bottom row, 1.0 = top
+// row) into stacked terrain bands. The map is resampled to the world width
+// with linear interpolation, so any resolution of source data works.
+
+use crate::{default_life, Cell, Element, World};
+
+/// One band of terrain, counted down from the surface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TerrainLayer {
+    pub elem: Element,
+    /// Thickness in cells. The last layer always extends to the bottom.
+    pub depth: i32,
+}
+
+impl TerrainLayer {
+    /// Plant surface, a few rows of dirt, stone base.
+    pub const DEFAULT: [TerrainLayer; 3] = [
+        TerrainLayer {
+            elem: Element::Plant,
+            depth: 1,
+        },
+        TerrainLayer {
+            elem: Element::Dirt,
+            depth: 6,
+        },
+        TerrainLayer {
+            elem: Element::Stone,
+            depth: 0,
+        },
+    ];
+}
+
+impl World {
+    /// Fill each column from its surface height down with `layers`.
+    /// Cells above the surface are left untouched.
+    pub fn fill_from_heightmap(&mut self, heights: &[f32], layers: &[TerrainLayer]) {
+        if heights.is_empty() || layers.is_empty() {
+            return;
+        }
+        for x in 0..self.width {
+            let h = sample(heights, x, self.width).clamp(0.0, 1.0);
+            let top = self.height - (h * self.height as f32).round() as i32;
+
+            let mut layer = 0;
+            let mut used = 0;
+            for y in top.max(0)..self.height {
+                while layer + 1 < layers.len() && used >= layers[layer].depth {
+                    layer += 1;
+                    used = 0;
+                }
+                let elem = layers[layer].elem;
+                let idx = self.idx(x, y);
+                self.cells[idx] = Cell {
+                    elem,
+                    life: default_life(elem),
+                };
+                used += 1;
+            }
+        }
+        self.cells_changed();
+    }
+}
+
+/// Height for column `x` of `width`, linearly interpolated from `heights`.
+fn sample(heights: &[f32], x: i32, width: i32) -> f32 {
+    if heights.len() == 1 || width <= 1 {
+        return heights[0];
+    }
+    let t = x as f32 / (width - 1) as f32 * (heights.len() - 1) as f32;
+    let i = (t.floor() as usize).min(heights.len() - 2);
+    let f = t - i as f32;
+    heights[i] * (1.0 - f) + heights[i + 1] * f
+}
@@ -0,0 +1,127 @@
+// PowderCore - text stamping
+//
+// Writes text into the world with a tiny built-in 3x5 bitmap font, for
+// signs, level titles and letter-shaped walls. Letters are case-folded;
+// characters the font doesn't have are drawn as '?'.
+
+use crate::{Element, Rect, World};
+
+/// Built-in bitmap fonts for `World::stamp_text`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Font {
+    /// 3x5 cells per character.
+    #[default]
+    Small,
+    /// The small font doubled: 6x10 cells per character.
+    Large,
+}
+
+impl Font {
+    fn scale(self) -> i32 {
+        match self {
+            Font::Small => 1,
+            Font::Large => 2,
+        }
+    }
+}
+
+const GLYPH_W: i32 = 3;
+const GLYPH_H: i32 = 5;
+
+/// Glyph rows top to bottom, bit 2 = leftmost column.
+#[rustfmt::skip]
+const GLYPHS: &[(char, [u8; 5])] = &[
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', [0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', [0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b110, 0b001, 0b010, 0b100, 0b111]),
+    ('3', [0b110, 0b001, 0b010, 0b001, 0b110]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b110, 0b001, 0b110]),
+    ('6', [0b011, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b010, 0b010, 0b010]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b110]),
+    (' ', [0b000, 0b000, 0b000, 0b000, 0b000]),
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    ('!', [0b010, 0b010, 0b010, 0b000, 0b010]),
+    ('?', [0b110, 0b001, 0b010, 0b000, 0b010]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+    ('=', [0b000, 0b111, 0b000, 0b111, 0b000]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]),
+    ('(', [0b001, 0b010, 0b010, 0b010, 0b001]),
+    (')', [0b100, 0b010, 0b010, 0b010, 0b100]),
+    ('\'', [0b010, 0b010, 0b000, 0b000, 0b000]),
+    ('_', [0b000, 0b000, 0b000, 0b000, 0b111]),
+];
+
+fn glyph(ch: char) -> [u8; 5] {
+    let ch = ch.to_ascii_uppercase();
+    GLYPHS
+        .iter()
+        .find(|(c, _)| *c == ch)
+        .or_else(|| GLYPHS.iter().find(|(c, _)| *c == '?'))
+        .map(|&(_, rows)| rows)
+        .unwrap_or_default()
+}
+
+impl World {
+    /// Write `text` in `elem` with its top-left corner at (x, y). Lines are
+    /// split on '\n', with a one-cell gap (times the font scale) between
+    /// characters and lines. Returns the rectangle the text covers, before clipping.
+    pub fn stamp_text(&mut self, x: i32, y: i32, text: &str, elem: Element, font: Font) -> Rect {
+        let s = font.scale();
+        let (advance, line_height) = ((GLYPH_W + 1) * s, (GLYPH_H + 1) * s);
+        let mut bounds = Rect::new(x, y, 0, 0);
+
+        for (row, line) in text.split('\n').enumerate() {
+            let top = y + row as i32 * line_height;
+            let mut left = x;
+            for ch in line.chars() {
+                for (gy, bits) in glyph(ch).iter().enumerate() {
+                    for gx in 0..GLYPH_W {
+                        if bits & (0b100 >> gx) == 0 {
+                            continue;
+                        }
+                        let px = left + gx * s;
+                        let py = top + gy as i32 * s;
+                        self.fill_rect(Rect::new(px, py, s, s), elem);
+                    }
+                }
+                left += advance;
+            }
+            let width = (left - x - s).max(0);
+            bounds.width = bounds.width.max(width);
+            bounds.height = top + GLYPH_H * s - y;
+        }
+        bounds
+    }
+}
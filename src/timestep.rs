@@ -0,0 +1,56 @@
+// PowderCore - fixed timestep driver
+//
+// Game engines hand out variable frame times; the simulation wants a fixed
+// tick rate so it behaves the same at 30 or 240 fps. FixedTimestep turns
+// elapsed seconds into a whole number of step() calls, carrying the
+// remainder to the next frame and capping catch-up after long stalls.
+
+use crate::World;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FixedTimestep {
+    step_secs: f64,
+    max_steps: u32,
+    acc: f64,
+}
+
+impl FixedTimestep {
+    /// Run at `ticks_per_sec`, doing at most `max_steps_per_frame` steps per
+    /// advance() so a long hitch doesn't stall the game further.
+    pub fn new(ticks_per_sec: f64, max_steps_per_frame: u32) -> Self {
+        FixedTimestep {
+            step_secs: 1.0 / ticks_per_sec.max(f64::MIN_POSITIVE),
+            max_steps: max_steps_per_frame.max(1),
+            acc: 0.0,
+        }
+    }
+
+    /// Add `elapsed_secs` of real time and step `world` as many times as it
+    /// covers. Returns the number of steps taken. Time beyond the per-frame
+    /// cap is dropped rather than queued.
+    pub fn advance(&mut self, world: &mut World, elapsed_secs: f64) -> u32 {
+        self.acc += elapsed_secs.max(0.0);
+        let mut n = 0;
+        while self.acc >= self.step_secs && n < self.max_steps {
+            world.step();
+            self.acc -= self.step_secs;
+            n += 1;
+        }
+        if n == self.max_steps {
+            self.acc = self.acc.min(self.step_secs);
+        }
+        n
+    }
+
+    /// How far into the next tick we are (0..1), for interpolated rendering.
+    pub fn alpha(&self) -> f64 {
+        (self.acc / self.step_secs).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for FixedTimestep {
+    /// 60 ticks per second, up to 5 steps per frame.
+    fn default() -> Self {
+        FixedTimestep::new(60.0, 5)
+    }
+}
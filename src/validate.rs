@@ -0,0 +1,113 @@
+// PowderCore - invariant checks
+//
+// Things that must hold between steps, checked on demand for tests and
+// while debugging rule changes:
+//
+//   - no negative life, except on elements that count their life down
+//     (fire, lightning, gases),
+//   - no Fire whose life has run out (step() turns it into smoke or air
+//     the tick it hits zero; frozen cells are exempt),
+//   - the actor table matches the grid: one entry per human, zombie,
+//     animal and player cell, none elsewhere, and its running totals agree,
+//   - the census counts match the grid.
+//
+// Cells written with get_cell_mut or set_cell skip the simulation's rules,
+// so a frontend storing odd lives can fail the first check on purpose.
+// The actor and census checks are skipped while those are waiting to be
+// rebuilt after such an edit.
+
+use std::fmt;
+
+use crate::{default_life, Element, World};
+
+/// A broken invariant found by `World::check_invariants`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// A cell whose element doesn't count down has negative life.
+    NegativeLife {
+        x: i32,
+        y: i32,
+        elem: Element,
+        life: i32,
+    },
+    /// A Fire cell with no life left survived a step.
+    SpentFire { x: i32, y: i32, life: i32 },
+    /// An actor cell with no entry in the actor table.
+    UntrackedActor { x: i32, y: i32, elem: Element },
+    /// An actor table entry on a cell that holds no actor.
+    StrayActor { x: i32, y: i32, id: u64 },
+    /// The actor table's running health or infection totals are off.
+    ActorTotals,
+    /// The census count for `elem` doesn't match the grid.
+    Census {
+        elem: Element,
+        counted: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::NegativeLife { x, y, elem, life } => {
+                write!(f, "{elem:?} at ({x}, {y}) has negative life {life}")
+            }
+            Violation::SpentFire { x, y, life } => {
+                write!(f, "fire at ({x}, {y}) survived with life {life}")
+            }
+            Violation::UntrackedActor { x, y, elem } => {
+                write!(f, "{elem:?} at ({x}, {y}) is missing from the actor table")
+            }
+            Violation::StrayActor { x, y, id } => {
+                write!(
+                    f,
+                    "actor {id} is recorded at ({x}, {y}), which holds no actor"
+                )
+            }
+            Violation::ActorTotals => write!(f, "actor table totals are out of sync"),
+            Violation::Census {
+                elem,
+                counted,
+                actual,
+            } => write!(
+                f,
+                "census counts {counted} {elem:?} but the grid has {actual}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Violation {}
+
+impl World {
+    /// Check the invariants above, returning the first one broken.
+    /// Walks the whole grid and actor table.
+    pub fn check_invariants(&self) -> Result<(), Violation> {
+        for (i, c) in self.cells.iter().enumerate() {
+            let (x, y) = self.pos_of(i);
+            if c.life < 0 && default_life(c.elem) == 0 {
+                return Err(Violation::NegativeLife {
+                    x,
+                    y,
+                    elem: c.elem,
+                    life: c.life,
+                });
+            }
+            if c.elem == Element::Fire && c.life <= 0 && !self.is_frozen(x, y) {
+                return Err(Violation::SpentFire { x, y, life: c.life });
+            }
+        }
+        self.check_actors()?;
+        self.check_census()
+    }
+
+    /// Panic with the first broken invariant. Debug builds only; a no-op
+    /// in release builds, so it can stay in a game loop.
+    pub fn validate(&self) {
+        if cfg!(debug_assertions) {
+            if let Err(v) = self.check_invariants() {
+                panic!("world invariant broken: {v}");
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+// PowderCore - viewports
+//
+// A Viewport is a read-only window onto a rectangle of the world, in local
+// coordinates: (0, 0) is the rectangle's top-left corner. Frontends that
+// scroll a camera over a world bigger than the screen take one per frame
+// with World::view(camera) and read or render it without offsetting every
+// coordinate themselves.
+//
+// The rectangle isn't clipped, so a camera can hang past the world's
+// edges; cells out there read as Empty and render in the palette's Empty
+// color. In wrap mode they read the wrapped-around cells instead, as
+// get_cell does.
+
+use crate::{Cell, Element, Palette, Rect, World};
+
+/// Borrowed view of `rect` within a world; see `World::view`.
+#[derive(Copy, Clone)]
+pub struct Viewport<'a> {
+    world: &'a World,
+    rect: Rect,
+}
+
+impl World {
+    /// View of `rect` (world coordinates). Negative sizes count as zero.
+    pub fn view(&self, rect: Rect) -> Viewport<'_> {
+        let rect = Rect::new(rect.x, rect.y, rect.width.max(0), rect.height.max(0));
+        Viewport { world: self, rect }
+    }
+}
+
+impl<'a> Viewport<'a> {
+    pub fn world(&self) -> &'a World {
+        self.world
+    }
+
+    /// The viewed rectangle in world coordinates.
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn width(&self) -> i32 {
+        self.rect.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.rect.height
+    }
+
+    /// Whether local (x, y) lies inside the viewport.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && x < self.rect.width && y >= 0 && y < self.rect.height
+    }
+
+    /// World coordinates of local (x, y).
+    pub fn to_world(&self, x: i32, y: i32) -> (i32, i32) {
+        (self.rect.x.saturating_add(x), self.rect.y.saturating_add(y))
+    }
+
+    /// Local coordinates of world (x, y); may lie outside the viewport.
+    pub fn to_local(&self, x: i32, y: i32) -> (i32, i32) {
+        (x.saturating_sub(self.rect.x), y.saturating_sub(self.rect.y))
+    }
+
+    /// Cell at local (x, y); Empty outside the viewport or the world.
+    pub fn get(&self, x: i32, y: i32) -> Cell {
+        if !self.contains(x, y) {
+            return Cell::default();
+        }
+        let (wx, wy) = self.to_world(x, y);
+        self.world.get_cell(wx, wy)
+    }
+
+    /// Every cell of the viewport with its local position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, i32, Cell)> + 'a {
+        let view = *self;
+        (0..view.rect.height)
+            .flat_map(move |y| (0..view.rect.width).map(move |x| (x, y, view.get(x, y))))
+    }
+
+    /// Render the viewport into a new RGBA buffer of width*height*4 bytes.
+    /// Cells are tinted as in `World::render_rgba`, so a scrolling view
+    /// matches a full render pixel for pixel.
+    pub fn render_rgba(&self, palette: &Palette) -> Vec<u8> {
+        let mut out = vec![0; self.rect.width as usize * self.rect.height as usize * 4];
+        self.render_rgba_into(&mut out, palette);
+        out
+    }
+
+    /// Render into `out`, which must hold at least width*height*4 bytes.
+    /// Returns false (and writes nothing) if it is too small.
+    pub fn render_rgba_into(&self, out: &mut [u8], palette: &Palette) -> bool {
+        let len = self.rect.width as usize * self.rect.height as usize;
+        if out.len() / 4 < len {
+            return false;
+        }
+        let world = self.world;
+        let outside = palette.rgba_of(Element::Empty, 0);
+        for (n, px) in out.chunks_exact_mut(4).take(len).enumerate() {
+            let x = (n % self.rect.width as usize) as i32;
+            let y = (n / self.rect.width as usize) as i32;
+            let (wx, wy) = self.to_world(x, y);
+            let rgba = if world.in_bounds(wx, wy) {
+                world.cell_rgba(world.idx(wx, wy), palette)
+            } else {
+                outside
+            };
+            px.copy_from_slice(&rgba);
+        }
+        true
+    }
+}
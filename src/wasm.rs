@@ -0,0 +1,150 @@
+// PowderCore - WebAssembly bindings (feature "wasm")
+//
+// The C ABI in ffi.rs leans on u64 seeds, out-pointers and by-value structs,
+// which map poorly onto wasm imports. This wraps World in a wasm-bindgen
+// class instead, so a page drives it like any other JS object:
+//
+//   import init, { World } from "./powdercore.js";
+//   await init();
+//   const w = new World(320, 180, 1n);
+//   w.placeBrush(160, 20, 4, World.elementId("Sand"));
+//   w.step();
+//   ctx.putImageData(new ImageData(w.renderRgba(), 320, 180), 0, 0);
+//
+// Build with `cargo build --target wasm32-unknown-unknown --release
+// --features wasm` and run wasm-bindgen (or wasm-pack) over the .wasm.
+//
+// cells() and renderRgba() are views straight into wasm memory, not
+// copies. A view is valid until the next call on the world, and any view
+// is detached when memory grows, so take fresh ones each frame.
+//
+// As in ffi.rs, every export runs inside catch_unwind and reports a panic
+// as a thrown Error rather than unwinding into the host (on targets that
+// unwind; wasm32 builds abort on panic by default).
+
+use std::panic::{self, AssertUnwindSafe};
+
+use js_sys::{Uint8Array, Uint8ClampedArray};
+use wasm_bindgen::prelude::*;
+
+use crate::{name_of, Cell, Element, Palette, World};
+
+/// Run `f`, turning a panic into a JS error.
+fn guard<T>(f: impl FnOnce() -> Result<T, JsError>) -> Result<T, JsError> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let what = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+        Err(JsError::new(&format!("engine panicked: {what}")))
+    })
+}
+
+/// World plus the RGBA buffer handed out by renderRgba().
+#[wasm_bindgen(js_name = World)]
+pub struct WasmWorld {
+    world: World,
+    rgba: Vec<u8>,
+}
+
+#[wasm_bindgen(js_class = World)]
+impl WasmWorld {
+    /// Empty world; `seed` is a BigInt.
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: i32, height: i32, seed: u64) -> Result<WasmWorld, JsError> {
+        guard(|| {
+            Ok(WasmWorld {
+                world: World::new(width, height, seed),
+                rgba: Vec::new(),
+            })
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> i32 {
+        self.world.width()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> i32 {
+        self.world.height()
+    }
+
+    pub fn step(&mut self) -> Result<(), JsError> {
+        guard(|| {
+            self.world.step();
+            Ok(())
+        })
+    }
+
+    pub fn clear(&mut self) -> Result<(), JsError> {
+        guard(|| {
+            self.world.clear();
+            Ok(())
+        })
+    }
+
+    /// Paint a disc of element `elem` (an id, see elementId). Throws on an
+    /// unknown id.
+    #[wasm_bindgen(js_name = placeBrush)]
+    pub fn place_brush(&mut self, cx: i32, cy: i32, rad: i32, elem: i32) -> Result<(), JsError> {
+        guard(|| {
+            let elem = Element::try_from(elem)
+                .map_err(|_| JsError::new(&format!("unknown element id {elem}")))?;
+            self.world.place_brush(cx, cy, rad, elem);
+            Ok(())
+        })
+    }
+
+    /// The cell grid as a Uint8Array view, row-major, 8 bytes per cell:
+    /// element id then life, each a little-endian int32. Wrap it as
+    /// `new Int32Array(v.buffer, v.byteOffset, v.length / 4)` to read
+    /// both as numbers.
+    pub fn cells(&self) -> Result<Uint8Array, JsError> {
+        guard(|| {
+            let cells = &self.world.cells;
+            let bytes = unsafe {
+                std::slice::from_raw_parts(
+                    cells.as_ptr().cast::<u8>(),
+                    std::mem::size_of_val::<[Cell]>(cells),
+                )
+            };
+            // Safety: the view is only valid until the world is next
+            // touched, as the module notes spell out.
+            Ok(unsafe { Uint8Array::view(bytes) })
+        })
+    }
+
+    /// RGBA image (width*height*4) in the default palette, as a view ready
+    /// for ImageData.
+    #[wasm_bindgen(js_name = renderRgba)]
+    pub fn render_rgba(&mut self) -> Result<Uint8ClampedArray, JsError> {
+        guard(|| {
+            self.rgba.resize(self.world.cells.len() * 4, 0);
+            self.world.render_rgba_into(&mut self.rgba, &Palette::DEFAULT);
+            Ok(unsafe { Uint8ClampedArray::view(&self.rgba) })
+        })
+    }
+
+    /// Number of element ids (0..count).
+    #[wasm_bindgen(js_name = elementCount)]
+    pub fn element_count() -> i32 {
+        Element::COUNT as i32
+    }
+
+    /// Display name of element `id`, or undefined for an unknown id.
+    #[wasm_bindgen(js_name = elementName)]
+    pub fn element_name(id: i32) -> Option<String> {
+        Element::try_from(id).ok().map(|e| name_of(e).to_string())
+    }
+
+    /// Id of the element named `name` (as elementName spells it), or -1.
+    #[wasm_bindgen(js_name = elementId)]
+    pub fn element_id(name: &str) -> i32 {
+        Element::ALL
+            .iter()
+            .find(|&&e| name_of(e) == name)
+            .map_or(-1, |&e| e as i32)
+    }
+}
@@ -0,0 +1,97 @@
+// PowderCore - region watchers
+//
+// Triggers for UIs and level scripts: "tell me when water reaches the
+// sensor", "open the door when the room is empty". A watcher remembers the
+// element of every cell in its rectangle; at the end of each step() it
+// compares, calls its callback once per cell whose element changed (in
+// row-major order) and remembers the new state. Edits made between steps
+// are picked up by the next step.
+//
+// Each watcher only looks at its own rectangle, so a small sensor costs a
+// few cells per tick however big the world is. Callbacks run inside
+// step() and can't touch the world; queue work up and do it afterwards.
+
+use crate::{Element, Rect, World};
+
+/// One watched cell changing element.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CellChange {
+    pub x: i32,
+    pub y: i32,
+    pub from: Element,
+    pub to: Element,
+}
+
+type Callback = Box<dyn FnMut(CellChange) + Send + Sync>;
+
+struct Watcher {
+    id: u64,
+    rect: Rect,
+    /// Elements in `rect` (clipped) as of the last check, row-major.
+    last: Vec<Element>,
+    callback: Callback,
+}
+
+#[derive(Default)]
+pub(crate) struct Watchers {
+    list: Vec<Watcher>,
+    next_id: u64,
+}
+
+impl World {
+    /// Call `callback` after each step for every cell in `rect` whose
+    /// element changed. Returns an id for `unwatch`.
+    pub fn watch_region<F>(&mut self, rect: Rect, callback: F) -> u64
+    where
+        F: FnMut(CellChange) + Send + Sync + 'static,
+    {
+        self.watchers.next_id += 1;
+        let id = self.watchers.next_id;
+        let last = self.region_elems(rect);
+        self.watchers.list.push(Watcher {
+            id,
+            rect,
+            last,
+            callback: Box::new(callback),
+        });
+        id
+    }
+
+    /// Stop the watcher `id`. Returns false if there is no such watcher.
+    pub fn unwatch(&mut self, id: u64) -> bool {
+        let before = self.watchers.list.len();
+        self.watchers.list.retain(|w| w.id != id);
+        self.watchers.list.len() != before
+    }
+
+    fn region_elems(&self, rect: Rect) -> Vec<Element> {
+        let r = rect.clip(self.width, self.height);
+        (r.y..r.y + r.height)
+            .flat_map(|y| (r.x..r.x + r.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.cells[self.idx(x, y)].elem)
+            .collect()
+    }
+
+    /// Report changes to every watcher. Called at the end of step().
+    pub(crate) fn notify_watchers(&mut self) {
+        if self.watchers.list.is_empty() {
+            return;
+        }
+        let mut list = std::mem::take(&mut self.watchers.list);
+        for w in &mut list {
+            let now = self.region_elems(w.rect);
+            // the world was resized: start over without reporting
+            if now.len() == w.last.len() {
+                let r = w.rect.clip(self.width, self.height);
+                for (i, (&from, &to)) in w.last.iter().zip(&now).enumerate() {
+                    if from != to {
+                        let (x, y) = (r.x + i as i32 % r.width, r.y + i as i32 / r.width);
+                        (w.callback)(CellChange { x, y, from, to });
+                    }
+                }
+            }
+            w.last = now;
+        }
+        self.watchers.list = list;
+    }
+}
@@ -0,0 +1,92 @@
+// PowderCore - weather
+//
+// Precipitation dropped in along the top edge at the end of every step(),
+// so frontends no longer paint rows of water each frame to make it rain.
+// Rain, snow and acid rain seed single drops into empty top-row cells;
+// meteors are rarer, a small clump of lava. Like the edge mode, the
+// weather is a property of the World and isn't saved with it.
+
+use crate::{default_life, Element, World, WorldEvent};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Weather {
+    #[default]
+    Clear,
+    /// Water drops; `intensity` is the percent chance per top-row cell per
+    /// tick.
+    Rain { intensity: u32 },
+    /// Snow flakes, same units as Rain.
+    Snow { intensity: u32 },
+    /// Acid drops, same units as Rain.
+    AcidRain { intensity: u32 },
+    /// Lava clumps; `intensity` is the percent chance per tick that one
+    /// falls.
+    Meteors { intensity: u32 },
+}
+
+impl Weather {
+    /// Weather from its C API kind (0 Clear, 1 Rain, 2 Snow, 3 AcidRain,
+    /// 4 Meteors).
+    pub(crate) fn from_id(kind: i32, intensity: u32) -> Option<Weather> {
+        match kind {
+            0 => Some(Weather::Clear),
+            1 => Some(Weather::Rain { intensity }),
+            2 => Some(Weather::Snow { intensity }),
+            3 => Some(Weather::AcidRain { intensity }),
+            4 => Some(Weather::Meteors { intensity }),
+            _ => None,
+        }
+    }
+}
+
+impl World {
+    pub fn weather(&self) -> Weather {
+        self.weather
+    }
+
+    pub fn set_weather(&mut self, weather: Weather) {
+        self.weather = weather;
+    }
+
+    /// Drop this tick's precipitation into the top row.
+    pub(crate) fn precipitate(&mut self) {
+        let (elem, intensity) = match self.weather {
+            Weather::Clear => return,
+            Weather::Rain { intensity } => (Element::Water, intensity),
+            Weather::Snow { intensity } => (Element::Snow, intensity),
+            Weather::AcidRain { intensity } => (Element::Acid, intensity),
+            Weather::Meteors { intensity } => {
+                if self.rng.chance(intensity) {
+                    let x = self.rng.range_i32(0, self.width - 1);
+                    self.meteor(x);
+                    self.emit(WorldEvent::ElementCreated {
+                        x,
+                        y: 0,
+                        elem: Element::Lava,
+                    });
+                }
+                return;
+            }
+        };
+        for x in 0..self.width {
+            let i = self.idx(x, 0);
+            if self.cells[i].elem == Element::Empty && self.rng.chance(intensity) {
+                self.put(i, elem, default_life(elem));
+            }
+        }
+    }
+
+    /// A small clump of lava entering at column `x`.
+    fn meteor(&mut self, x: i32) {
+        for (dx, dy) in [(-1, 0), (0, 0), (1, 0), (0, 1)] {
+            let (px, py) = (x + dx, dy);
+            if !self.in_bounds(px, py) {
+                continue;
+            }
+            let i = self.idx(px, py);
+            if self.cells[i].elem == Element::Empty {
+                self.put(i, Element::Lava, default_life(Element::Lava));
+            }
+        }
+    }
+}
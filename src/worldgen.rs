@@ -0,0 +1,329 @@
+// PowderCore - procedural terrain
+//
+// World::generate replaces the world's contents with a ready-made map:
+//
+//   1. rolling hills from two octaves of value noise, laid down as dirt
+//      over stone with fill_from_heightmap,
+//   2. caves carved out of the stone by a few rounds of cellular automaton
+//      smoothing over random noise (kept below a crust so the surface
+//      stays intact),
+//   3. pockets of coal, and of metal deeper down, inside the stone,
+//   4. lakes dug into the surface, filled with water and lined with sand,
+//   5. tufts of plant on exposed dirt.
+//
+// Generation uses its own RNG seeded from `seed`, so the same seed and
+// world size always give the same map and the simulation RNG is left
+// alone.
+//
+// Features (volcano, geyser) are stamped onto an existing world and keep
+// working afterwards through repeating actions on the world's schedule.
+
+use crate::{default_life, Action, Cell, Element, Rng, TerrainLayer, World};
+
+/// Spacing (in columns) of the coarse and fine noise control points.
+const HILL_SPACING: i32 = 32;
+const BUMP_SPACING: i32 = 8;
+/// Solid rows kept under the dirt before caves may open up.
+const CAVE_CRUST: i32 = 4;
+const CAVE_SMOOTHING: usize = 4;
+/// Ticks between volcano eruptions.
+const ERUPTION_PERIOD: u64 = 400;
+/// Ticks between geyser blasts.
+const GEYSER_PERIOD: u64 = 150;
+
+/// Knobs for `World::generate`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WorldGen {
+    /// Average ground height as a fraction of the world height.
+    pub surface: f32,
+    /// How far hills rise and fall around `surface`, same units.
+    pub hilliness: f32,
+    /// Rows of dirt over the stone.
+    pub dirt_depth: i32,
+    /// Percent of deep stone seeded as cave before smoothing; 0 disables
+    /// caves, around 40 gives connected tunnels.
+    pub caves: u32,
+    /// Number of lakes dug into the surface.
+    pub lakes: u32,
+    /// Number of coal/metal pockets in the stone.
+    pub ore_pockets: u32,
+    /// Percent of open dirt columns that grow a plant.
+    pub plants: u32,
+}
+
+impl WorldGen {
+    pub const DEFAULT: WorldGen = WorldGen {
+        surface: 0.55,
+        hilliness: 0.12,
+        dirt_depth: 5,
+        caves: 40,
+        lakes: 2,
+        ore_pockets: 12,
+        plants: 30,
+    };
+}
+
+impl Default for WorldGen {
+    fn default() -> Self {
+        WorldGen::DEFAULT
+    }
+}
+
+impl World {
+    /// Clear the world and fill it with terrain generated from `seed`.
+    pub fn generate(&mut self, seed: u64, gen: &WorldGen) {
+        self.clear();
+        if self.width <= 0 || self.height <= 0 {
+            return;
+        }
+        let mut rng = Rng::new(seed);
+
+        let heights = heightmap(&mut rng, self.width, gen);
+        let layers = [
+            TerrainLayer {
+                elem: Element::Dirt,
+                depth: gen.dirt_depth.max(0),
+            },
+            TerrainLayer {
+                elem: Element::Stone,
+                depth: 0,
+            },
+        ];
+        self.fill_from_heightmap(&heights, &layers);
+        let tops: Vec<i32> = (0..self.width).map(|x| self.surface_at(x)).collect();
+
+        self.carve_caves(&mut rng, &tops, gen);
+        self.place_ores(&mut rng, &tops, gen);
+        self.dig_lakes(&mut rng, &tops, gen);
+        self.grow_plants(&mut rng, gen);
+        self.cells_changed();
+    }
+
+    /// Topmost non-empty row in column `x` (height if the column is empty).
+    fn surface_at(&self, x: i32) -> i32 {
+        (0..self.height)
+            .find(|&y| self.cells[self.idx(x, y)].elem != Element::Empty)
+            .unwrap_or(self.height)
+    }
+
+    fn set_generated(&mut self, x: i32, y: i32, elem: Element) {
+        let i = self.idx(x, y);
+        self.cells[i] = Cell {
+            elem,
+            life: default_life(elem),
+        };
+    }
+
+    fn carve_caves(&mut self, rng: &mut Rng, tops: &[i32], gen: &WorldGen) {
+        if gen.caves == 0 {
+            return;
+        }
+        let (w, h) = (self.width, self.height);
+        let floor = |x: i32| tops[x as usize] + gen.dirt_depth.max(0) + CAVE_CRUST;
+        let carvable = |x: i32, y: i32| y >= floor(x) && y < h - 1;
+
+        let mut solid: Vec<bool> = (0..w * h)
+            .map(|i| !carvable(i % w, i / w) || !rng.chance(gen.caves))
+            .collect();
+        for _ in 0..CAVE_SMOOTHING {
+            let prev = solid.clone();
+            let solid_at =
+                |x: i32, y: i32| x < 0 || y < 0 || x >= w || y >= h || prev[(y * w + x) as usize];
+            for y in 0..h {
+                for x in 0..w {
+                    if !carvable(x, y) {
+                        continue;
+                    }
+                    let walls = (-1..=1)
+                        .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                        .filter(|&(dx, dy)| (dx, dy) != (0, 0) && solid_at(x + dx, y + dy))
+                        .count();
+                    solid[(y * w + x) as usize] = walls >= 5;
+                }
+            }
+        }
+        for y in 0..h {
+            for x in 0..w {
+                if !solid[(y * w + x) as usize] {
+                    self.set_generated(x, y, Element::Empty);
+                }
+            }
+        }
+    }
+
+    fn place_ores(&mut self, rng: &mut Rng, tops: &[i32], gen: &WorldGen) {
+        for _ in 0..gen.ore_pockets {
+            let x = rng.range_i32(0, self.width - 1);
+            let top = tops[x as usize] + gen.dirt_depth.max(0);
+            if top >= self.height - 1 {
+                continue;
+            }
+            let y = rng.range_i32(top, self.height - 1);
+            let deep = y > (top + self.height) / 2;
+            let elem = if deep && rng.chance(50) {
+                Element::Metal
+            } else {
+                Element::Coal
+            };
+            let r = rng.range_i32(1, 3);
+            for py in y - r..=y + r {
+                for px in x - r..=x + r {
+                    let (dx, dy) = (px - x, py - y);
+                    if dx * dx + dy * dy <= r * r
+                        && self.in_bounds(px, py)
+                        && self.cells[self.idx(px, py)].elem == Element::Stone
+                    {
+                        self.set_generated(px, py, elem);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Dig a bowl into the surface and fill it with water up to the lower
+    /// of its two rims, so it doesn't spill.
+    fn dig_lakes(&mut self, rng: &mut Rng, tops: &[i32], gen: &WorldGen) {
+        let w = self.width;
+        for _ in 0..gen.lakes {
+            let rx = rng.range_i32(6, 16).min(w / 4);
+            if rx < 2 {
+                return;
+            }
+            let cx = rng.range_i32(rx, w - 1 - rx);
+            let level = tops[(cx - rx) as usize].max(tops[(cx + rx) as usize]);
+            let depth = rng.range_i32(3, 6);
+            for x in cx - rx + 1..cx + rx {
+                let f = (x - cx) as f32 / rx as f32;
+                let bottom = level + (depth as f32 * (1.0 - f * f).sqrt()).round() as i32;
+                let bottom = bottom.min(self.height - 2);
+                for y in tops[x as usize].min(level)..=bottom {
+                    let elem = if y >= level {
+                        Element::Water
+                    } else {
+                        Element::Empty
+                    };
+                    self.set_generated(x, y, elem);
+                }
+                if self.cells[self.idx(x, bottom + 1)].elem != Element::Empty {
+                    self.set_generated(x, bottom + 1, Element::Sand);
+                }
+            }
+        }
+    }
+
+    fn grow_plants(&mut self, rng: &mut Rng, gen: &WorldGen) {
+        for x in 0..self.width {
+            let top = self.surface_at(x);
+            if top < 1
+                || top >= self.height
+                || self.cells[self.idx(x, top)].elem != Element::Dirt
+                || !rng.chance(gen.plants)
+            {
+                continue;
+            }
+            let tall = rng.range_i32(1, 3).min(top);
+            for y in top - tall..top {
+                self.set_generated(x, y, Element::Plant);
+            }
+        }
+    }
+}
+
+/// Build a stone cone `size` cells tall standing on row `base_y`, centred
+/// on column `x`, with a lava chamber in its base and a vent up to a flat
+/// crater. Every 400 ticks the chamber refills and lava and
+/// smoke burst out over the crater.
+pub fn volcano(world: &mut World, x: i32, base_y: i32, size: i32) {
+    let size = size.max(5);
+    let peak = base_y - size + 1;
+    let crater = peak + size / 5;
+    for y in crater..=base_y {
+        let half = y - peak;
+        for px in x - half..=x + half {
+            world.paint(px, y, Element::Stone);
+        }
+    }
+    let r = (size / 4).max(1);
+    let chamber = base_y - r;
+    for py in chamber - r..=chamber + r {
+        for px in x - r..=x + r {
+            let (dx, dy) = (px - x, py - chamber);
+            if dx * dx + dy * dy <= r * r {
+                world.paint(px, py, Element::Lava);
+            }
+        }
+    }
+    for y in crater..chamber - r {
+        world.paint(x, y, Element::Empty);
+    }
+
+    let spout = (size / 8).max(1);
+    let first = world.tick() + ERUPTION_PERIOD;
+    for (y, rad, elem) in [
+        (chamber, r, Element::Lava),
+        (crater - spout - 1, spout, Element::Lava),
+        (crater - 3 * spout - 3, spout + 1, Element::Smoke),
+    ] {
+        let action = Action::Spawn { x, y, rad, elem };
+        world.schedule_every(first, ERUPTION_PERIOD, action);
+    }
+}
+
+/// Sink a stone-lined shaft `size` cells deep below (x, base_y), the
+/// geyser's mouth. Every 150 ticks the shaft fills with steam and
+/// a burst of water is thrown up over the mouth.
+pub fn geyser(world: &mut World, x: i32, base_y: i32, size: i32) {
+    let size = size.max(2);
+    for y in base_y..=base_y + size {
+        world.paint(x - 1, y, Element::Stone);
+        world.paint(x, y, Element::Empty);
+        world.paint(x + 1, y, Element::Stone);
+    }
+    world.paint(x, base_y + size + 1, Element::Stone);
+
+    let first = world.tick() + GEYSER_PERIOD;
+    for y in base_y..=base_y + size {
+        let action = Action::Spawn {
+            x,
+            y,
+            rad: 0,
+            elem: Element::Steam,
+        };
+        world.schedule_every(first, GEYSER_PERIOD, action);
+    }
+    let burst = Action::Spawn {
+        x,
+        y: base_y - size / 2 - 2,
+        rad: (size / 4).max(1),
+        elem: Element::Water,
+    };
+    world.schedule_every(first, GEYSER_PERIOD, burst);
+}
+
+/// Surface height (fraction of the world height) for each column: a
+/// coarse layer of hills plus finer bumps at half the amplitude.
+fn heightmap(rng: &mut Rng, width: i32, gen: &WorldGen) -> Vec<f32> {
+    let hills = noise(rng, width, HILL_SPACING);
+    let bumps = noise(rng, width, BUMP_SPACING);
+    hills
+        .iter()
+        .zip(&bumps)
+        .map(|(a, b)| (gen.surface + gen.hilliness * (a + 0.5 * b) / 1.5).clamp(0.05, 0.95))
+        .collect()
+}
+
+/// Smoothly interpolated random values in -1..=1, one per column, with a
+/// control point every `spacing` columns.
+fn noise(rng: &mut Rng, width: i32, spacing: i32) -> Vec<f32> {
+    let points: Vec<f32> = (0..=width / spacing + 1)
+        .map(|_| rng.range_i32(-1000, 1000) as f32 / 1000.0)
+        .collect();
+    (0..width)
+        .map(|x| {
+            let i = (x / spacing) as usize;
+            let t = (x % spacing) as f32 / spacing as f32;
+            let t = t * t * (3.0 - 2.0 * t);
+            points[i] * (1.0 - t) + points[i + 1] * t
+        })
+        .collect()
+}
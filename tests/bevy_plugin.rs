@@ -0,0 +1,71 @@
+// PowderPlugin in a headless Bevy app: no window or renderer, just the
+// image assets and a hand-driven clock, so `cargo test --features bevy`
+// can check that it steps the world and keeps the texture in sync.
+#![cfg(feature = "bevy")]
+
+use std::time::Duration;
+
+use bevy_app::App;
+use bevy_asset::Assets;
+use bevy_image::Image;
+use bevy_time::Time;
+use powdercore::{Element, Palette, PowderPlugin, PowderTexture, PowderWorld};
+
+fn headless_app() -> App {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<Assets<Image>>()
+        .add_plugins(PowderPlugin::new(16, 8, 1));
+    app.finish();
+    app
+}
+
+fn frame(app: &mut App, secs: f64) {
+    let mut time = app.world_mut().resource_mut::<Time>();
+    time.advance_by(Duration::from_secs_f64(secs));
+    app.update();
+}
+
+fn texture(app: &App) -> Image {
+    let handle = &app.world().resource::<PowderTexture>().0;
+    app.world()
+        .resource::<Assets<Image>>()
+        .get(handle)
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn plugin_steps_at_fixed_rate_and_draws_the_grid() {
+    let mut app = headless_app();
+    assert_eq!(texture(&app).texture_descriptor.size.width, 16);
+    assert_eq!(texture(&app).texture_descriptor.size.height, 8);
+
+    let mut powder = app.world_mut().resource_mut::<PowderWorld>();
+    powder.world.place_brush(8, 1, 1, Element::Sand);
+    frame(&mut app, 0.1);
+
+    let powder = app.world().resource::<PowderWorld>();
+    assert_eq!(powder.world.tick(), 5); // 60 ticks/s, capped at 5 per frame
+    assert_eq!(
+        texture(&app).data,
+        powder.world.render_rgba(&Palette::DEFAULT)
+    );
+
+    // less than a tick steps nothing
+    let mut app = headless_app();
+    frame(&mut app, 0.01);
+    assert_eq!(app.world().resource::<PowderWorld>().world.tick(), 0);
+}
+
+#[test]
+fn texture_follows_a_resize() {
+    let mut app = headless_app();
+    let mut powder = app.world_mut().resource_mut::<PowderWorld>();
+    powder.world.resize(4, 3);
+    frame(&mut app, 0.0);
+    let image = texture(&app);
+    assert_eq!(image.texture_descriptor.size.width, 4);
+    assert_eq!(image.texture_descriptor.size.height, 3);
+    assert_eq!(image.data.len(), 4 * 3 * 4);
+}
@@ -0,0 +1,241 @@
+// Bit-exact determinism of the simulation and its RNG (see the notes on Rng
+// in lib.rs). The golden values pin the LCG and the stepping order: if they
+// change, saves and replays from earlier versions no longer reproduce.
+
+use powdercore::{
+    EdgeMode, Element, LockstepSession, NetCommand, NetFrame, Rect, Replay, ReplayInput, SimConfig,
+    Weather, World,
+};
+
+const SEED: u64 = 0x5eed_1234;
+
+/// A busy little scene that exercises most of the RNG-driven rules.
+fn scene() -> World {
+    let mut w = World::new(64, 48, SEED);
+    w.fill_rect(Rect::new(0, 44, 64, 4), Element::Stone);
+    w.fill_rect(Rect::new(4, 30, 20, 6), Element::Water);
+    w.fill_rect(Rect::new(30, 30, 6, 6), Element::Gunpowder);
+    w.fill_rect(Rect::new(40, 36, 16, 8), Element::Wood);
+    w.place_brush(12, 4, 4, Element::Sand);
+    w.place_brush(34, 26, 2, Element::Fire);
+    w.place_brush(48, 30, 3, Element::Lava);
+    w.place_brush(56, 4, 3, Element::Acid);
+    w.place_brush(20, 10, 3, Element::Oil);
+    w
+}
+
+fn run(w: &mut World, steps: u32) {
+    for _ in 0..steps {
+        w.step();
+    }
+}
+
+#[test]
+fn golden_values() {
+    let mut w = scene();
+    run(&mut w, 300);
+    assert_eq!(w.state_hash(), 0xdd36ac84ce938e6d);
+    assert_eq!(w.rng_state(), 0x199bf25f622b15eb);
+}
+
+#[test]
+fn same_seed_same_world() {
+    let (mut a, mut b) = (scene(), scene());
+    for _ in 0..100 {
+        a.step();
+        b.step();
+        assert_eq!(a.state_hash(), b.state_hash());
+    }
+    assert_eq!(a.cells(), b.cells());
+}
+
+#[test]
+fn seed_accessors() {
+    let w = World::new(8, 8, 42);
+    assert_eq!(w.rng_seed(), 42);
+    assert_eq!(World::new(8, 8, 0).rng_seed(), 0);
+
+    let loaded = World::load_from_bytes(&w.save_to_bytes()).unwrap();
+    assert_eq!(loaded.rng_seed(), 42);
+    assert_eq!(loaded.rng_state(), w.rng_state());
+}
+
+#[test]
+fn truncated_save_claiming_a_huge_grid_fails() {
+    let mut bytes = World::new(4, 4, 1).save_to_bytes();
+    // 16384 x 16384 is within MAX_CELLS, but the runs only cover 16 cells
+    bytes[6..10].copy_from_slice(&16384u32.to_le_bytes());
+    bytes[10..14].copy_from_slice(&16384u32.to_le_bytes());
+    assert!(World::load_from_bytes(&bytes).is_err());
+    assert!(World::read_from(&bytes[..]).is_err());
+}
+
+#[test]
+fn replay_keeps_config_edges_and_weather() {
+    let mut w = World::new(48, 32, SEED);
+    w.set_config(SimConfig {
+        reactivity: 200,
+        ..SimConfig::default()
+    });
+    w.set_edge_mode(EdgeMode::Wrap);
+    w.set_weather(Weather::AcidRain { intensity: 20 });
+    let mut replay = Replay::for_world(&w);
+    for tick in 0..120 {
+        if tick % 30 == 0 {
+            let input = ReplayInput::Brush {
+                x: tick / 4,
+                y: 20,
+                rad: 3,
+                elem: Element::Wood,
+            };
+            replay.record(&mut w, input);
+        }
+        replay.record_step(&mut w);
+    }
+
+    let mut played = World::new(1, 1, 0);
+    replay.play_into(&mut played);
+    assert_eq!(played.edge_mode(), EdgeMode::Wrap);
+    assert_eq!(played.state_hash(), w.state_hash());
+}
+
+#[test]
+fn save_resumes_identically() {
+    let mut a = scene();
+    run(&mut a, 50);
+    let mut b = World::load_from_bytes(&a.save_to_bytes()).unwrap();
+    assert_eq!(b.state_hash(), a.state_hash());
+    assert_eq!(b.tick(), a.tick());
+    run(&mut a, 100);
+    run(&mut b, 100);
+    assert_eq!(b.state_hash(), a.state_hash());
+}
+
+#[test]
+fn streamed_save_resumes_identically() {
+    let mut a = scene();
+    run(&mut a, 50);
+    let mut buf = Vec::new();
+    a.write_to(&mut buf).unwrap();
+    let mut b = World::read_from(buf.as_slice()).unwrap();
+    run(&mut a, 100);
+    run(&mut b, 100);
+    assert_eq!(b.state_hash(), a.state_hash());
+}
+
+#[test]
+fn snapshot_resumes_identically() {
+    let mut w = scene();
+    run(&mut w, 50);
+    let snap = w.snapshot();
+    run(&mut w, 100);
+    let expected = w.state_hash();
+    w.restore(&snap);
+    run(&mut w, 100);
+    assert_eq!(w.state_hash(), expected);
+}
+
+#[test]
+fn set_rng_state_continues_sequence() {
+    let mut a = scene();
+    run(&mut a, 50);
+
+    // same cells, different seed: only the RNG state differs
+    let mut b = World::new(a.width(), a.height(), 7);
+    for y in 0..a.height() {
+        for x in 0..a.width() {
+            *b.get_cell_mut(x, y).unwrap() = a.get_cell(x, y);
+        }
+    }
+    assert_ne!(b.state_hash(), a.state_hash());
+    b.set_rng_state(a.rng_state());
+    assert_eq!(b.state_hash(), a.state_hash());
+
+    run(&mut a, 100);
+    run(&mut b, 100);
+    assert_eq!(b.state_hash(), a.state_hash());
+}
+
+fn with_streams(mut w: World) -> World {
+    w.set_config(SimConfig {
+        rng_streams: true,
+        ..SimConfig::DEFAULT
+    });
+    w
+}
+
+#[test]
+fn rng_streams_golden_values() {
+    let mut w = with_streams(scene());
+    run(&mut w, 300);
+    assert_eq!(w.state_hash(), 0x5817aed39fbbe0a1);
+    assert_eq!(w.rng_state(), 0x9bb56820f3b952ec);
+}
+
+/// Two worlds split by a wall, the same on the left and different on the
+/// right. Returns whether the left halves still match after `steps`.
+fn left_halves_match(streams: bool, steps: u32) -> bool {
+    let build = |right: Element| {
+        let mut w = scene();
+        if streams {
+            w = with_streams(w);
+        }
+        w.fill_rect(Rect::new(31, 0, 3, 48), Element::Wall);
+        w.fill_rect(Rect::new(34, 0, 30, 44), Element::Empty);
+        w.fill_rect(Rect::new(36, 10, 24, 20), right);
+        w
+    };
+    let (mut a, mut b) = (build(Element::Sand), build(Element::Water));
+    run(&mut a, steps);
+    run(&mut b, steps);
+    (0..48).all(|y| (0..31).all(|x| a.get_cell(x, y) == b.get_cell(x, y)))
+}
+
+#[test]
+fn rng_streams_are_local() {
+    assert!(left_halves_match(true, 200));
+    // the shared sequence couples them
+    assert!(!left_halves_match(false, 200));
+}
+
+#[test]
+fn lockstep_peers_stay_identical() {
+    let (mut wa, mut wb) = (scene(), scene());
+    let mut a = LockstepSession::new(&wa, 0, 2, 3);
+    let mut b = LockstepSession::new(&wb, 1, 2, 3);
+    // b's frames reach a two ticks late, inside the input delay
+    let mut in_flight = Vec::new();
+    for t in 0..200 {
+        if t % 9 == 0 {
+            a.queue(NetCommand::brush(t % 60, 2, 2, Element::Sand));
+        }
+        if t % 13 == 0 {
+            b.queue(NetCommand::brush(40, 2, 1, Element::Water));
+        }
+        let to_b = NetFrame::from_bytes(&a.local_frame().to_bytes()).unwrap();
+        assert!(b.receive(to_b));
+        in_flight.push(b.local_frame().to_bytes());
+        if in_flight.len() > 2 {
+            assert!(a.receive(NetFrame::from_bytes(&in_flight.remove(0)).unwrap()));
+        }
+        assert!(a.advance(&mut wa));
+        assert!(b.advance(&mut wb));
+    }
+    assert_eq!(wa.state_hash(), wb.state_hash());
+    assert_eq!(a.desync(), None);
+
+    // an edit outside the session shows up in the next hash exchange
+    wb.fill_rect(Rect::new(0, 0, 4, 4), Element::Wall);
+    for frame in in_flight.drain(..) {
+        a.receive(NetFrame::from_bytes(&frame).unwrap());
+    }
+    for _ in 0..4 {
+        let (fa, fb) = (a.local_frame(), b.local_frame());
+        b.receive(fa);
+        a.receive(fb);
+        a.advance(&mut wa);
+        b.advance(&mut wb);
+    }
+    assert_eq!(a.desync().map(|d| d.peer), Some(1));
+    assert_eq!(b.desync().map(|d| d.peer), Some(0));
+}
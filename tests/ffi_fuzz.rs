@@ -0,0 +1,60 @@
+// Replays the FFI fuzz driver (fuzz/ffi_ops.rs) on seeded random scripts,
+// so the C ABI gets shaken on every `cargo test` and not only under
+// `cargo fuzz`. A failing case prints its script; save it as a file in
+// fuzz/corpus/ffi to keep it around.
+
+#[path = "../fuzz/ffi_ops.rs"]
+mod ffi_ops;
+
+/// Tiny xorshift64 so the cases are reproducible without extra crates.
+struct Gen(u64);
+
+impl Gen {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn bytes(&mut self, n: usize) -> Vec<u8> {
+        (0..n).map(|_| self.next() as u8).collect()
+    }
+}
+
+fn run_case(script: &[u8]) {
+    let result = std::panic::catch_unwind(|| ffi_ops::run(script));
+    if let Err(e) = result {
+        eprintln!("failing script: {script:?}");
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+fn random_scripts() {
+    for case in 1..=1000u64 {
+        let mut g = Gen(case.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let len = (g.next() % 512) as usize;
+        run_case(&g.bytes(len));
+    }
+}
+
+#[test]
+fn hostile_arguments() {
+    // brush of radius i32::MAX at i32::MIN, in both edge modes
+    let mut brush = vec![9, 0xff, 0, 0, 0, 0x80, 0xff, 0, 0, 0, 0x80, 0xff];
+    brush.extend_from_slice(&i32::MAX.to_le_bytes());
+    brush.push(3);
+    run_case(&brush);
+    let mut wrapped = vec![14, 2];
+    wrapped.extend_from_slice(&brush);
+    run_case(&wrapped);
+
+    // resize to negative, huge and zero sizes, then step and render
+    for size in [i32::MIN, -1, 0, i32::MAX] {
+        let mut script = vec![8, 0xff];
+        script.extend_from_slice(&size.to_le_bytes());
+        script.extend_from_slice(&[7, 3, 7, 7, 21, 0x10, 0, 1, 19, 0x40, 0, 27]);
+        run_case(&script);
+    }
+}
@@ -0,0 +1,288 @@
+// Golden-state regression harness: canonical seeded scenes are stepped a
+// fixed number of ticks and their state_hash() compared with the values
+// recorded below. A refactor that shouldn't change behaviour (parallel
+// stepping, chunking, reordering rules) must leave every hash alone.
+//
+// When a change is meant to alter behaviour, the failure message lists the
+// new hash of every scene that moved; paste them in and say why in the
+// commit.
+
+use powdercore::{
+    Action, ChunkedWorld, Edge, EdgeMode, Element, PortKind, Rect, SimConfig, Weather, World,
+    WorldBuilder, WorldGen,
+};
+
+struct Scene {
+    name: &'static str,
+    /// Build the scene, step it and return the final hash.
+    run: fn() -> u64,
+    hash: u64,
+}
+
+const SCENES: &[Scene] = &[
+    Scene {
+        name: "powders",
+        run: powders,
+        hash: 0x9b282078f84b243b,
+    },
+    Scene {
+        name: "liquids",
+        run: liquids,
+        hash: 0x8f7bb068c6b165bf,
+    },
+    Scene {
+        name: "fire",
+        run: fire,
+        hash: 0x6d4a936e3fd1861c,
+    },
+    Scene {
+        name: "explosions",
+        run: explosions,
+        hash: 0x38967206707b0ecd,
+    },
+    Scene {
+        name: "lava",
+        run: lava,
+        hash: 0x90578da7f607eee3,
+    },
+    Scene {
+        name: "electricity",
+        run: electricity,
+        hash: 0x84a4b5a8c2895e1,
+    },
+    Scene {
+        name: "creatures",
+        run: creatures,
+        hash: 0xbbf4a130cee0dec5,
+    },
+    Scene {
+        name: "terrain",
+        run: terrain,
+        hash: 0x8b82a3d6aafc99eb,
+    },
+    Scene {
+        name: "weather",
+        run: weather,
+        hash: 0xd6cea6c6219f758b,
+    },
+    Scene {
+        name: "gravity_and_frozen",
+        run: gravity_and_frozen,
+        hash: 0x111de218226847c0,
+    },
+    Scene {
+        name: "cave_ins",
+        run: cave_ins,
+        hash: 0x1db172991b9900be,
+    },
+    Scene {
+        name: "edges_and_ports",
+        run: edges_and_ports,
+        hash: 0x90d01c011c78a65a,
+    },
+    Scene {
+        name: "chunked",
+        run: chunked,
+        hash: 0x8ab4a94e817ce5de,
+    },
+];
+
+#[test]
+fn golden_states() {
+    let mismatches: Vec<String> = SCENES
+        .iter()
+        .filter_map(|s| {
+            let got = (s.run)();
+            (got != s.hash).then(|| format!("  {}: expected {:#x}, got {:#x}", s.name, s.hash, got))
+        })
+        .collect();
+    assert!(
+        mismatches.is_empty(),
+        "golden state hashes changed:\n{}",
+        mismatches.join("\n")
+    );
+}
+
+fn stepped(mut w: World, ticks: u32) -> u64 {
+    for _ in 0..ticks {
+        w.step();
+    }
+    w.state_hash()
+}
+
+/// Empty box with a stone floor and wall sides.
+fn boxed(width: i32, height: i32, seed: u64) -> World {
+    WorldBuilder::new(width, height)
+        .seed(seed)
+        .floor(Element::Stone, 2)
+        .walls(Element::Wall)
+        .build()
+}
+
+fn powders() -> u64 {
+    let mut w = boxed(64, 48, 1);
+    w.fill_rect(Rect::new(20, 30, 24, 2), Element::Wall);
+    w.place_brush(16, 6, 5, Element::Sand);
+    w.place_brush(32, 4, 4, Element::Gunpowder);
+    w.place_brush(44, 8, 4, Element::Ash);
+    w.place_brush(52, 4, 3, Element::Snow);
+    stepped(w, 200)
+}
+
+fn liquids() -> u64 {
+    let mut w = boxed(64, 48, 2);
+    w.fill_rect(Rect::new(4, 4, 10, 10), Element::Water);
+    w.fill_rect(Rect::new(16, 4, 10, 10), Element::Oil);
+    w.fill_rect(Rect::new(28, 4, 8, 8), Element::SaltWater);
+    w.fill_rect(Rect::new(38, 4, 6, 6), Element::Mercury);
+    w.fill_rect(Rect::new(46, 4, 6, 6), Element::Ethanol);
+    w.fill_rect(Rect::new(30, 36, 20, 4), Element::Wood);
+    w.place_brush(40, 20, 3, Element::Acid);
+    stepped(w, 250)
+}
+
+fn fire() -> u64 {
+    let mut w = boxed(64, 48, 3);
+    w.fill_rect(Rect::new(6, 30, 20, 14), Element::Wood);
+    w.fill_rect(Rect::new(30, 36, 10, 8), Element::Coal);
+    w.fill_rect(Rect::new(42, 30, 4, 14), Element::Plant);
+    w.fill_rect(Rect::new(48, 40, 12, 4), Element::Oil);
+    w.place_brush(16, 28, 2, Element::Fire);
+    w.place_brush(54, 38, 1, Element::Fire);
+    stepped(w, 300)
+}
+
+fn explosions() -> u64 {
+    let mut w = boxed(64, 48, 4);
+    w.fill_rect(Rect::new(10, 36, 12, 8), Element::Gunpowder);
+    w.fill_rect(Rect::new(30, 10, 8, 8), Element::Hydrogen);
+    w.fill_rect(Rect::new(44, 10, 8, 8), Element::Gas);
+    w.fill_rect(Rect::new(4, 20, 56, 1), Element::Glass);
+    w.place_brush(16, 34, 1, Element::Fire);
+    w.place_brush(34, 20, 1, Element::Fire);
+    w.place_brush(48, 20, 1, Element::Fire);
+    stepped(w, 150)
+}
+
+fn lava() -> u64 {
+    let mut w = boxed(64, 48, 5);
+    w.fill_rect(Rect::new(4, 38, 16, 6), Element::Ice);
+    w.fill_rect(Rect::new(24, 38, 16, 6), Element::Water);
+    w.fill_rect(Rect::new(44, 38, 16, 6), Element::Sand);
+    w.place_brush(12, 10, 4, Element::Lava);
+    w.place_brush(32, 10, 4, Element::Lava);
+    w.place_brush(52, 10, 4, Element::Lava);
+    stepped(w, 300)
+}
+
+fn electricity() -> u64 {
+    let mut w = boxed(64, 48, 6);
+    w.fill_rect(Rect::new(4, 30, 40, 1), Element::Wire);
+    w.fill_rect(Rect::new(44, 26, 6, 6), Element::Metal);
+    w.fill_rect(Rect::new(50, 36, 10, 8), Element::Water);
+    w.fill_rect(Rect::new(20, 27, 6, 3), Element::Gunpowder);
+    w.place_brush(8, 2, 0, Element::Lightning);
+    w.place_brush(55, 2, 0, Element::Lightning);
+    stepped(w, 120)
+}
+
+fn creatures() -> u64 {
+    let mut w = boxed(96, 48, 7);
+    w.fill_rect(Rect::new(60, 36, 20, 10), Element::Water);
+    for x in (6..40).step_by(6) {
+        w.spawn(x, 44, Element::Human);
+    }
+    for x in (50..90).step_by(8) {
+        w.spawn(x, 30, Element::Zombie);
+    }
+    w.spawn(44, 44, Element::Animal);
+    w.spawn(4, 44, Element::HumanSpawner);
+    w.spawn(90, 44, Element::ZombieSpawner);
+    w.place_brush(30, 40, 2, Element::Fire);
+    stepped(w, 400)
+}
+
+fn terrain() -> u64 {
+    let mut w = World::new(128, 64, 8);
+    w.generate(8, &WorldGen::DEFAULT);
+    stepped(w, 100)
+}
+
+fn weather() -> u64 {
+    let mut w = boxed(64, 48, 9);
+    w.fill_rect(Rect::new(10, 36, 20, 8), Element::Wood);
+    w.set_weather(Weather::Rain { intensity: 200 });
+    w.schedule(
+        60,
+        Action::Weather {
+            weather: Weather::Meteors { intensity: 50 },
+        },
+    );
+    w.schedule(
+        120,
+        Action::Weather {
+            weather: Weather::Snow { intensity: 300 },
+        },
+    );
+    w.schedule_every(
+        10,
+        40,
+        Action::Spawn {
+            x: 50,
+            y: 4,
+            rad: 2,
+            elem: Element::Sand,
+        },
+    );
+    stepped(w, 200)
+}
+
+fn gravity_and_frozen() -> u64 {
+    let mut w = boxed(64, 48, 10);
+    w.add_gravity_zone(Rect::new(0, 0, 32, 48), 0, -1);
+    w.add_gravity_zone(Rect::new(32, 0, 16, 24), 1, 0);
+    w.fill_rect(Rect::new(8, 20, 10, 10), Element::Sand);
+    w.fill_rect(Rect::new(20, 20, 8, 8), Element::Water);
+    w.fill_rect(Rect::new(36, 4, 6, 6), Element::Oil);
+    w.fill_rect(Rect::new(50, 4, 8, 8), Element::Sand);
+    w.set_frozen_region(Rect::new(50, 4, 8, 4), true);
+    stepped(w, 150)
+}
+
+fn cave_ins() -> u64 {
+    let mut w = World::new(64, 48, 11);
+    w.set_config(SimConfig {
+        support_distance: 4,
+        ..SimConfig::DEFAULT
+    });
+    w.fill_rect(Rect::new(0, 28, 64, 20), Element::Stone);
+    w.fill_rect(Rect::new(8, 32, 20, 6), Element::Empty);
+    w.fill_rect(Rect::new(36, 32, 6, 6), Element::Empty);
+    w.fill_rect(Rect::new(0, 20, 64, 8), Element::Dirt);
+    w.fill_rect(Rect::new(46, 8, 2, 12), Element::Wood);
+    w.fill_rect(Rect::new(40, 8, 14, 1), Element::Wood);
+    stepped(w, 100)
+}
+
+fn edges_and_ports() -> u64 {
+    let mut w = World::new(64, 48, 12);
+    w.set_edge_mode(EdgeMode::Wrap);
+    w.fill_rect(Rect::new(0, 40, 64, 1), Element::Glass);
+    w.fill_rect(Rect::new(20, 10, 10, 10), Element::Sand);
+    w.fill_rect(Rect::new(40, 30, 10, 8), Element::Water);
+    w.place_brush(5, 5, 3, Element::Smoke);
+    w.add_port(Edge::Top, 50..54, PortKind::Source(Element::Water));
+    w.add_port(Edge::Bottom, 0..64, PortKind::Drain);
+    stepped(w, 200)
+}
+
+fn chunked() -> u64 {
+    let mut c = ChunkedWorld::new(13);
+    c.place_brush(10, 10, 4, Element::Sand);
+    c.place_brush(-70, 5, 3, Element::Water);
+    c.place_brush(90, -40, 3, Element::Gunpowder);
+    for _ in 0..120 {
+        c.step();
+    }
+    c.world().state_hash()
+}
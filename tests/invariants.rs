@@ -0,0 +1,471 @@
+// Property-style checks of World::check_invariants: random sequences of
+// brushes, edits and steps must never leave the world in a state that
+// breaks an invariant. proptest generates the edit sequences (and shrinks a
+// failing one down); the scripted scenarios further down draw from a seeded
+// xorshift so they replay exactly.
+
+use powdercore::{
+    default_life, Brush, BrushShape, Cell, ChunkedWorld, Command, EdgeMode, Element, MirrorAxis,
+    MixRule, PasteMode, Rect, SimConfig, Violation, World, WorldGen,
+};
+use proptest::prelude::*;
+
+/// Tiny xorshift64 so the cases are reproducible without extra crates.
+struct Gen(u64);
+
+impl Gen {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: i32) -> i32 {
+        (self.next() % n.max(1) as u64) as i32
+    }
+
+    fn elem(&mut self) -> Element {
+        Element::ALL[self.below(Element::COUNT as i32) as usize]
+    }
+
+    fn shape(&mut self) -> BrushShape {
+        match self.below(4) {
+            0 => BrushShape::Circle,
+            1 => BrushShape::Square,
+            2 => BrushShape::Ring,
+            _ => BrushShape::Spray { density: 40 },
+        }
+    }
+}
+
+/// One edit the way a frontend might make it. Coordinates can land off the
+/// grid; edits there must be clipped, not wrap into a bad state.
+#[derive(Clone, Debug)]
+enum Edit {
+    Brush {
+        x: i32,
+        y: i32,
+        rad: i32,
+        elem: Element,
+        shape: BrushShape,
+    },
+    Spawn {
+        x: i32,
+        y: i32,
+        elem: Element,
+    },
+    Fill {
+        rect: Rect,
+        elem: Element,
+    },
+    /// Raw write, bypassing put(): the tables are rebuilt next step.
+    Raw {
+        x: i32,
+        y: i32,
+        elem: Element,
+    },
+    Line {
+        from: (i32, i32),
+        to: (i32, i32),
+        elem: Element,
+    },
+}
+
+fn any_elem() -> impl Strategy<Value = Element> {
+    prop::sample::select(Element::ALL.to_vec())
+}
+
+fn any_shape() -> impl Strategy<Value = BrushShape> {
+    prop_oneof![
+        Just(BrushShape::Circle),
+        Just(BrushShape::Square),
+        Just(BrushShape::Ring),
+        (1..=100u8).prop_map(|density| BrushShape::Spray { density }),
+    ]
+}
+
+fn any_edit() -> impl Strategy<Value = Edit> {
+    let at = || -4..84i32;
+    prop_oneof![
+        6 => (at(), at(), 0..5i32, any_elem(), any_shape())
+            .prop_map(|(x, y, rad, elem, shape)| Edit::Brush { x, y, rad, elem, shape }),
+        1 => (at(), at(), any_elem()).prop_map(|(x, y, elem)| Edit::Spawn { x, y, elem }),
+        1 => (at(), at(), 0..12i32, 0..12i32, any_elem()).prop_map(|(x, y, w, h, elem)| {
+            Edit::Fill {
+                rect: Rect::new(x, y, w, h),
+                elem,
+            }
+        }),
+        1 => (at(), at(), any_elem()).prop_map(|(x, y, elem)| Edit::Raw { x, y, elem }),
+        1 => ((at(), at()), (at(), at()), any_elem())
+            .prop_map(|(from, to, elem)| Edit::Line { from, to, elem }),
+    ]
+}
+
+fn apply_edit(w: &mut World, edit: &Edit) {
+    match *edit {
+        Edit::Brush {
+            x,
+            y,
+            rad,
+            elem,
+            shape,
+        } => w.place_brush_shape(x, y, rad, elem, shape),
+        Edit::Spawn { x, y, elem } => {
+            w.spawn(x, y, elem);
+        }
+        Edit::Fill { rect, elem } => w.fill_rect(rect, elem),
+        Edit::Raw { x, y, elem } => {
+            if let Some(c) = w.get_cell_mut(x, y) {
+                *c = Cell {
+                    elem,
+                    life: default_life(elem),
+                };
+            }
+        }
+        Edit::Line { from, to, elem } => w.draw_line(from.0, from.1, to.0, to.1, elem),
+    }
+}
+
+fn check(w: &World, case: u64, tick: u32) {
+    if let Err(v) = w.check_invariants() {
+        panic!("case {case}, tick {tick}: {v}");
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    /// Each edit is followed by a few steps, checking after every one.
+    #[test]
+    fn random_edits_keep_invariants(
+        width in 48..80i32,
+        height in 32..56i32,
+        seed in any::<u64>(),
+        wrap in any::<bool>(),
+        generate in any::<bool>(),
+        edits in prop::collection::vec((any_edit(), 1..6u32), 0..60),
+    ) {
+        let mut w = World::new(width, height, seed);
+        if wrap {
+            w.set_edge_mode(EdgeMode::Wrap);
+        }
+        if generate {
+            w.generate(seed, &WorldGen::DEFAULT);
+        }
+        let mut tick = 0;
+        for (edit, steps) in &edits {
+            apply_edit(&mut w, edit);
+            for _ in 0..*steps {
+                w.step();
+                if let Err(v) = w.check_invariants() {
+                    return Err(TestCaseError::fail(format!("tick {tick}, after {edit:?}: {v}")));
+                }
+                tick += 1;
+            }
+        }
+        w.validate();
+    }
+}
+
+/// A random command for World::apply, anywhere near the world.
+fn random_command(w: &World, g: &mut Gen) -> Command {
+    let (x, y) = (g.below(w.width() + 20) - 10, g.below(w.height() + 20) - 10);
+    let rect = Rect::new(x, y, g.below(16), g.below(16));
+    match g.below(4) {
+        0 => Command::Brush {
+            x,
+            y,
+            elem: g.elem(),
+            brush: Brush {
+                shape: g.shape(),
+                ..Brush::new(g.below(6))
+            },
+        },
+        1 => Command::Fill {
+            rect,
+            elem: g.elem(),
+        },
+        2 => Command::Stamp {
+            stamp: w.copy_region(rect),
+            x: g.below(w.width()),
+            y: g.below(w.height()),
+            mode: [PasteMode::Overwrite, PasteMode::Merge, PasteMode::Under][g.below(3) as usize],
+        },
+        _ => Command::Replace {
+            rect,
+            from: g.elem(),
+            to: g.elem(),
+        },
+    }
+}
+
+#[test]
+fn undo_restores_applied_commands() {
+    for case in 1..=8u64 {
+        let mut g = Gen(case.wrapping_mul(0x2545_f491_4f6c_dd1d));
+        let mut w = World::new(40 + g.below(24), 30 + g.below(16), case);
+        if case % 2 == 0 {
+            w.set_edge_mode(EdgeMode::Wrap);
+        }
+        w.generate(case, &WorldGen::DEFAULT);
+        let before = w.cells().to_vec();
+        for _ in 0..40 {
+            let cmd = random_command(&w, &mut g);
+            assert_eq!(Command::from_bytes(&cmd.to_bytes()).as_ref(), Ok(&cmd));
+            w.apply(cmd);
+        }
+        while w.undo().is_some() {}
+        assert!(w.cells() == before, "case {case}: undo left cells changed");
+        check(&w, case, 0);
+    }
+}
+
+#[test]
+fn undo_survives_chunked_growth_and_restore() {
+    let mut c = ChunkedWorld::new(3);
+    let (x, y) = c.to_local(10, 10);
+    c.world_mut().apply(Command::Fill {
+        rect: Rect::new(x, y, 4, 4),
+        elem: Element::Wall,
+    });
+    c.place_brush(10, 30, 2, Element::Stone);
+    // grow up and to the left, shifting every local coordinate
+    assert!(c.ensure(Rect::new(-100, -100, 1, 1)));
+    assert_eq!(c.get_cell(11, 11).elem, Element::Wall);
+    assert!(c.world_mut().undo().is_some());
+    assert_eq!(c.get_cell(11, 11).elem, Element::Empty);
+    assert_eq!(c.get_cell(10, 30).elem, Element::Stone);
+
+    let mut w = World::new(16, 16, 1);
+    let snap = World::new(8, 8, 2).snapshot();
+    w.apply(Command::Fill {
+        rect: Rect::new(0, 0, 4, 4),
+        elem: Element::Sand,
+    });
+    w.restore(&snap);
+    assert_eq!(w.undo_count(), 0);
+}
+
+#[test]
+fn rows_of_a_zero_width_wrapped_world_are_empty() {
+    let mut w = World::new(0, 5, 1);
+    w.set_edge_mode(EdgeMode::Wrap);
+    assert!(w.row(2).is_empty());
+    assert!(w.row(5).is_empty());
+}
+
+#[test]
+fn lines_from_far_off_grid_are_clipped() {
+    for mode in [EdgeMode::Solid, EdgeMode::Wrap] {
+        let mut w = World::new(32, 16, 1);
+        w.set_edge_mode(mode);
+        w.draw_line(i32::MIN, 5, i32::MAX, 5, Element::Wall);
+        assert!(w.row(5).iter().all(|c| c.elem == Element::Wall));
+        w.draw_line(i32::MIN, i32::MIN, i32::MAX, i32::MAX, Element::Wall);
+        w.stroke_brush(i32::MAX, i32::MAX, i32::MIN, 0, i32::MAX, Element::Sand);
+        assert!(w.check_invariants().is_ok());
+    }
+}
+
+#[test]
+fn huge_circle_outlines_are_clipped() {
+    let mut w = World::new(32, 16, 1);
+    w.draw_circle_outline(0, 0, 3, Element::Wall);
+    let walls: Vec<_> = (0..4)
+        .flat_map(|y| (0..4).map(move |x| (x, y)))
+        .filter(|&(x, y)| w.get_cell(x, y).elem == Element::Wall)
+        .collect();
+    assert_eq!(walls, [(3, 0), (3, 1), (2, 2), (0, 3), (1, 3)]);
+
+    w.draw_circle_outline(i32::MIN, i32::MAX, i32::MAX, Element::Wall);
+    w.draw_circle_outline(16, 8, i32::MAX, Element::Wall);
+    assert!(w.check_invariants().is_ok());
+}
+
+#[test]
+fn region_edits_ignore_cells_off_the_edge() {
+    let mut w = World::new(4, 1, 1);
+    w.fill_rect(Rect::new(0, 0, 4, 1), Element::Wall);
+    w.mirror_region(Rect::new(-2, 0, 4, 1), MirrorAxis::X);
+    for x in 0..4 {
+        assert_eq!(w.get_cell(x, 0).elem, Element::Wall, "x = {x}");
+    }
+
+    let mut w = World::new(4, 4, 1);
+    w.fill_rect(Rect::new(0, 0, 4, 4), Element::Wall);
+    w.rotate_region(Rect::new(2, 2, 4, 4), 1);
+    assert!(w.cells().iter().all(|c| c.elem == Element::Wall));
+
+    let mut w = World::new(4, 1, 1);
+    w.set_edge_mode(EdgeMode::Wrap);
+    w.fill_rect(Rect::new(0, 0, 1, 1), Element::Sand);
+    w.mirror_region(Rect::new(-2, 0, 4, 1), MirrorAxis::X);
+    assert_eq!(w.get_cell(3, 0).elem, Element::Sand);
+    assert_eq!(w.get_cell(0, 0).elem, Element::Empty);
+}
+
+#[test]
+fn actors_take_one_hazard_hit_per_tick() {
+    let health_after_one_step = |hazards: &[(i32, i32)], elem: Element| {
+        let mut w = World::new(7, 7, 1);
+        w.fill_rect(Rect::new(0, 6, 7, 1), Element::Wall);
+        for &(x, y) in hazards {
+            // frozen hot water: hurts on contact but, unlike lava, doesn't
+            // also set zombies alight
+            w.fill_rect(Rect::new(x, y, 1, 1), Element::Water);
+            w.get_cell_mut(x, y).unwrap().life = 50;
+            w.set_frozen_region(Rect::new(x, y, 1, 1), true);
+        }
+        w.spawn(3, 5, elem);
+        w.step();
+        let health = w.actors().map(|(_, _, a)| a.health).next();
+        health
+    };
+    for elem in [Element::Human, Element::Zombie] {
+        let one = health_after_one_step(&[(2, 5)], elem);
+        let many = health_after_one_step(&[(2, 4), (2, 5), (4, 4), (4, 5)], elem);
+        assert!(one.is_some());
+        assert_eq!(many, one, "{elem:?}");
+    }
+}
+
+#[test]
+fn creatures_keep_actor_table_in_sync() {
+    for case in 1..=8u64 {
+        let mut g = Gen(case);
+        let mut w = World::new(64, 40, case);
+        w.fill_rect(Rect::new(0, 36, 64, 4), Element::Stone);
+        w.fill_rect(Rect::new(40, 28, 16, 8), Element::Water);
+        for _ in 0..20 {
+            let elem = [Element::Human, Element::Zombie, Element::Animal][g.below(3) as usize];
+            w.spawn(g.below(64), g.below(36), elem);
+        }
+        for tick in 0..400 {
+            if tick % 50 == 0 {
+                let hazard = [Element::Fire, Element::Acid, Element::Lava][g.below(3) as usize];
+                w.place_brush(g.below(64), g.below(20), 2, hazard);
+            }
+            w.step();
+            check(&w, case, tick);
+        }
+    }
+}
+
+#[test]
+fn chunked_growth_keeps_invariants() {
+    let mut g = Gen(99);
+    let mut c = ChunkedWorld::new(99);
+    for tick in 0..200 {
+        if tick % 10 == 0 {
+            let (x, y) = (g.below(400) - 200, g.below(300) - 150);
+            c.place_brush(x, y, 3, g.elem());
+        }
+        c.step();
+        check(c.world(), 0, tick);
+    }
+}
+
+#[test]
+fn violations_are_detected() {
+    let mut w = World::new(8, 8, 1);
+    *w.get_cell_mut(1, 1).unwrap() = Cell {
+        elem: Element::Sand,
+        life: -3,
+    };
+    assert_eq!(
+        w.check_invariants(),
+        Err(Violation::NegativeLife {
+            x: 1,
+            y: 1,
+            elem: Element::Sand,
+            life: -3
+        })
+    );
+
+    let mut w = World::new(8, 8, 1);
+    *w.get_cell_mut(2, 3).unwrap() = Cell {
+        elem: Element::Fire,
+        life: 0,
+    };
+    assert_eq!(
+        w.check_invariants(),
+        Err(Violation::SpentFire {
+            x: 2,
+            y: 3,
+            life: 0
+        })
+    );
+    w.get_cell_mut(2, 3).unwrap().life = 5;
+    assert_eq!(w.check_invariants(), Ok(()));
+
+    // timers may run below zero
+    let mut w = World::new(8, 8, 1);
+    *w.get_cell_mut(0, 0).unwrap() = Cell {
+        elem: Element::Smoke,
+        life: -1,
+    };
+    assert_eq!(w.check_invariants(), Ok(()));
+}
+
+#[test]
+fn mixing_liquids_keeps_their_volume() {
+    let tank = |top: Element, bottom: Element| {
+        let mut w = World::new(24, 24, 5);
+        w.fill_rect(Rect::new(0, 0, 24, 24), Element::Wall);
+        w.fill_rect(Rect::new(1, 1, 22, 22), Element::Empty);
+        w.fill_rect(Rect::new(1, 8, 22, 6), top);
+        w.fill_rect(Rect::new(1, 14, 22, 8), bottom);
+        for _ in 0..200 {
+            w.step();
+        }
+        assert_eq!(w.check_invariants(), Ok(()));
+        w.measure(Rect::new(0, 0, 24, 24))
+    };
+
+    let m = tank(Element::Ethanol, Element::Water);
+    assert!(m.count(Element::DilutedEthanol) > 0);
+    assert_eq!(m.liquid_volume, 22 * 14);
+
+    // acid either dilutes or, as it always has, turns to salt water and
+    // boils some water off, so only the products are checked
+    let m = tank(Element::Acid, Element::Water);
+    assert!(m.count(Element::WeakAcid) > 0);
+    assert!(m.count(Element::SaltWater) > 0);
+
+    // no rule: oil just floats
+    let m = tank(Element::Oil, Element::Water);
+    assert_eq!(m.count(Element::Oil), 22 * 6);
+    assert_eq!(m.count(Element::Water), 22 * 8);
+    assert_eq!(m.liquid_volume, 22 * 14);
+}
+
+#[test]
+fn mix_pairs_roll_once_per_tick() {
+    // a lone, settled pair with a 10% rule should mix after ~10 ticks on
+    // average; rolling from both cells would make it ~5
+    const RULES: &[MixRule] = &[MixRule {
+        a: Element::Water,
+        b: Element::Ethanol,
+        chance: 10,
+        a_into: Element::DilutedEthanol,
+        b_into: Element::DilutedEthanol,
+    }];
+    let mut ticks = 0;
+    for seed in 0..500 {
+        let mut w = World::new(1, 2, seed);
+        w.set_config(SimConfig {
+            mix_rules: RULES,
+            ..SimConfig::DEFAULT
+        });
+        w.fill_rect(Rect::new(0, 0, 1, 1), Element::Ethanol);
+        w.fill_rect(Rect::new(0, 1, 1, 1), Element::Water);
+        while w.get_cell(0, 1).elem == Element::Water {
+            w.step();
+            ticks += 1;
+        }
+    }
+    let mean = ticks as f64 / 500.0;
+    assert!((8.0..12.0).contains(&mean), "mean {mean}");
+}